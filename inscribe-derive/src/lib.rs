@@ -1,59 +1,195 @@
-use proc_macro2::TokenStream;
-use syn::{Attribute, AttrStyle, Data, DataStruct, DeriveInput, Field, Fields, Ident, Meta, Token};
+use proc_macro2::{Span, TokenStream};
+use syn::{Attribute, AttrStyle, Data, DataEnum, DeriveInput, Expr, ExprLit, Field, Fields, Ident, Index, Lit, LitInt, LitStr, Member, Meta, Path, Token};
+use quote::format_ident;
 use syn::punctuated::Punctuated;
+use syn::spanned::Spanned;
 use quote::quote;
-use std::collections::HashMap;
+use std::fmt;
 
 const INSCRIBE_LENGTH: usize = 64;
 const INSCRIBE_HANDLING_IDENT: &str = "inscribe";
 const INSCRIBE_ADDL_IDENT: &str = "inscribe_addl";
+const INSCRIBE_ADDL_BYTES_IDENT: &str = "inscribe_addl_bytes";
 const INSCRIBE_MARK_IDENT: &str = "inscribe_mark";
+const INSCRIBE_LENGTH_IDENT: &str = "inscribe_length";
+const INSCRIBE_BACKEND_IDENT: &str = "inscribe_backend";
 const INSCRIBE_NAME_IDENT: &str = "inscribe_name";
+const INSCRIBE_DISCRIMINANT_IDENT: &str = "inscribe_discriminant";
 const SKIP_IDENT: &str = "skip";
 const SERIALIZE_IDENT: &str = "serialize";
 const RECURSE_IDENT: &str = "recurse";
-
-// The three derive options for each struct member: inscribe it, serialize it, or skip it.
+const BYTES_IDENT: &str = "bytes";
+const SKIP_IF_EMPTY_IDENT: &str = "skip_if_empty";
+const COMPUTE_WITH_IDENT: &str = "compute_with";
+const SERIALIZE_WITH_IDENT: &str = "serialize_with";
+const ORDERED_IDENT: &str = "ordered";
+const DENY_UNJUSTIFIED_SKIP_IDENT: &str = "deny_unjustified_skip";
+const BIND_NAMES_IDENT: &str = "bind_names";
+const REASON_IDENT: &str = "reason";
+
+// The derive options for each struct member: inscribe it, serialize it (with `bcs` or a given
+// function), absorb its raw bytes, skip it (optionally with a stated reason), serialize it only
+// when it isn't empty, or inscribe a value computed by a method on the struct in its place.
 enum Handling {
     Recurse,
     Serialize,
-    Skip
+    SerializeWith(Path),
+    Bytes,
+    Skip(Option<LitStr>),
+    SkipIfEmpty,
+    Compute(Path),
+}
+
+// Members are sorted by name, unless they are unnamed tuple members, which keep their position.
+// Positional members come before named ones.
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord)]
+enum SortKey {
+    Position(usize),
+    Name(String),
+}
+
+impl fmt::Display for SortKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SortKey::Position(index) => write!(f, "{}", index),
+            SortKey::Name(name) => write!(f, "{}", name),
+        }
+    }
 }
 
 struct MemberInfo {
-    handling:   Handling,
-    name_ident: Ident,
-    sort_ident: Ident,
+    handling: Handling,
+    member:   Member,
+    sort_key: SortKey,
+    span:     Span,
+    position: usize,
 }
 
-fn parse_contained_ident(attr: &Attribute) -> Option<Ident> {
-    // Get the nested attribute data
-    let nested = match attr.parse_args_with(Punctuated::<Meta, Token![,]>::parse_terminated) {
-        Ok(parse_result) => parse_result,
-        Err(_) => { return None; },
-    };
+// Malformed attributes are reported as compile errors pointing at the offending tokens, rather
+// than as proc-macro panics.
+fn parse_nested(attr: &Attribute) -> syn::Result<Punctuated<Meta, Token![,]>> {
+    let nested = attr.parse_args_with(Punctuated::<Meta, Token![,]>::parse_terminated)?;
+    if nested.len() != 1 {
+        return Err(syn::Error::new_spanned(attr, "Expected exactly one argument"));
+    }
+    Ok(nested)
+}
+
+// Names and marks can be given as an identifier, or as a string literal for text that isn't a
+// valid identifier (such as text with spaces or hyphens).
+enum NameSpec {
+    Ident(Ident),
+    Literal(LitStr),
+}
+
+fn parse_name_spec(attr: &Attribute) -> syn::Result<NameSpec> {
+    // `#[attr = "text"]`
+    if let Meta::NameValue(name_value) = &attr.meta {
+        return match &name_value.value {
+            Expr::Lit(ExprLit { lit: Lit::Str(literal), .. }) => Ok(NameSpec::Literal(literal.clone())),
+            other => Err(syn::Error::new_spanned(other, "Expected a string literal")),
+        };
+    }
+
+    // `#[attr("text")]`
+    if let Ok(literal) = attr.parse_args::<LitStr>() {
+        return Ok(NameSpec::Literal(literal));
+    }
+
+    // `#[attr(ident)]`
+    parse_contained_ident(attr).map(NameSpec::Ident)
+}
+
+fn parse_contained_ident(attr: &Attribute) -> syn::Result<Ident> {
+    let nested = parse_nested(attr)?;
 
     // This was originally a for loop, but clippy noted that it never actually loops, so it
     // has been replaced with an if-let construction. This may be something to watch if the
     // metadata API changes.
-    if let Some(meta) = nested.iter().next() {
-        match meta {
-            Meta::Path(path) => { return Some(path.get_ident().unwrap().clone()); },
-            _ => { },
+    if let Some(Meta::Path(path)) = nested.iter().next() {
+        if let Some(ident) = path.get_ident() {
+            return Ok(ident.clone());
         }
     };
 
-    None
+    Err(syn::Error::new_spanned(&nested, "Expected an identifier or string literal"))
 }
 
-fn get_member_info(field: &Field) -> MemberInfo {
-    // By default: handling is recursive, and the name is the field name
+fn parse_handling(attr: &Attribute) -> syn::Result<Handling> {
+    let nested = attr.parse_args_with(Punctuated::<Meta, Token![,]>::parse_terminated)?;
+
+    // `skip, reason = "..."` is the only handling that takes a second argument
+    if let (2, Some(Meta::Path(path)), Some(Meta::NameValue(name_value))) = (nested.len(), nested.first(), nested.last()) {
+        if path.is_ident(SKIP_IDENT) && name_value.path.is_ident(REASON_IDENT) {
+            return match &name_value.value {
+                Expr::Lit(ExprLit { lit: Lit::Str(reason), .. }) if !reason.value().trim().is_empty() => {
+                    Ok(Handling::Skip(Some(reason.clone())))
+                },
+                other => Err(syn::Error::new_spanned(other, "Expected a non-empty string literal")),
+            };
+        }
+    }
+    if nested.len() != 1 {
+        return Err(syn::Error::new_spanned(attr, "Expected exactly one argument"));
+    }
+
+    match nested.iter().next() {
+        Some(Meta::Path(path)) => {
+            if path.is_ident(SKIP_IDENT) {
+                Ok(Handling::Skip(None))
+            } else if path.is_ident(SERIALIZE_IDENT) {
+                Ok(Handling::Serialize)
+            } else if path.is_ident(RECURSE_IDENT) {
+                Ok(Handling::Recurse)
+            } else if path.is_ident(BYTES_IDENT) {
+                Ok(Handling::Bytes)
+            } else if path.is_ident(SKIP_IF_EMPTY_IDENT) {
+                Ok(Handling::SkipIfEmpty)
+            } else {
+                Err(syn::Error::new_spanned(path,
+                    "Invalid handling specification; expected `skip`, `serialize`, `bytes`, `recurse`, \
+                     `skip_if_empty`, `compute_with = method`, or `serialize_with = function`"))
+            }
+        },
+        // `compute_with = path` names a method on the struct that produces the value to inscribe
+        Some(Meta::NameValue(name_value)) if name_value.path.is_ident(COMPUTE_WITH_IDENT) => {
+            match &name_value.value {
+                Expr::Path(expr_path) => Ok(Handling::Compute(expr_path.path.clone())),
+                other => Err(syn::Error::new_spanned(other, "Invalid method specification for compute_with")),
+            }
+        },
+        // `serialize_with = path` (or `"path"`) names a function that encodes the member's value
+        Some(Meta::NameValue(name_value)) if name_value.path.is_ident(SERIALIZE_WITH_IDENT) => {
+            match &name_value.value {
+                Expr::Path(expr_path) => Ok(Handling::SerializeWith(expr_path.path.clone())),
+                Expr::Lit(ExprLit { lit: Lit::Str(literal), .. }) => Ok(Handling::SerializeWith(literal.parse()?)),
+                other => Err(syn::Error::new_spanned(other, "Invalid function specification for serialize_with")),
+            }
+        },
+        Some(other) => Err(syn::Error::new_spanned(other,
+            "Invalid handling specification; expected `skip`, `serialize`, `bytes`, `recurse`, \
+             `skip_if_empty`, `compute_with = method`, or `serialize_with = function`")),
+        None => Err(syn::Error::new_spanned(attr, "Expected exactly one argument")),
+    }
+}
+
+fn get_member_info(field: &Field, position: usize) -> syn::Result<MemberInfo> {
+    // By default: handling is recursive, and the name is the field name (or, for tuple members,
+    // the field position)
     let mut member_handling = Handling::Recurse;
     let mut found_handling: bool = false;
     let mut found_name: bool = false;
-    let mut sort_name = match field.ident.clone() {
-        Some(k) => k,
-        None => { panic!("Couldn't get field name"); }
+    let member = match field.ident.clone() {
+        Some(k) => Member::Named(k),
+        None => Member::Unnamed(Index::from(position)),
+    };
+    let mut sort_key = match field.ident {
+        Some(ref k) => SortKey::Name(k.to_string()),
+        None => SortKey::Position(position),
+    };
+    let mut span = match field.ident {
+        Some(ref k) => k.span(),
+        None => field.ty.span(),
     };
 
     // Run over all the attributes
@@ -67,28 +203,14 @@ fn get_member_info(field: &Field) -> MemberInfo {
                 continue;
         }
 
-        // Parse out whatever is inside the attribute
-        let inside = match parse_contained_ident(&attr) {
-            Some(ident) => ident,
-            None => { panic!("Failed to parse member attribute for Inscribe trait"); }
-        };
-
         // Get handling specifications
         if attr.path().is_ident(INSCRIBE_HANDLING_IDENT) {
             // Don't process the same handling twice
             if found_handling {
-                panic!("Inscribe handling attribute defined more than once");
-            }
-
-            if inside.to_string() == String::from(SKIP_IDENT) {
-                member_handling = Handling::Skip;
-            } else if inside.to_string() == String::from(SERIALIZE_IDENT) {
-                member_handling = Handling::Serialize;
-            } else if inside.to_string() == String::from(RECURSE_IDENT) {
-                member_handling = Handling::Recurse;
-            } else {
-                panic!("Invalid handling specification");
+                return Err(syn::Error::new_spanned(&attr,
+                    "Inscribe handling attribute defined more than once"));
             }
+            member_handling = parse_handling(&attr)?;
             found_handling = true;
             continue;
         }
@@ -97,62 +219,188 @@ fn get_member_info(field: &Field) -> MemberInfo {
         if attr.path().is_ident(INSCRIBE_NAME_IDENT) {
             // Don't process the name twice
             if found_name {
-                panic!("Inscribe name attribute defined more than once");
+                return Err(syn::Error::new_spanned(&attr,
+                    "Inscribe name attribute defined more than once"));
             }
-            sort_name = inside.clone();
+            (sort_key, span) = match parse_name_spec(&attr)? {
+                NameSpec::Ident(name) => (SortKey::Name(name.to_string()), name.span()),
+                NameSpec::Literal(name) => (SortKey::Name(name.value()), name.span()),
+            };
             found_name = true;
             continue;
         }
     }
 
-    MemberInfo {
-        name_ident: field.ident.clone().unwrap(),
-        sort_ident: sort_name,
-        handling: member_handling
-    }
+    Ok(MemberInfo {
+        member,
+        sort_key,
+        handling: member_handling,
+        span,
+        position,
+    })
 }
 
-fn implement_get_inscription(dstruct: &DataStruct) -> TokenStream {
-    let members = match dstruct.fields.clone() {
-        Fields::Named(a) => a,
-        _ => { panic!("Invalid struct type"); }
-    };
+// The type-level options for a derived type.
+#[derive(Clone, Copy, Default)]
+struct TypeOptions {
+    // `#[inscribe(ordered)]` keeps members in declaration order rather than sorting them
+    ordered: bool,
+    // `#[inscribe(deny_unjustified_skip)]` requires every skipped member to state a reason
+    deny_unjustified_skip: bool,
+    // `#[inscribe(bind_names)]` absorbs each member's sort name ahead of its value
+    bind_names: bool,
+}
 
-    // Build hash table to match each of the struct member names to an associated MemberInfo
-    // struct
-    let mut member_table: HashMap<String, MemberInfo> = HashMap::new();
-    let mut member_vec: Vec<String> = Vec::new();
+fn get_type_options(ast: &DeriveInput) -> syn::Result<TypeOptions> {
+    let mut options = TypeOptions::default();
 
+    for attr in &ast.attrs {
+        if !attr.path().is_ident(INSCRIBE_HANDLING_IDENT) { continue; }
+
+        let nested = parse_nested(attr)?;
+        match nested.iter().next() {
+            Some(Meta::Path(path)) if path.is_ident(ORDERED_IDENT) => {
+                // Only one ordering is allowed
+                if options.ordered {
+                    return Err(syn::Error::new_spanned(attr, "Inscribe ordering attribute defined more than once"));
+                }
+                options.ordered = true;
+            },
+            Some(Meta::Path(path)) if path.is_ident(DENY_UNJUSTIFIED_SKIP_IDENT) => {
+                if options.deny_unjustified_skip {
+                    return Err(syn::Error::new_spanned(attr, "Inscribe skip policy attribute defined more than once"));
+                }
+                options.deny_unjustified_skip = true;
+            },
+            Some(Meta::Path(path)) if path.is_ident(BIND_NAMES_IDENT) => {
+                if options.bind_names {
+                    return Err(syn::Error::new_spanned(attr, "Inscribe name binding attribute defined more than once"));
+                }
+                options.bind_names = true;
+            },
+            Some(other) => {
+                return Err(syn::Error::new_spanned(other,
+                    "Invalid type-level specification; expected `ordered`, `deny_unjustified_skip`, or `bind_names`"));
+            },
+            None => { return Err(syn::Error::new_spanned(attr, "Expected exactly one argument")); },
+        }
+    }
+    Ok(options)
+}
 
-    for field in members.named.iter() {
-        let member_info = get_member_info(&field);
-        let sort_name_str = member_info.sort_ident.to_string();
+// Collects the member info for each struct (or enum variant) member, in the order in which the
+// members are added to the inscription: declaration order if `ordered` is set, and sorted
+// otherwise.
+fn get_sorted_members(fields: &Fields, options: TypeOptions) -> syn::Result<Vec<MemberInfo>> {
+    let mut members: Vec<MemberInfo> = fields.iter()
+        .enumerate()
+        .map(|(position, field)| get_member_info(field, position))
+        .collect::<syn::Result<_>>()?;
+
+    // A skipped member is left out of the transcript entirely, so under `deny_unjustified_skip`
+    // the omission has to be explained
+    if options.deny_unjustified_skip {
+        if let Some(member) = members.iter().find(|member| matches!(member.handling, Handling::Skip(None))) {
+            return Err(syn::Error::new(member.span,
+                format!("Skipped member `{}` needs a justification; use `#[inscribe(skip, reason = \"...\")]`",
+                    member.sort_key)));
+        }
+    }
+
+    members.sort_by(|a, b| a.sort_key.cmp(&b.sort_key));
 
-        member_table.insert(sort_name_str.clone(), member_info);
-        member_vec.push(sort_name_str);
+    // Two members with the same sort key would have an ambiguous order, and would be
+    // indistinguishable in `describe`, so they're rejected even if the order is fixed
+    for pair in members.windows(2) {
+        if pair[0].sort_key == pair[1].sort_key {
+            return Err(syn::Error::new(pair[1].span,
+                format!("Inscribe name `{}` used more than once", pair[1].sort_key)));
+        }
     }
 
-    // Now run through the elements in sorted order
+    if options.ordered {
+        members.sort_by_key(|member| member.position);
+    }
+    Ok(members)
+}
+
+// Builds the hash updates for a list of members. The `access` closure gives the expression used
+// to reach each member's value, which differs between structs and enum variants. With
+// `bind_names`, each member that contributes a value is preceded by its sort name.
+fn implement_members(members: &[MemberInfo], access: &dyn Fn(&Member) -> TokenStream, bind_names: bool) -> TokenStream {
     let mut center = quote!{};
-    member_vec.sort();
 
-    for sort_name in member_vec.iter() {
-        let current_member = member_table.get(sort_name).unwrap(); // Guaranteed to work
-        let member_ident = current_member.name_ident.clone();
+    for current_member in members.iter() {
+        let member = access(&current_member.member);
+        let member_name = quote!{#member}.to_string().replace(' ', "");
+        let sort_name = current_member.sort_key.to_string();
+        let name = if bind_names {
+            quote!{ hasher.update(#sort_name.as_bytes()); }
+        } else {
+            quote!{}
+        };
+        // Inscribed members with an empty inscription (such as `PhantomData` markers) add nothing,
+        // so they don't add their name either
+        let inscribed_name = |value: TokenStream| if bind_names {
+            quote!{
+                if #value.inscription_length() != 0 {
+                    #name
+                }
+            }
+        } else {
+            quote!{}
+        };
+
+        // Child inscriptions are streamed into the hasher, which saves an allocation per level
+        // for derived children. Hand-written children fall back to `get_inscription`, and are
+        // checked for the right length by the default `get_inscription_into`.
+        let serialize = quote!{
+            serial_out = match bcs::to_bytes(&#member) {
+                Ok(bvec) => bvec,
+                Err(e) => {
+                    return Err(decree::error::Error::new_serialization(
+                        format!("Could not serialize member {}: {}", #member_name, e)));
+                },
+            };
+            hasher.update(serial_out.as_slice());
+        };
 
         let elt = match current_member.handling {
-            Handling::Recurse => quote!{
-                let sub_inscription = self.#member_ident.get_inscription()?;
-                hasher.update(sub_inscription.as_slice());
+            Handling::Recurse => {
+                let name = inscribed_name(member.clone());
+                quote!{
+                    #name
+                    #member.get_inscription_into(&mut hasher)?;
+                }
             },
             Handling::Serialize => quote!{
-                serial_out = match bcs::to_bytes(&self.#member_ident) {
-                    Ok(bvec) => bvec,
-                    _ => { return Err(decree::error::Error::new_general("Could not serialize Value")); },
-                };
-                hasher.update(serial_out.as_slice());
+                #name
+                #serialize
+            },
+            Handling::SerializeWith(ref path) => quote!{
+                #name
+                hasher.update(#path(&#member)?.as_slice());
+            },
+            Handling::Bytes => quote!{
+                #name
+                hasher.update(AsRef::<[u8]>::as_ref(&#member));
+            },
+            Handling::Skip(_) => quote!{}, // Add nothing to the process
+            // An empty member leaves no trace, not even its name
+            Handling::SkipIfEmpty => quote!{
+                if !#member.is_empty() {
+                    #name
+                    #serialize
+                }
+            },
+            Handling::Compute(ref path) => {
+                let name = inscribed_name(quote!{ computed });
+                quote!{
+                    let computed = self.#path();
+                    #name
+                    computed.get_inscription_into(&mut hasher)?;
+                }
             },
-            Handling::Skip => quote!{}, // Add nothing to the process
         };
 
         // Integrate the hash update string into the overall routine
@@ -161,34 +409,205 @@ fn implement_get_inscription(dstruct: &DataStruct) -> TokenStream {
             #elt
         }
     }
+    center
+}
+
+// Enum variant fields are bound by reference in a match arm; the bindings get a prefix so that
+// they can't shadow the locals used by `get_inscription`.
+fn variant_binding(member: &Member) -> Ident {
+    format_ident!("__inscribe_{}", member)
+}
+
+// The tag each variant hashes ahead of its fields.
+struct VariantTag {
+    discriminant: u32,
+    name: String,
+}
+
+// Reads each variant's discriminant from something like `#[inscribe_discriminant(7)]`, defaulting
+// to the variant's declaration-order index, and its name from something like
+// `#[inscribe_name("hello")]`, defaulting to the variant's identifier. Discriminants must be
+// distinct, whether explicit or not.
+fn get_variant_tags(denum: &DataEnum) -> syn::Result<Vec<VariantTag>> {
+    let mut tags: Vec<VariantTag> = Vec::new();
+
+    for (index, variant) in denum.variants.iter().enumerate() {
+        let mut discriminant: Option<(u32, Span)> = None;
+        let mut name: Option<String> = None;
+        for attr in &variant.attrs {
+            if attr.path().is_ident(INSCRIBE_NAME_IDENT) {
+                // Only one name is allowed
+                if name.is_some() {
+                    return Err(syn::Error::new_spanned(attr, "Inscribe name attribute defined more than once"));
+                }
+                name = Some(match parse_name_spec(attr)? {
+                    NameSpec::Ident(name) => name.to_string(),
+                    NameSpec::Literal(name) => name.value(),
+                });
+                continue;
+            }
+            if !attr.path().is_ident(INSCRIBE_DISCRIMINANT_IDENT) { continue; }
+
+            // Only one discriminant is allowed
+            if discriminant.is_some() {
+                return Err(syn::Error::new_spanned(attr, "Inscribe discriminant attribute defined more than once"));
+            }
+            let literal: LitInt = attr.parse_args()?;
+            discriminant = Some((literal.base10_parse()?, literal.span()));
+        }
+
+        let (value, span) = discriminant.unwrap_or((index as u32, variant.ident.span()));
+        if tags.iter().any(|tag| tag.discriminant == value) {
+            return Err(syn::Error::new(span, format!("Inscribe discriminant {} used more than once", value)));
+        }
+        tags.push(VariantTag {
+            discriminant: value,
+            name: name.unwrap_or_else(|| variant.ident.to_string()),
+        });
+    }
+    Ok(tags)
+}
+
+// Each variant hashes its discriminant (by default, its declaration-order index) and its name
+// (by default, its identifier) ahead of its fields, so that variants with identical (or no)
+// fields still produce distinct inscriptions.
+fn implement_enum_members(denum: &DataEnum, options: TypeOptions) -> syn::Result<TokenStream> {
+    let mut arms = quote!{};
+    let tags = get_variant_tags(denum)?;
+
+    for (variant, VariantTag { discriminant, name: variant_name }) in denum.variants.iter().zip(tags) {
+        let variant_ident = &variant.ident;
+        let members = get_sorted_members(&variant.fields, options)?;
+        let center = implement_members(&members, &|member| {
+            let binding = variant_binding(member);
+            quote!{ (*#binding) }
+        }, options.bind_names);
+
+        let variant_members: Vec<Member> = variant.fields.members().collect();
+        let bindings: Vec<Ident> = variant_members.iter().map(variant_binding).collect();
+        let pattern = match variant.fields {
+            Fields::Named(_) => quote!{ Self::#variant_ident { #(#variant_members: #bindings),* } },
+            Fields::Unnamed(_) => quote!{ Self::#variant_ident ( #(#bindings),* ) },
+            Fields::Unit => quote!{ Self::#variant_ident },
+        };
+
+        arms = quote!{
+            #arms
+            #pattern => {
+                hasher.update(&#discriminant.to_le_bytes());
+                hasher.update(#variant_name.as_bytes());
+                #center
+            },
+        };
+    }
+
+    Ok(quote!{
+        match self {
+            #arms
+        }
+    })
+}
+
+fn implement_get_inscription(ast: &DeriveInput) -> syn::Result<TokenStream> {
+    // Run through the elements in sorted (or declaration) order
+    let options = get_type_options(ast)?;
+    let center = match ast.data {
+        Data::Struct(ref dstruct) => {
+            let members = get_sorted_members(&dstruct.fields, options)?;
+            implement_members(&members, &|member| quote!{ self.#member }, options.bind_names)
+        },
+        Data::Enum(ref denum) => implement_enum_members(denum, options)?,
+        Data::Union(_) => { return Err(syn::Error::new_spanned(&ast.ident, "Invalid type for derive(Inscribe)")); },
+    };
+
+    let length = get_inscription_length(ast)?;
+    let backend = get_backend(ast)?;
 
     // Now that we have all the relevant hash update lines in #center, we slap in in the middle
     // of a routine that sets up the various temporary values and performs the final hash
     // computation.
-    quote! {
-        fn get_inscription(&self) -> Result<Vec<u8>, decree::error::Error> {
-            use tiny_keccak::TupleHash;
-            use tiny_keccak::Hasher;
-            use bcs;
-            use serde::Serialize;
-            use decree::inscribe::InscribeBuffer;
-            use decree::decree::FSInput;
+    // The hash is computed the same way whether it is returned or streamed into a parent's
+    // hasher; only what happens to `hash_buf` differs.
+    let compute = quote! {
+        use decree::backend::HashBackend;
+        use bcs;
+        use serde::Serialize;
+        use decree::decree::FSInput;
 
-            let mut serial_out: Vec<u8> = Vec::new();
-            let mut hasher = TupleHash::v256(self.get_mark().as_bytes());
+        let mut serial_out: Vec<u8> = Vec::new();
+        let mut hasher = <#backend as HashBackend>::new_with_domain(self.get_mark().as_bytes());
 
-            // Add the struct members into the TupleHash
-            #center
+        // Add the struct members into the TupleHash
+        #center
 
-            // Add the final additional data
-            let additional = self.get_additional()?;
-            hasher.update(additional.as_slice());
+        // Add the final additional data
+        self.get_additional_into(&mut hasher)?;
+
+        let mut hash_buf = [0u8; #length];
+        hasher.finalize_into(&mut hash_buf);
+    };
 
-            let mut hash_buf: InscribeBuffer = [0u8; #INSCRIBE_LENGTH];
-            hasher.finalize(&mut hash_buf);
+    Ok(quote! {
+        fn get_inscription(&self) -> Result<Vec<u8>, decree::error::Error> {
+            #compute
             Ok(hash_buf.to_vec())
         }
+
+        fn get_inscription_into(&self, __inscribe_outer: &mut dyn decree::backend::HashBackend) -> Result<(), decree::error::Error> {
+            #compute
+            __inscribe_outer.update(&hash_buf);
+            Ok(())
+        }
+
+        fn inscription_length(&self) -> usize {
+            #length
+        }
+    })
+}
+
+// Reads the hash backend from something like `#[inscribe_backend(Blake3)]`, defaulting to
+// TupleHash. Bare names refer to the backends that ship with decree.
+fn get_backend(ast: &DeriveInput) -> syn::Result<TokenStream> {
+    let mut backend: Option<TokenStream> = None;
+
+    for attr in &ast.attrs {
+        if !attr.path().is_ident(INSCRIBE_BACKEND_IDENT) { continue; }
+
+        // Only one backend is allowed
+        if backend.is_some() {
+            return Err(syn::Error::new_spanned(attr, "Inscribe backend attribute defined more than once"));
+        }
+
+        let path: Path = attr.parse_args()?;
+        backend = Some(match path.get_ident() {
+            Some(ident) => quote!{ decree::backend::#ident },
+            None => quote!{ #path },
+        });
     }
+    Ok(backend.unwrap_or(quote!{ decree::backend::TupleHash }))
+}
+
+// Reads the output length from something like `#[inscribe_length(32)]`, defaulting to
+// `INSCRIBE_LENGTH`.
+fn get_inscription_length(ast: &DeriveInput) -> syn::Result<usize> {
+    let mut length: Option<usize> = None;
+
+    for attr in &ast.attrs {
+        if !attr.path().is_ident(INSCRIBE_LENGTH_IDENT) { continue; }
+
+        // Only one length is allowed
+        if length.is_some() {
+            return Err(syn::Error::new_spanned(attr, "Inscribe length attribute defined more than once"));
+        }
+
+        let literal: LitInt = attr.parse_args()?;
+        let value: usize = literal.base10_parse()?;
+        if value == 0 {
+            return Err(syn::Error::new_spanned(literal, "Inscription length must be nonzero"));
+        }
+        length = Some(value);
+    }
+    Ok(length.unwrap_or(INSCRIBE_LENGTH))
 }
 
 fn implement_default_mark(ast: &DeriveInput) -> TokenStream {
@@ -204,39 +623,163 @@ fn implement_default_mark(ast: &DeriveInput) -> TokenStream {
     get_mark
 }
 
-fn implement_get_addl(ast: &DeriveInput) -> TokenStream {
-    // In the absence of an outer attribute, we use the default implementation
-    let mut addl_implementation: TokenStream = quote!{};
+// A source of additional data for a struct: a method returning an owned vector, or a method
+// returning borrowed bytes.
+enum Additional {
+    Owned(Path),
+    Borrowed(Path),
+}
 
-    // Check the outer attributes for something like `#[inscribe_addl(addl_function)]`
-    for attr in &ast.attrs {
-        // We only look for "inscribe" attributes
-        if !attr.path().is_ident(INSCRIBE_ADDL_IDENT) { continue; }
+impl Additional {
+    fn path(&self) -> &Path {
+        match self {
+            Additional::Owned(path) | Additional::Borrowed(path) => path,
+        }
+    }
+}
 
-        let nested = match attr.parse_args_with(Punctuated::<Meta, Token![,]>::parse_terminated) {
-            Ok(parse_result) => {
-                parse_result
-            },
-            Err(_) => { panic!("Failed to parse inscribe_addl field attribute"); }
-        };
+// Collects the sources of additional data, in declaration order. With none, the trait default
+// is used.
+fn get_additional_sources(ast: &DeriveInput) -> syn::Result<Vec<Additional>> {
+    let mut sources = Vec::new();
+
+    // Check the outer attributes for something like `#[inscribe_addl(addl_function)]` or
+    // `#[inscribe_addl_bytes(addl_function)]`
+    for attr in &ast.attrs {
+        let borrowed = attr.path().is_ident(INSCRIBE_ADDL_BYTES_IDENT);
+        if !attr.path().is_ident(INSCRIBE_ADDL_IDENT) && !borrowed { continue; }
 
+        let nested = parse_nested(attr)?;
         if let Some(meta) = nested.iter().next() {
             match meta {
-                Meta::Path(path) => { addl_implementation = quote!{
-                    fn get_additional(&self) -> Result<Vec<u8>, decree::error::Error> {
-                        self.#path()
-                    }
-                 }},
-                _ => { panic!("Invalid metadata for field attribute"); },
+                Meta::Path(path) if borrowed => { sources.push(Additional::Borrowed(path.clone())); },
+                Meta::Path(path) => { sources.push(Additional::Owned(path.clone())); },
+                other => {
+                    return Err(syn::Error::new_spanned(other, "Expected the name of a method"));
+                },
             }
         }
+    }
+    Ok(sources)
+}
+
+fn implement_get_addl(ast: &DeriveInput) -> syn::Result<TokenStream> {
+    let sources = get_additional_sources(ast)?;
+
+    // In the absence of an outer attribute, we use the default implementation
+    if sources.is_empty() {
+        return Ok(quote!{});
+    }
 
-        break;
+    // The inscription absorbs each source as its own item. `get_additional` can only return one
+    // vector, so with several sources, each is framed with its length to keep the result
+    // unambiguous. Borrowed data goes straight into the hasher, skipping the copy that
+    // `get_additional` would make.
+    let get_additional = match sources.as_slice() {
+        [Additional::Owned(path)] => quote!{ self.#path() },
+        [Additional::Borrowed(path)] => quote!{ Ok(self.#path()?.to_vec()) },
+        _ => {
+            let appends = sources.iter().map(|source| {
+                let path = source.path();
+                quote!{
+                    let source = self.#path()?;
+                    additional.extend_from_slice(&(source.len() as u64).to_le_bytes());
+                    additional.extend_from_slice(&source);
+                }
+            });
+            quote!{
+                let mut additional = Vec::new();
+                #(#appends)*
+                Ok(additional)
+            }
+        },
+    };
+    let updates = sources.iter().map(|source| match source {
+        Additional::Owned(path) => quote!{ hasher.update(self.#path()?.as_slice()); },
+        Additional::Borrowed(path) => quote!{ hasher.update(self.#path()?); },
+    });
+
+    Ok(quote!{
+        fn get_additional(&self) -> Result<Vec<u8>, decree::error::Error> {
+            #get_additional
+        }
+
+        fn get_additional_into(&self, hasher: &mut dyn decree::backend::HashBackend) -> Result<(), decree::error::Error> {
+            #(#updates)*
+            Ok(())
+        }
+    })
+}
+
+// Describes each member on its own line, with the given indentation.
+#[cfg(feature = "describe")]
+fn describe_members(members: &[MemberInfo], indent: &str) -> String {
+    let mut body = String::new();
+    for member in members.iter() {
+        let handling = match member.handling {
+            Handling::Recurse => String::from(RECURSE_IDENT),
+            Handling::Serialize => String::from(SERIALIZE_IDENT),
+            Handling::SerializeWith(ref path) => {
+                format!("{}({})", SERIALIZE_WITH_IDENT, quote!{#path}.to_string().replace(' ', ""))
+            },
+            Handling::Bytes => String::from(BYTES_IDENT),
+            Handling::Skip(None) => String::from(SKIP_IDENT),
+            Handling::Skip(Some(ref reason)) => format!("{}({})", SKIP_IDENT, reason.value()),
+            Handling::SkipIfEmpty => String::from(SKIP_IF_EMPTY_IDENT),
+            Handling::Compute(ref path) => {
+                format!("{}({})", COMPUTE_WITH_IDENT, quote!{#path}.to_string().replace(' ', ""))
+            },
+        };
+        let member_name = &member.member;
+        let member_name = quote!{#member_name}.to_string();
+        body.push_str(&format!("{}{} ({}): {}\n", indent, member.sort_key, member_name, handling));
+    }
+    body
+}
+
+#[cfg(feature = "describe")]
+fn implement_describe(ast: &DeriveInput) -> syn::Result<TokenStream> {
+    // Everything but the mark is known at compile time, so we build the rest of the description
+    // here and just prepend the mark at runtime.
+    let mut body = String::new();
+    let options = get_type_options(ast)?;
+    if options.bind_names {
+        body.push_str(&format!("  {}\n", BIND_NAMES_IDENT));
+    }
+    match ast.data {
+        Data::Struct(ref dstruct) => {
+            body.push_str(&describe_members(&get_sorted_members(&dstruct.fields, options)?, "  "));
+        },
+        Data::Enum(ref denum) => {
+            for (variant, tag) in denum.variants.iter().zip(get_variant_tags(denum)?) {
+                body.push_str(&format!("  variant {} {} ({})\n", tag.discriminant, variant.ident, tag.name));
+                body.push_str(&describe_members(&get_sorted_members(&variant.fields, options)?, "    "));
+            }
+        },
+        Data::Union(_) => { return Err(syn::Error::new_spanned(&ast.ident, "Invalid type for derive(Inscribe)")); },
     }
-    addl_implementation
+    let sources = get_additional_sources(ast)?;
+    if sources.is_empty() {
+        body.push_str("  additional: none\n");
+    }
+    for source in sources.iter() {
+        let path = source.path();
+        body.push_str(&format!("  additional: {}\n", quote!{#path}.to_string().replace(' ', "")));
+    }
+
+    Ok(quote!{
+        fn describe(&self) -> String {
+            format!("{}\n{}", self.get_mark(), #body)
+        }
+    })
+}
+
+#[cfg(not(feature = "describe"))]
+fn implement_describe(_ast: &DeriveInput) -> syn::Result<TokenStream> {
+    Ok(quote!{})
 }
 
-fn implement_get_mark(ast: &DeriveInput) -> TokenStream {
+fn implement_get_mark(ast: &DeriveInput) -> syn::Result<TokenStream> {
     let mut found_mark: bool = false;
     let mut mark_implementation: TokenStream = quote!{};
 
@@ -245,68 +788,80 @@ fn implement_get_mark(ast: &DeriveInput) -> TokenStream {
         // We only look for "inscribe" attributes
         if !attr.path().is_ident(INSCRIBE_MARK_IDENT) { continue; }
 
-        let nested = match attr.parse_args_with(Punctuated::<Meta, Token![,]>::parse_terminated) {
-            Ok(parse_result) => {
-                parse_result
+        // Only one mark is allowed
+        if found_mark {
+            return Err(syn::Error::new_spanned(attr, "Inscribe mark attribute defined more than once"));
+        }
+
+        // An identifier names a method that returns the mark; a literal is the mark itself
+        mark_implementation = match parse_name_spec(attr)? {
+            NameSpec::Ident(method) => quote!{
+                fn get_mark(&self) -> &'static str {
+                    self.#method()
+                }
+            },
+            NameSpec::Literal(mark) => quote!{
+                fn get_mark(&self) -> &'static str {
+                    #mark
+                }
             },
-            Err(_) => { panic!("Failed to parse inscribe_mark field attribute"); }
         };
-
-        if let Some(meta) = nested.iter().next() {
-            match meta {
-                Meta::Path(path) => { mark_implementation = quote!{
-                    fn get_mark(&self) -> &'static str {
-                        self.#path()
-                    }
-                 }},
-                _ => { panic!("Invalid metadata for field attribute"); },
-            }
-        }
         found_mark = true;
-        break;
     }
     if found_mark {
-        mark_implementation
+        Ok(mark_implementation)
     } else {
-        implement_default_mark(ast)
+        Ok(implement_default_mark(ast))
     }
 }
 
-fn implement_inscribe_trait(ast: DeriveInput, dstruct: &DataStruct) -> TokenStream {
-    let get_mark: TokenStream = implement_get_mark(&ast);
-    let get_inscr: TokenStream = implement_get_inscription(dstruct);
-    let get_addl: TokenStream = implement_get_addl(&ast);
+fn implement_inscribe_trait(ast: DeriveInput) -> syn::Result<TokenStream> {
+    let get_mark: TokenStream = implement_get_mark(&ast)?;
+    let get_inscr: TokenStream = implement_get_inscription(&ast)?;
+    let get_addl: TokenStream = implement_get_addl(&ast)?;
+    let describe: TokenStream = implement_describe(&ast)?;
 
-    let ident = ast.ident;
-    let generics = ast.generics;
+    let ident = &ast.ident;
+    let (impl_generics, ty_generics, where_clause) = ast.generics.split_for_impl();
 
-    quote! {
-        impl #generics Inscribe for #ident #generics {
+    Ok(quote! {
+        impl #impl_generics Inscribe for #ident #ty_generics #where_clause {
 
             #get_mark
 
             #get_inscr
 
             #get_addl
+
+            #describe
         }
-    }
+    })
 }
 
 
-#[proc_macro_derive(Inscribe, attributes(inscribe, inscribe_addl, inscribe_mark, inscribe_name))]
+#[proc_macro_derive(Inscribe, attributes(inscribe, inscribe_addl, inscribe_addl_bytes, inscribe_backend, inscribe_discriminant, inscribe_length, inscribe_mark, inscribe_name))]
 pub fn inscribe_derive(item: proc_macro::TokenStream) -> proc_macro::TokenStream {
-    let ast: DeriveInput = syn::parse(item.clone()).unwrap();
+    let ast: DeriveInput = match syn::parse(item) {
+        Ok(ast) => ast,
+        Err(e) => { return e.to_compile_error().into(); },
+    };
 
-    // We don't support for derive for anything but structs
-    let dstruct = match ast.clone().data {
-        Data::Struct(d) => d,
-        _ => { panic!("Invalid type for derive(Inscribe)")},
+    // We support derive for structs with members and for enums
+    let supported = match ast.data {
+        Data::Struct(ref dstruct) => {
+            // We don't support unit structs
+            if matches!(dstruct.fields, Fields::Unit) {
+                Err(syn::Error::new_spanned(&ast.ident, "Unit structs not supported for derive(Inscribe)"))
+            } else {
+                Ok(())
+            }
+        },
+        Data::Enum(_) => Ok(()),
+        Data::Union(_) => Err(syn::Error::new_spanned(&ast.ident, "Unions not supported for derive(Inscribe)")),
     };
 
-    // We don't support unnamed structs
-    if !matches!(dstruct.fields, Fields::Named(_)) {
-        panic!("Unnamed structs not supported for derive(Inscribe)");
+    match supported.and_then(|_| implement_inscribe_trait(ast)) {
+        Ok(implementation) => implementation.into(),
+        Err(e) => e.to_compile_error().into(),
     }
-
-    implement_inscribe_trait(ast, &dstruct).into()
 }
\ No newline at end of file
@@ -0,0 +1,121 @@
+use tiny_keccak::Hasher;
+
+/// The `HashBackend` trait abstracts the hash function that derived `Inscribe` implementations
+/// absorb their members into. A backend is started with a domain (the struct's mark), absorbs a
+/// sequence of items, and is finalized into an output buffer of any length.
+///
+/// Backends must be injective over the sequence of items: splitting the same bytes into items
+/// differently must give a different output. TupleHash does this natively; other backends need to
+/// frame each item with its length.
+///
+/// The default backend is `TupleHash`; a derived struct can choose another with
+/// `#[inscribe_backend(...)]`. A bare name such as `Blake3` refers to a backend in this module,
+/// while a longer path names any other type implementing `HashBackend`.
+///
+/// Backends can be used as `&mut dyn HashBackend`, e.g. by `Inscribe::get_additional_into`; only
+/// `update` is available through a trait object.
+pub trait HashBackend {
+    /// Starts a new hash, customized with `domain`.
+    fn new_with_domain(domain: &[u8]) -> Self where Self: Sized;
+
+    /// Absorbs a single item.
+    fn update(&mut self, item: &[u8]);
+
+    /// Finishes the hash, filling `dest`. The output depends on the length of `dest`, so a
+    /// shorter output is not a prefix of a longer one.
+    fn finalize_into(self, dest: &mut [u8]) where Self: Sized;
+}
+
+/// The default backend: cSHAKE256-based TupleHash, as specified in NIST SP 800-185.
+pub struct TupleHash(tiny_keccak::TupleHash);
+
+impl HashBackend for TupleHash {
+    fn new_with_domain(domain: &[u8]) -> Self {
+        TupleHash(tiny_keccak::TupleHash::v256(domain))
+    }
+
+    fn update(&mut self, item: &[u8]) {
+        self.0.update(item);
+    }
+
+    fn finalize_into(self, dest: &mut [u8]) {
+        self.0.finalize(dest);
+    }
+}
+
+/// With the `blake3` feature, a backend based on BLAKE3 in extendable-output mode. BLAKE3 has no
+/// native tuple encoding, so the domain and each item are framed with their length as an 8-byte
+/// little-endian integer, and the output length is absorbed the same way before finalizing.
+///
+/// # Tests
+///
+/// ```
+/// # use decree::Inscribe;
+/// # use decree::error::DecreeResult;
+/// #[derive(Inscribe)]
+/// #[inscribe_mark(point_mark)]
+/// pub struct Point {
+///     #[inscribe(serialize)]
+///     x: i32,
+///     #[inscribe(serialize)]
+///     y: i32,
+/// }
+///
+/// #[derive(Inscribe)]
+/// #[inscribe_mark(point_mark)]
+/// #[inscribe_backend(Blake3)]
+/// pub struct Blake3Point {
+///     #[inscribe(serialize)]
+///     x: i32,
+///     #[inscribe(serialize)]
+///     y: i32,
+/// }
+///
+/// impl Point {
+///     fn point_mark(&self) -> &'static str { "point" }
+/// }
+///
+/// impl Blake3Point {
+///     fn point_mark(&self) -> &'static str { "point" }
+/// }
+///
+/// # fn main() -> DecreeResult<()> {
+/// let point = Point { x: 1, y: 2 };
+/// let blake3_point = Blake3Point { x: 1, y: 2 };
+///
+/// // Both backends are stable, but they don't agree with each other
+/// assert_eq!(point.get_inscription()?, Point { x: 1, y: 2 }.get_inscription()?);
+/// assert_eq!(blake3_point.get_inscription()?, Blake3Point { x: 1, y: 2 }.get_inscription()?);
+/// assert_ne!(point.get_inscription()?, blake3_point.get_inscription()?);
+/// assert_eq!(blake3_point.get_inscription()?.len(), 64);
+/// # Ok(())
+/// # }
+/// ```
+#[cfg(feature = "blake3")]
+pub struct Blake3(blake3::Hasher);
+
+#[cfg(feature = "blake3")]
+impl Blake3 {
+    fn absorb_framed(&mut self, item: &[u8]) {
+        self.0.update(&(item.len() as u64).to_le_bytes());
+        self.0.update(item);
+    }
+}
+
+#[cfg(feature = "blake3")]
+impl HashBackend for Blake3 {
+    fn new_with_domain(domain: &[u8]) -> Self {
+        let mut backend = Blake3(blake3::Hasher::new());
+        backend.absorb_framed(domain);
+        backend
+    }
+
+    fn update(&mut self, item: &[u8]) {
+        self.absorb_framed(item);
+    }
+
+    fn finalize_into(mut self, dest: &mut [u8]) {
+        self.0.update(&(dest.len() as u64).to_le_bytes());
+        self.0.finalize_xof().fill(dest);
+    }
+}
@@ -0,0 +1,79 @@
+use num_bigint::BigUint;
+use crate::error::{Error, DecreeResult};
+
+/// The `ChallengeFrom` trait describes how a typed challenge value is built from the raw bytes
+/// squeezed out of a `Decree` transcript. It is used by `Decree::get_challenge_as`, which draws
+/// exactly `CHALLENGE_LENGTH` bytes for the challenge and hands them to `from_challenge`.
+///
+/// Keeping the conversion here means the draw width and byte order are fixed in one place, rather
+/// than being repeated (and possibly varied) at every call site.
+///
+/// # Examples
+/// ```
+/// # use decree::decree::{Decree, InputLabel, ChallengeLabel};
+/// # use decree::error::DecreeResult;
+/// # use num_bigint::BigUint;
+/// # fn main() -> DecreeResult<()> {
+/// let inputs: [InputLabel; 1] = ["input1"];
+/// let challenges: [ChallengeLabel; 2] = ["challenge1", "challenge2"];
+/// let mut my_decree = Decree::new("testname", &inputs, &challenges)?;
+/// my_decree.add_serial("input1", 10u32)?;
+/// let c1: BigUint = my_decree.get_challenge_as("challenge1")?;
+/// let c2: [u8; 32] = my_decree.get_challenge_as("challenge2")?;
+/// # Ok(())
+/// # }
+/// ```
+pub trait ChallengeFrom: Sized {
+    /// The number of transcript bytes drawn for a single challenge.
+    const CHALLENGE_LENGTH: usize;
+
+    /// Builds the challenge value from exactly `CHALLENGE_LENGTH` bytes.
+    fn from_challenge(bytes: &[u8]) -> DecreeResult<Self>;
+}
+
+/// The byte order in which squeezed challenge bytes are read as an integer. Methods that don't
+/// take a `ByteOrder` use `LittleEndian`, which is also the default. The prover and verifier must
+/// agree on the order, so it should be fixed by the protocol specification.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ByteOrder {
+    /// The first byte squeezed is the least significant.
+    #[default]
+    LittleEndian,
+    /// The first byte squeezed is the most significant.
+    BigEndian,
+}
+
+/// Challenge bytes are interpreted as a 256-bit little-endian integer.
+impl ChallengeFrom for BigUint {
+    const CHALLENGE_LENGTH: usize = 32;
+
+    fn from_challenge(bytes: &[u8]) -> DecreeResult<Self> {
+        if bytes.len() != Self::CHALLENGE_LENGTH {
+            return Err(Error::new_invalid_challenge("Invalid challenge length"));
+        }
+        Ok(BigUint::from_bytes_le(bytes))
+    }
+}
+
+impl ChallengeFrom for [u8; 32] {
+    const CHALLENGE_LENGTH: usize = 32;
+
+    fn from_challenge(bytes: &[u8]) -> DecreeResult<Self> {
+        bytes.try_into()
+            .map_err(|_| Error::new_invalid_challenge("Invalid challenge length"))
+    }
+}
+
+/// With the `ristretto` feature, challenges are reduced to Ristretto255 scalars from a 64-byte
+/// draw, using `Scalar::from_bytes_mod_order_wide`. The wide reduction keeps the bias from the
+/// modular reduction negligible.
+#[cfg(feature = "ristretto")]
+impl ChallengeFrom for curve25519_dalek::Scalar {
+    const CHALLENGE_LENGTH: usize = 64;
+
+    fn from_challenge(bytes: &[u8]) -> DecreeResult<Self> {
+        let wide: [u8; 64] = bytes.try_into()
+            .map_err(|_| Error::new_invalid_challenge("Invalid challenge length"))?;
+        Ok(curve25519_dalek::Scalar::from_bytes_mod_order_wide(&wide))
+    }
+}
@@ -1,20 +1,219 @@
+use std::borrow::Cow;
 use std::collections::HashMap;
-use merlin::Transcript;
+use std::fmt;
+use std::sync::RwLock;
+use merlin::{Transcript, TranscriptRngBuilder};
+use num_bigint::BigUint;
 use bcs::to_bytes;
 use bcs;
 use serde::Serialize;
 pub use crate::{Inscribe};
+use crate::challenge::{ByteOrder, ChallengeFrom};
+use crate::inscribe::inscribe_sequence;
 use crate::error::{Error, DecreeResult};
+use crate::proof::Proof;
+use crate::transcript::LoggedTranscript;
+#[cfg(feature = "serde")]
+use std::sync::Arc;
+#[cfg(feature = "serde")]
+use crate::transcript::{intern, intern_str};
 
 pub type InputLabel = &'static str;
 pub type ChallengeLabel = &'static str;
 pub type ErrMsg = &'static str;
 pub type FSInput = Vec<u8>;
 
+// With the `zeroize` feature, stored inputs are wiped when they are dropped, since provers may add
+// secret witness material.
+#[cfg(feature = "zeroize")]
+type StoredInput = zeroize::Zeroizing<FSInput>;
+#[cfg(not(feature = "zeroize"))]
+type StoredInput = FSInput;
+
+/// Labels are stored as `Cow`s so that both `&'static str` and runtime-generated labels can be
+/// used without leaking memory. Label accessors return slices of them, which compare equal to
+/// slices of `&str`.
+pub type Label = Cow<'static, str>;
+
+const STRICT_LABEL_LABEL: &[u8] = b"decree-challenge-label";
+const STRICT_INDEX_LABEL: &[u8] = b"decree-challenge-index";
+const BINDING_NAME_LABEL: &[u8] = b"decree-name";
+const BINDING_INPUT_LABEL: &[u8] = b"decree-input-label";
+const BINDING_CHALLENGE_LABEL: &[u8] = b"decree-challenge-spec";
+const OPTIONAL_PRESENCE_LABEL: &[u8] = b"decree-optional-present";
+const OWNED_LABEL_LABEL: &[u8] = b"decree-owned-label";
+const OWNED_INPUT_LABEL: &[u8] = b"decree-owned-input";
+const OWNED_CHALLENGE_LABEL: &[u8] = b"decree-owned-challenge";
+const VERSION_LABEL: &[u8] = b"decree-version";
+const INPUT_FRAME_LABEL: &[u8] = b"decree-input-frame";
+const BATCH_LABEL_LABEL: &[u8] = b"decree-batch-label";
+const BATCH_INDEX_LABEL: &[u8] = b"decree-batch-index";
+const BATCH_COEFFICIENT_LABEL: &[u8] = b"decree-batch-coefficient";
+const SUBTRANSCRIPT_LABEL: &[u8] = b"decree-subtranscript";
+const SUBTRANSCRIPT_DIGEST_LENGTH: usize = 64;
+
+// Process-wide default limits, used by `Decree::new`. The limits are kept behind a single lock so
+// that a `Decree` never sees a mix of old and new limits while they are being changed.
+static GLOBAL_LIMITS: RwLock<DecreeLimits> = RwLock::new(DecreeLimits::UNLIMITED);
+
+/// Resource limits for a `Decree`, for services that build transcripts from untrusted protocol
+/// descriptions. The input and challenge limits apply to each stage, and are enforced by `new`
+/// and `extend`. The byte limit applies to the total size of all inputs added over the lifetime of
+/// the `Decree`, and is enforced when inputs are added.
+///
+/// The defaults are unlimited. `Decree::new` uses the process-wide limits, which can be changed
+/// with `DecreeLimits::set_global`; `Decree::new_with_limits` sets limits for a single `Decree`.
+///
+/// # Tests
+///
+/// Each limit is enforced
+/// ```
+/// # use decree::decree::{Decree, DecreeLimits, InputLabel, ChallengeLabel};
+/// # use decree::error::{Error, DecreeErrType, DecreeResult};
+/// # fn main() -> DecreeResult<()> {
+/// let limits = DecreeLimits { max_inputs: 2, max_challenges: 1, max_input_bytes: 8 };
+/// let inputs: [InputLabel; 2] = ["input1", "input2"];
+/// let challenges: [ChallengeLabel; 1] = ["challenge1"];
+///
+/// let too_many_inputs: [InputLabel; 3] = ["input1", "input2", "input3"];
+/// let err = Decree::new_with_limits("testname", &too_many_inputs, &challenges, limits);
+/// assert_eq!(err.err().unwrap().get_type(), DecreeErrType::InitFail);
+///
+/// let too_many_challenges: [ChallengeLabel; 2] = ["challenge1", "challenge2"];
+/// let err = Decree::new_with_limits("testname", &inputs, &too_many_challenges, limits);
+/// assert_eq!(err.err().unwrap().get_type(), DecreeErrType::InitFail);
+///
+/// let mut my_decree = Decree::new_with_limits("testname", &inputs, &challenges, limits)?;
+/// my_decree.add_serial("input1", 10u32)?;
+/// assert!(my_decree.add_serial("input2", 10u64).is_err());
+/// my_decree.add_serial("input2", 14u32)?;
+/// let mut challenge_out: [u8; 32] = [0u8; 32];
+/// my_decree.get_challenge("challenge1", &mut challenge_out)?;
+///
+/// let err = my_decree.extend(&too_many_inputs, &challenges);
+/// assert_eq!(err.err().unwrap().get_type(), DecreeErrType::ExtendFail);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DecreeLimits {
+    pub max_inputs: usize,
+    pub max_challenges: usize,
+    pub max_input_bytes: usize,
+}
+
+impl Default for DecreeLimits {
+    fn default() -> Self {
+        DecreeLimits::UNLIMITED
+    }
+}
+
+impl DecreeLimits {
+    const UNLIMITED: DecreeLimits = DecreeLimits {
+        max_inputs: usize::MAX,
+        max_challenges: usize::MAX,
+        max_input_bytes: usize::MAX,
+    };
+
+    /// Returns the process-wide limits used by `Decree::new`.
+    pub fn global() -> DecreeLimits {
+        *GLOBAL_LIMITS.read().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// Sets the process-wide limits used by `Decree::new`. All three limits are replaced at once.
+    /// Existing `Decree` structs keep the limits they were created with.
+    pub fn set_global(limits: DecreeLimits) {
+        *GLOBAL_LIMITS.write().unwrap_or_else(|poisoned| poisoned.into_inner()) = limits;
+    }
+}
+
+/// Whether a `TraceEntry` records a message absorbed by the transcript, or a challenge drawn
+/// from it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TraceKind {
+    Append,
+    Challenge,
+}
+
+/// A single transcript operation, as recorded by `Decree::enable_trace`. The value itself isn't
+/// kept, only its length and SHA3-256 digest, so a trace can be shared for debugging without
+/// exposing secret inputs.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TraceEntry {
+    pub kind: TraceKind,
+    pub label: String,
+    pub length: usize,
+    pub digest: [u8; 32],
+}
+
+/// Values accepted by `Decree::absorb`. `Inscribe` types are absorbed by their inscription, as
+/// with `Decree::add`, and values wrapped in `Serial` by their BCS serialization, as with
+/// `Decree::add_serial`. The trait is sealed, so those are the only two encodings.
+pub trait IntoFSInput: private::Sealed {
+    /// Adds the value to `decree` under `label`.
+    fn absorb_into(&self, decree: &mut Decree, label: &str) -> DecreeResult<()>;
+}
+
+/// Marks a `Serialize` value for `Decree::absorb`, which then adds it with `add_serial`. Unlike
+/// `inscribe::Serialized`, which inscribes the serialization, this adds the serialized bytes
+/// directly.
+#[derive(Clone, Copy, Debug)]
+pub struct Serial<T>(pub T);
+
+mod private {
+    pub trait Sealed {}
+    impl<T: crate::Inscribe + ?Sized> Sealed for T {}
+    impl<T: serde::Serialize> Sealed for super::Serial<T> {}
+}
+
+impl<T: Inscribe + ?Sized> IntoFSInput for T {
+    fn absorb_into(&self, decree: &mut Decree, label: &str) -> DecreeResult<()> {
+        decree.add(label, self)
+    }
+}
+
+impl<T: Serialize> IntoFSInput for Serial<T> {
+    fn absorb_into(&self, decree: &mut Decree, label: &str) -> DecreeResult<()> {
+        decree.add_serial(label, &self.0)
+    }
+}
+
+/// A `DecreeCheckpoint` is a snapshot of the Fiat-Shamir state of a `Decree` within its current
+/// stage, created by `Decree::checkpoint` and applied with `Decree::restore`. It holds copies of
+/// the transcript, the pending labels, and the inputs provided so far.
+#[derive(Clone)]
+pub struct DecreeCheckpoint {
+    inputs: Vec<Label>,
+    optional_inputs: Vec<Label>,
+    kept_inputs: Vec<Label>,
+    stage_challenges: Vec<Label>,
+    challenges: Vec<Label>,
+    values: HashMap<Label, StoredInput>,
+    transcript: LoggedTranscript,
+    committed: bool,
+    challenge_cache: Option<Vec<(Label, Vec<u8>)>>,
+    challenge_index: u64,
+    input_bytes: usize,
+    #[cfg(feature = "serde")]
+    history: Option<History>
+}
+
 /// A `Decree` struct is used to formalize (and enforce) Fiat-Shamir transforms. It sits atop a
 /// Merlin transcript, ensuring that required inputs are supplied before challenges are generated,
 /// and that challenges are generated in order.
 ///
+/// Cloning a `Decree` captures the exact Fiat-Shamir state at that point, including the
+/// transcript, the inputs absorbed so far, and the pending challenges. This allows a transcript to
+/// be forked after a common prefix, e.g. for batch verification or proof trees. To start over from
+/// the beginning of the protocol instead, use `clone_for_verification`.
+///
+/// When a stage's inputs are committed, each value is added to the transcript in sorted label
+/// order, preceded by a frame: a Merlin `append_message` call with label `b"decree-input-frame"`
+/// whose message is the byte length of the input label, then the byte length of the value, each
+/// as an 8-byte little-endian integer. Merlin already frames its messages, but the explicit
+/// frame keeps inputs with different labels unambiguous under any backend that doesn't.
+///
 /// # Examples
 /// ```
 /// # use decree::decree::{Decree, InputLabel, ChallengeLabel};
@@ -133,12 +332,100 @@ pub type FSInput = Vec<u8>;
 ///     let z = (challenge_int * log) + randomizer_int;
 /// #   Ok(())
 /// # }
+#[derive(Clone)]
 pub struct Decree {
-    inputs: Vec<InputLabel>,
-    challenges: Vec<ChallengeLabel>,
-    values: HashMap<InputLabel, FSInput>,
-    transcript: Transcript,
-    committed: bool
+    name: &'static str,
+    initial_inputs: Vec<Label>,
+    initial_optional_inputs: Vec<Label>,
+    initial_challenges: Vec<Label>,
+    inputs: Vec<Label>,
+    optional_inputs: Vec<Label>,
+    kept_inputs: Vec<Label>,
+    stage_challenges: Vec<Label>,
+    challenges: Vec<Label>,
+    values: HashMap<Label, StoredInput>,
+    initial_transcript: LoggedTranscript,
+    transcript: LoggedTranscript,
+    committed: bool,
+    challenge_cache: Option<Vec<(Label, Vec<u8>)>>,
+    strict_challenges: bool,
+    strict_binding: bool,
+    manual_commit: bool,
+    challenge_index: u64,
+    limits: DecreeLimits,
+    input_bytes: usize,
+    // How the transcript was built, for serialization. `None` if it can't be replayed, e.g. if it
+    // was created elsewhere.
+    #[cfg(feature = "serde")]
+    history: Option<History>
+}
+
+// The name is the Merlin transcript's domain separator, so it can't be empty. Only
+// `from_transcript`, which adopts a transcript named elsewhere, has no name of its own.
+fn check_name(name: &str) -> DecreeResult<()> {
+    if name.is_empty() {
+        return Err(Error::new_init_fail("Transcript name must be non-empty"));
+    }
+    Ok(())
+}
+
+// In strict mode, binds the label and position of the next challenge into the transcript.
+fn bind_strict(transcript: &mut LoggedTranscript, challenge: &str, index: u64) {
+    transcript.append_message(STRICT_LABEL_LABEL, challenge.as_bytes());
+    transcript.append_message(STRICT_INDEX_LABEL, &index.to_le_bytes());
+}
+
+// Merlin labels must be `&'static`, so owned labels are absorbed as a message of their own,
+// ahead of the value they label.
+fn append_labeled(transcript: &mut LoggedTranscript, label: &Label, message: &[u8]) {
+    match label {
+        Cow::Borrowed(label) => transcript.append_message(label.as_bytes(), message),
+        Cow::Owned(label) => {
+            transcript.append_message(OWNED_LABEL_LABEL, label.as_bytes());
+            transcript.append_message(OWNED_INPUT_LABEL, message);
+        }
+    }
+}
+
+// Rejection-samples a value uniformly distributed in `[0, modulus)` (or `[1, modulus)` if
+// `nonzero` is set), squeezing the transcript under `label` until a draw is accepted. Draws are as
+// wide as `modulus`, are read in the given byte order, and are accepted if they fall below the
+// largest multiple of `modulus` that fits in that width.
+fn squeeze_mod(
+        transcript: &mut LoggedTranscript,
+        label: &Label,
+        modulus: &BigUint,
+        nonzero: bool,
+        order: ByteOrder) -> BigUint {
+    let draw_len = modulus.bits().div_ceil(8) as usize;
+    let range = BigUint::from(1u32) << (8 * draw_len);
+    let limit = (&range / modulus) * modulus;
+
+    let mut buf = vec![0u8; draw_len];
+    loop {
+        challenge_labeled(transcript, label, &mut buf);
+        let draw = match order {
+            ByteOrder::LittleEndian => BigUint::from_bytes_le(&buf),
+            ByteOrder::BigEndian => BigUint::from_bytes_be(&buf),
+        };
+        if draw < limit {
+            let residue = draw % modulus;
+            if !nonzero || residue != BigUint::from(0u32) {
+                return residue;
+            }
+        }
+    }
+}
+
+// Squeezes challenge bytes under `label`, framing owned labels as in `append_labeled`.
+fn challenge_labeled(transcript: &mut LoggedTranscript, label: &Label, dest: &mut [u8]) {
+    match label {
+        Cow::Borrowed(label) => transcript.challenge_bytes(label.as_bytes(), dest),
+        Cow::Owned(label) => {
+            transcript.append_message(OWNED_LABEL_LABEL, label.as_bytes());
+            transcript.challenge_bytes(OWNED_CHALLENGE_LABEL, dest);
+        }
+    }
 }
 
 // Checks that all elements in a Vector of status 
@@ -151,6 +438,16 @@ where
     elts.iter().all(move |x| uniq.insert(x))
 }
 
+// Wraps borrowed `&'static str` labels without copying them.
+fn static_labels(labels: &[&'static str]) -> Vec<Label> {
+    labels.iter().map(|label| Cow::Borrowed(*label)).collect()
+}
+
+// Takes owned copies of runtime-generated labels.
+fn owned_labels(labels: &[String]) -> Vec<Label> {
+    labels.iter().map(|label| Cow::Owned(label.clone())).collect()
+}
+
 
 impl Decree {
     /// Creates a new `Decree` struct. This will fail if one or both of the `input` or `challenge`
@@ -171,6 +468,8 @@ impl Decree {
     /// ```
     ///
     /// # Panics
+    /// If `name` is empty, since it is the transcript's domain separator
+    ///
     /// If `inputs` or `challenges` is empty
     ///
     /// If `inputs` contains repeated entries
@@ -232,6 +531,155 @@ impl Decree {
         name: &'static str,
         inputs: &[InputLabel],
         challenges: &[ChallengeLabel]) -> DecreeResult<Decree> {
+        Decree::new_with_limits(name, inputs, challenges, DecreeLimits::global())
+    }
+
+
+    /// Creates a new `Decree` struct with the given resource limits, rather than the process-wide
+    /// limits used by `new`. Aside from the limits, the requirements are the same as for `new`.
+    pub fn new_with_limits(
+        name: &'static str,
+        inputs: &[InputLabel],
+        challenges: &[ChallengeLabel],
+        limits: DecreeLimits) -> DecreeResult<Decree> {
+        check_name(name)?;
+        // Initialize the Merlin trascript
+        let transcript = LoggedTranscript::new(name.as_bytes());
+        Decree::assemble(name, transcript, static_labels(inputs), static_labels(challenges), limits)
+    }
+
+
+    /// Creates a new `Decree` struct from owned input and challenge labels. This is useful when
+    /// the labels are only known at runtime, such as a protocol that repeats a round once per
+    /// commitment. The labels are copied into the `Decree`, and the requirements are the same as
+    /// for `new`.
+    ///
+    /// Merlin only accepts `&'static` labels, so owned labels are added to the transcript as
+    /// messages of their own. This means that a label gives different challenges depending on
+    /// whether it was passed to `new` or `new_owned`; prover and verifier must agree on which is
+    /// used.
+    ///
+    /// # Tests
+    ///
+    /// Owned labels behave like static labels, but give different challenges
+    /// ```
+    /// # use decree::decree::{Decree, InputLabel, ChallengeLabel};
+    /// # use decree::error::DecreeResult;
+    /// # fn main() -> DecreeResult<()> {
+    /// let owned_inputs: Vec<String> = (1..=2).map(|i| format!("input{i}")).collect();
+    /// let owned_challenges = vec![String::from("challenge1")];
+    /// let mut prover = Decree::new_owned("testname", &owned_inputs, &owned_challenges)?;
+    /// prover.add_serial("input1", 10u32)?;
+    /// prover.add_serial("input2", 14u32)?;
+    /// let mut prover_out: [u8; 32] = [0u8; 32];
+    /// prover.get_challenge("challenge1", &mut prover_out)?;
+    ///
+    /// let mut verifier = Decree::new_owned("testname", &owned_inputs, &owned_challenges)?;
+    /// verifier.add_serial(&owned_inputs[1], 14u32)?;
+    /// verifier.add_serial(&owned_inputs[0], 10u32)?;
+    /// let mut verifier_out: [u8; 32] = [0u8; 32];
+    /// verifier.get_challenge(&owned_challenges[0], &mut verifier_out)?;
+    /// assert_eq!(prover_out, verifier_out);
+    ///
+    /// let inputs: [InputLabel; 2] = ["input1", "input2"];
+    /// let challenges: [ChallengeLabel; 1] = ["challenge1"];
+    /// let mut static_decree = Decree::new("testname", &inputs, &challenges)?;
+    /// static_decree.add_serial("input1", 10u32)?;
+    /// static_decree.add_serial("input2", 14u32)?;
+    /// let mut static_out: [u8; 32] = [0u8; 32];
+    /// static_decree.get_challenge("challenge1", &mut static_out)?;
+    /// assert_ne!(prover_out, static_out);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn new_owned(
+        name: &'static str,
+        inputs: &[String],
+        challenges: &[String]) -> DecreeResult<Decree> {
+        check_name(name)?;
+        let transcript = LoggedTranscript::new(name.as_bytes());
+        Decree::assemble(
+            name,
+            transcript,
+            owned_labels(inputs),
+            owned_labels(challenges),
+            DecreeLimits::global())
+    }
+
+
+    /// Creates a new `Decree` struct on top of an existing Merlin transcript, rather than a fresh
+    /// one. This allows Decree to be used alongside other Merlin-based code, where the transcript
+    /// may already carry domain separators or prologue messages. The requirements are the same as
+    /// for `new`, and the process-wide limits apply.
+    ///
+    /// Since the transcript was named by its creator, the `Decree` itself has an empty name; this
+    /// is the name recorded in any `Proof` it produces. `clone_for_verification` starts over from
+    /// the transcript as it was passed in.
+    ///
+    /// # Tests
+    ///
+    /// Prologue messages on the adopted transcript change the challenges
+    /// ```
+    /// # use decree::decree::{Decree, InputLabel, ChallengeLabel};
+    /// # use decree::error::DecreeResult;
+    /// # use merlin::Transcript;
+    /// # fn main() -> DecreeResult<()> {
+    /// let inputs: [InputLabel; 1] = ["input1"];
+    /// let challenges: [ChallengeLabel; 1] = ["challenge1"];
+    ///
+    /// let mut plain = Decree::from_transcript(Transcript::new(b"outer protocol"), &inputs, &challenges)?;
+    /// plain.add_serial("input1", 10u32)?;
+    /// let mut plain_out: [u8; 32] = [0u8; 32];
+    /// plain.get_challenge("challenge1", &mut plain_out)?;
+    ///
+    /// let mut transcript = Transcript::new(b"outer protocol");
+    /// transcript.append_message(b"prologue", b"session 1");
+    /// let mut prologue = Decree::from_transcript(transcript, &inputs, &challenges)?;
+    /// prologue.add_serial("input1", 10u32)?;
+    /// let mut prologue_out: [u8; 32] = [0u8; 32];
+    /// prologue.get_challenge("challenge1", &mut prologue_out)?;
+    ///
+    /// assert_ne!(plain_out, prologue_out);
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// Labels are validated as in `new`
+    /// ```should_panic
+    /// # use decree::decree::{Decree, InputLabel, ChallengeLabel};
+    /// # use decree::error::DecreeResult;
+    /// # use merlin::Transcript;
+    /// # fn main() -> DecreeResult<()> {
+    /// let inputs: [InputLabel; 2] = ["repeated_input", "repeated_input"];
+    /// let challenges: [ChallengeLabel; 1] = ["challenge1"];
+    /// Decree::from_transcript(Transcript::new(b"outer protocol"), &inputs, &challenges)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn from_transcript(
+        transcript: Transcript,
+        inputs: &[InputLabel],
+        challenges: &[ChallengeLabel]) -> DecreeResult<Decree> {
+        let decree = Decree::assemble(
+            "",
+            LoggedTranscript::from_transcript(transcript),
+            static_labels(inputs),
+            static_labels(challenges),
+            DecreeLimits::global())?;
+        // The starting state of the transcript is unknown, so it can't be replayed
+        #[cfg(feature = "serde")]
+        let decree = Decree { history: None, ..decree };
+        Ok(decree)
+    }
+
+
+    // Validates the labels and builds a `Decree` around `transcript`.
+    fn assemble(
+        name: &'static str,
+        transcript: LoggedTranscript,
+        inputs: Vec<Label>,
+        challenges: Vec<Label>,
+        limits: DecreeLimits) -> DecreeResult<Decree> {
 
         // Make sure we have at least one input and one output
         if inputs.is_empty() {
@@ -241,452 +689,3696 @@ impl Decree {
             return Err(Error::new_init_fail("Must specify at least one challenge"));
         }
 
+        // Make sure we're within our limits
+        if inputs.len() > limits.max_inputs {
+            return Err(Error::new_init_fail("Too many inputs"));
+        }
+        if challenges.len() > limits.max_challenges {
+            return Err(Error::new_init_fail("Too many challenges"));
+        }
 
-        // Make sure our inputs are unique (should challenges be forced to be unique?)
-        let mut input_labels = inputs.to_vec();
+
+        // Make sure our inputs and challenges are unique
+        let mut input_labels = inputs;
         if !vector_is_distinct(&input_labels) {
             return Err(Error::new_init_fail("Inputs must be distinct"));
         }
+        if !vector_is_distinct(&challenges) {
+            return Err(Error::new_init_fail("Challenges must be distinct"));
+        }
 
         // We need  to sort the input labels to ensure that we have a consistent transcript.
         input_labels.sort();
 
-        // Initialize the Merlin trascript
-        let transcript = Transcript::new(name.as_bytes());
-
         Ok(Decree{
+            name,
+            initial_inputs: input_labels.clone(),
+            initial_optional_inputs: Vec::new(),
+            initial_challenges: challenges.clone(),
             inputs: input_labels,
-            challenges: challenges.to_vec(),
+            optional_inputs: Vec::new(),
+            kept_inputs: Vec::new(),
+            stage_challenges: challenges.clone(),
+            challenges,
             values: HashMap::new(),
+            initial_transcript: transcript.clone(),
             transcript,
-            committed: false
+            committed: false,
+            challenge_cache: None,
+            strict_challenges: false,
+            strict_binding: false,
+            manual_commit: false,
+            challenge_index: 0,
+            limits,
+            input_bytes: 0,
+            #[cfg(feature = "serde")]
+            history: Some(History::new())
         })
     }
 
 
-    /// The `extend` method is used to move from one phase of a protocol to the next while
-    /// maintaining Fiat-Shamir state. Calling `extend` should leave a `Decree` struct ready to
-    /// accept new inputs and generate new challenges, but without resetting the Merlin transcript.
-    ///
-    /// If you're used to working with Merlin transcripts directly, you can think of this as a step
-    /// that fits in between generating your latest challenge and adding your next input.
-    ///
-    /// Aside from not needing a `name` input as in the `new` method, the inputs must meet the same
-    /// requirements as the `new` method.
+    /// Builds a `Decree` from arbitrary runtime label sets and a list of values to add with
+    /// `add_bytes`, for property tests and fuzz harnesses. The labels must meet the same
+    /// requirements as for `new_owned`, and each value goes through the usual checks, so a
+    /// harness can feed in arbitrary data and expect either a valid `Decree` or an error, never a
+    /// panic. Available with the `fuzzing` feature.
     ///
     /// # Tests
-    /// 
-    /// Test the "happy path"
-    /// 
+    ///
     /// ```
     /// # use decree::decree::Decree;
-    /// # use decree::decree::{InputLabel, ChallengeLabel};
-    /// # use decree::error::{Error, DecreeErrType, DecreeResult};
+    /// # use decree::error::DecreeResult;
     /// # fn main() -> DecreeResult<()> {
-    /// # let mut challenge_out: [u8; 32] = [0u8; 32];
-    /// let inputs: [InputLabel; 2] = ["input1", "input2"];
-    /// let challenges: [ChallengeLabel; 2] = ["challenge1", "challenge2"];
-    /// let mut my_decree = Decree::new("testname", &inputs, &challenges)?;
-    /// my_decree.add_serial("input1", "input_data_1")?;
-    /// my_decree.add_serial("input2", "input_data_2")?;
-    /// my_decree.get_challenge("challenge1", &mut challenge_out)?;
-    /// my_decree.get_challenge("challenge2", &mut challenge_out)?;
-    /// my_decree.extend(&inputs, &challenges)?;
-    /// my_decree.add_serial("input1", "input_data_3")?;
-    /// my_decree.add_serial("input2", "input_data_4")?;
-    /// my_decree.get_challenge("challenge1", &mut challenge_out)?;
-    /// my_decree.get_challenge("challenge2", &mut challenge_out)?;
+    /// let inputs = vec![String::from("input1"), String::from("input2")];
+    /// let challenges = vec![String::from("challenge1")];
+    /// let values = vec![(String::from("input2"), vec![1u8, 2u8])];
+    /// let mut my_decree = Decree::from_parts("testname", &inputs, &challenges, &values)?;
+    /// my_decree.check_invariants()?;
+    /// assert_eq!(my_decree.missing_inputs(), vec!["input1"]);
+    ///
+    /// let unknown = vec![(String::from("input3"), vec![])];
+    /// assert!(Decree::from_parts("testname", &inputs, &challenges, &unknown).is_err());
     /// # Ok(())
     /// # }
     /// ```
-    pub fn extend(
-            &mut self,
-            inputs: &[InputLabel],
-            challenges: &[ChallengeLabel]) -> DecreeResult<()> {
-        // If we have pending challenges, or aren't in a committed state,
-        // bail.
-        if !self.challenges.is_empty() || !self.committed {
-            return Err(Error::new_extend_fail("Cannot extend Decree until all challenges generated"));
-        }
-        // Make sure we have at least one input and one output
-        if inputs.is_empty() {
-            return Err(Error::new_extend_fail("Must specify at least one input"));
-        }
-        if challenges.is_empty() {
-            return Err(Error::new_extend_fail("Must specify at least one challenge"));
-        }
-
-        // Make sure our inputs are unique (should challenges be forced to be unique?)
-        let mut input_labels = inputs.to_vec();
-        if !vector_is_distinct(&input_labels) {
-            return Err(Error::new_init_fail("Inputs must be distinct"));
+    #[cfg(any(test, feature = "fuzzing"))]
+    pub fn from_parts(
+        name: &'static str,
+        inputs: &[String],
+        challenges: &[String],
+        values: &[(String, Vec<u8>)]) -> DecreeResult<Decree> {
+        let mut decree = Decree::new_owned(name, inputs, challenges)?;
+        for (label, value) in values.iter() {
+            decree.add_bytes(label, value)?;
         }
-
-        // We need  to sort the input labels to ensure that we have a
-        // consistent transcript.
-        input_labels.sort();
-
-        // Set up all the new values, leaving the transcript in place
-        self.inputs = input_labels;
-        self.challenges = challenges.to_vec();
-        self.values = HashMap::new();
-        self.committed = false;
-
-        Ok(())
+        Ok(decree)
     }
 
 
-    fn can_commit(&self) -> bool {
-        // If we already committed the current values, don't do it again
-        if self.committed {
-            return false;
-        }
-
-        // Bail if any specified input lacks an associated value
-        for label in self.inputs.iter() {
-            if !self.values.contains_key(label) {
-                return false;
-            }
+    /// Checks the internal consistency of the stage state, returning a `General` error naming
+    /// the first violated invariant. Every public method should leave a `Decree` in a state that
+    /// passes, whether it succeeds or fails, so harnesses can call this after each step. A
+    /// deserialized `Decree` is checked before it is returned.
+    pub fn check_invariants(&self) -> DecreeResult<()> {
+        if self.committed && !self.missing_inputs().is_empty() {
+            return Err(Error::new_general("Invariant violated: committed with missing inputs"));
         }
-
-        // Otherwise, we have all our inputs and we can commit
-        true
-    }
-
-
-    // The `commit` method actually writes the Fiat-Shamir values into the transcript. It should
-    // only be called when every element of the `inputs` vector has a matching entry in the`values`
-    // hash map.
-    fn commit(&mut self) -> DecreeResult<()> {
-        // We iterate over the input label vector because the HashMap
-        // item iterator does not provide the elements in any guaranteed
-        // order.
-        for input_label in self.inputs.iter() {
-            let value = match self.values.get(input_label) {
-                Some(a) => a,
-                None => { return Err(Error::new_general("Error in label processing")); }
-            };
-            self.transcript.append_message(input_label.as_bytes(), value.as_slice());
+        if self.values.keys().any(|label| !self.inputs.contains(label)) {
+            return Err(Error::new_general("Invariant violated: value for an unspecified input"));
         }
-
-        // Set the committed flag
-        self.committed = true;
-
-        Ok(())
-    }
-
-    fn add_input(
-            &mut self,
-            label: InputLabel,
-            input: FSInput) -> DecreeResult<()> {
-        // If we're already committed, we can't add new values
-        if self.committed {
-            return Err(Error::new_general("Cannot add values after commitment"));
+        if self.optional_inputs.iter().chain(self.kept_inputs.iter()).any(|label| !self.inputs.contains(label)) {
+            return Err(Error::new_general("Invariant violated: optional or kept input not in the stage"));
         }
-
-        // Invalid inputs should result in an error
-        if !self.inputs.contains(&label) {
-            return Err(Error::new_invalid_label("Invalid label"));
+        if self.kept_inputs.iter().any(|label| !self.values.contains_key(label)) {
+            return Err(Error::new_general("Invariant violated: kept input without a value"));
         }
-
-        // Re-definition of an input should result in an error
-        if self.values.contains_key(label) {
-            return Err(Error::new_invalid_label("Label already used"));
+        if !self.stage_challenges.ends_with(&self.challenges) {
+            return Err(Error::new_general("Invariant violated: pending challenges out of order"));
         }
-
-        // Add the input to the map
-        self.values.insert(
-            label,
-            input.to_vec()
-        );
-
-        // If this is the last input, go ahead and commit the values
-        if self.can_commit() {
-            self.commit()?;
+        if self.challenges.len() < self.stage_challenges.len() && !self.committed {
+            return Err(Error::new_general("Invariant violated: challenge generated before commitment"));
         }
         Ok(())
     }
 
-    /// The `add_serial` method associates the BCS serialization of a value with the given input
-    /// label. This should be used when a Fiat-Shamir input supports the `Serialize` trait, but
-    // not the `Inscribe` trait.
-    ///
-    /// # Panics
+
+    /// Creates a new `Decree` struct and adds each of `values` with `add`, for verifiers that
+    /// rebuild a prover's transcript from a list of received values. Every input must be supplied
+    /// exactly once; an unknown, repeated, or missing input is an error. Keeping the values in one
+    /// list, rather than in a sequence of `add` calls, makes it harder for the verifier's code to
+    /// drift from the prover's.
     ///
-    /// If `label` is not a valid label specified in the most recent `new` or `extend` call.
+    /// # Tests
     ///
-    /// If `label` has already been used in a call to `add_serial`, or `add`
+    /// Replaying the prover's values reproduces its challenge
+    /// ```
+    /// # use decree::decree::{Decree, InputLabel, ChallengeLabel};
+    /// # use decree::error::{DecreeErrType, DecreeResult};
+    /// # fn main() -> DecreeResult<()> {
+    /// let inputs: [InputLabel; 2] = ["input1", "input2"];
+    /// let challenges: [ChallengeLabel; 1] = ["challenge1"];
+    /// let mut prover = Decree::new("testname", &inputs, &challenges)?;
+    /// prover.add("input1", &10u32)?;
+    /// prover.add("input2", &String::from("value"))?;
+    /// let prover_out: [u8; 32] = prover.challenge("challenge1")?;
     ///
-    /// If all inputs already have associated inputs.
+    /// let value = String::from("value");
+    /// let mut verifier = Decree::replay("testname", &inputs, &challenges,
+    ///     &[("input2", &value), ("input1", &10u32)])?;
+    /// let verifier_out: [u8; 32] = verifier.challenge("challenge1")?;
+    /// assert_eq!(prover_out, verifier_out);
     ///
-    /// If `label` is the last value to be processed, and an error occurs during commitment.
+    /// // Missing and repeated inputs are rejected
+    /// let missing = Decree::replay("testname", &inputs, &challenges, &[("input1", &10u32)]);
+    /// assert_eq!(missing.err().unwrap().get_type(), DecreeErrType::InitFail);
+    /// let repeated = Decree::replay("testname", &inputs, &challenges,
+    ///     &[("input1", &10u32), ("input1", &10u32), ("input2", &value)]);
+    /// assert_eq!(repeated.err().unwrap().get_type(), DecreeErrType::InvalidLabel);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn replay(
+        name: &'static str,
+        inputs: &[InputLabel],
+        challenges: &[ChallengeLabel],
+        values: &[(InputLabel, &dyn Inscribe)]) -> DecreeResult<Decree> {
+        let mut decree = Decree::new(name, inputs, challenges)?;
+        for (label, value) in values.iter() {
+            decree.add(label, *value)?;
+        }
+
+        let missing = decree.missing_inputs();
+        if !missing.is_empty() {
+            return Err(Error::new_init_fail(format!("Missing replayed input(s): {}", missing.join(", "))));
+        }
+        Ok(decree)
+    }
+
+
+    /// Creates a new `Decree` struct in strict challenge mode. The requirements are the same as
+    /// for `new`.
+    ///
+    /// In strict mode, each challenge's label and its position in the challenge schedule are bound
+    /// into the transcript just before the challenge is generated. A verifier whose challenge
+    /// schedule differs from the prover's will get different challenges, rather than possibly
+    /// matching ones. Strict mode carries over to later stages created with `extend`.
+    ///
+    /// Specifically, before the Merlin `challenge_bytes` call for a challenge, strict mode makes
+    /// two Merlin `append_message` calls:
+    ///
+    ///   1. Label `b"decree-challenge-label"`, with the challenge label as the message
+    ///   2. Label `b"decree-challenge-index"`, with the zero-based position of the challenge in
+    ///      the current stage's schedule, as an 8-byte little-endian integer, as the message
     ///
     /// # Tests
-    /// 
-    /// Test the "happy path"
-    /// 
+    ///
+    /// Strict mode matches a manually constructed Merlin transcript
     /// ```
-    /// # use decree::decree::Decree;
-    /// # use decree::decree::{InputLabel, ChallengeLabel};
+    /// # use decree::decree::{Decree, InputLabel, ChallengeLabel};
     /// # use decree::error::{Error, DecreeErrType, DecreeResult};
-    /// # use serde::Serialize;
-    /// #[derive(Serialize)]
-    /// pub struct Point {
-    ///     x: i32,
-    ///     y: i32,
-    /// }
+    /// # use merlin::Transcript;
     /// # fn main() -> DecreeResult<()> {
-    /// let input_data = "input_data_1";
-    /// let inputs: [InputLabel; 2] = ["input1", "input2"];
+    /// let inputs: [InputLabel; 1] = ["input1"];
     /// let challenges: [ChallengeLabel; 2] = ["challenge1", "challenge2"];
-    /// let mut my_decree = Decree::new("testname", &inputs, &challenges)?;
-    /// my_decree.add_serial("input1", Point{ x: 1i32, y: 2i32 })?;
+    /// let mut my_decree = Decree::with_strict_challenges("testname", &inputs, &challenges)?;
+    /// my_decree.add_serial("input1", 10u32)?;
+    /// let mut challenge1: [u8; 32] = [0u8; 32];
+    /// let mut challenge2: [u8; 32] = [0u8; 32];
+    /// my_decree.get_challenge("challenge1", &mut challenge1)?;
+    /// my_decree.get_challenge("challenge2", &mut challenge2)?;
+    ///
+    /// let mut transcript = Transcript::new(b"testname");
+    /// transcript.append_message(b"decree-input-frame", &[6u64.to_le_bytes(), 4u64.to_le_bytes()].concat());
+    /// transcript.append_message(b"input1", &bcs::to_bytes(&10u32).unwrap());
+    /// let mut expected1: [u8; 32] = [0u8; 32];
+    /// let mut expected2: [u8; 32] = [0u8; 32];
+    /// transcript.append_message(b"decree-challenge-label", b"challenge1");
+    /// transcript.append_message(b"decree-challenge-index", &0u64.to_le_bytes());
+    /// transcript.challenge_bytes(b"challenge1", &mut expected1);
+    /// transcript.append_message(b"decree-challenge-label", b"challenge2");
+    /// transcript.append_message(b"decree-challenge-index", &1u64.to_le_bytes());
+    /// transcript.challenge_bytes(b"challenge2", &mut expected2);
+    /// assert_eq!(challenge1, expected1);
+    /// assert_eq!(challenge2, expected2);
     /// # Ok(())
     /// # }
     /// ```
-    pub fn add_serial<T: Serialize>(
-            &mut self,
-            label: InputLabel,
-            input: T) -> DecreeResult<()> {
-        let bytevec = match to_bytes::<T>(&input) {
-            Ok(a) => a,
-            Err(_) => { return Err(Error::new_general("Could not serialize")); }
-        };
-        self.add_input(label, bytevec)
+    ///
+    /// Strict mode diverges from the default mode, and a reordered schedule diverges
+    /// ```
+    /// # use decree::decree::{Decree, InputLabel, ChallengeLabel};
+    /// # use decree::error::{Error, DecreeErrType, DecreeResult};
+    /// # fn main() -> DecreeResult<()> {
+    /// let inputs: [InputLabel; 1] = ["input1"];
+    /// let schedule: [ChallengeLabel; 2] = ["challenge1", "challenge2"];
+    /// let reordered: [ChallengeLabel; 2] = ["challenge2", "challenge1"];
+    ///
+    /// let mut default_decree = Decree::new("testname", &inputs, &schedule)?;
+    /// let mut strict_decree = Decree::with_strict_challenges("testname", &inputs, &schedule)?;
+    /// let mut reordered_decree = Decree::with_strict_challenges("testname", &inputs, &reordered)?;
+    /// default_decree.add_serial("input1", 10u32)?;
+    /// strict_decree.add_serial("input1", 10u32)?;
+    /// reordered_decree.add_serial("input1", 10u32)?;
+    ///
+    /// let mut default_out: [u8; 32] = [0u8; 32];
+    /// let mut strict_out: [u8; 32] = [0u8; 32];
+    /// let mut reordered_out: [u8; 32] = [0u8; 32];
+    /// default_decree.get_challenge("challenge1", &mut default_out)?;
+    /// strict_decree.get_challenge("challenge1", &mut strict_out)?;
+    /// assert_ne!(default_out, strict_out);
+    ///
+    /// strict_decree.get_challenge("challenge2", &mut strict_out)?;
+    /// reordered_decree.get_challenge("challenge2", &mut reordered_out)?;
+    /// assert_ne!(strict_out, reordered_out);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn with_strict_challenges(
+        name: &'static str,
+        inputs: &[InputLabel],
+        challenges: &[ChallengeLabel]) -> DecreeResult<Decree> {
+        let mut decree = Decree::new(name, inputs, challenges)?;
+        decree.strict_challenges = true;
+        Ok(decree)
     }
 
 
-    /// The `add` method associates the inscription of an object with the given input
-    /// label. This should always be used when a Fiat-Shamir input supports the `Inscribe`
-    /// trait.
-    ///
-    /// # Panics
+    /// Creates a new `Decree` struct in strict binding mode. The requirements are the same as for
+    /// `new`.
     ///
-    /// If `label` is not a valid label specified in the most recent `new` or `extend` call.
+    /// By default, only the values of the inputs feed the transcript, so a verifier that rebuilds
+    /// a `Decree` with a different label set than the prover (say, with a renamed challenge that
+    /// hasn't been generated yet) may still get matching challenges. In strict binding mode, the
+    /// name and the specification of each stage are bound into the transcript, so any divergence
+    /// changes every challenge of that stage. Strict binding carries over to later stages created
+    /// with `extend`.
     ///
-    /// If `label` has already been used in a call to `add_serial` or `add`
+    /// Specifically, strict binding makes the following Merlin `append_message` calls:
     ///
-    /// If all inputs already have associated inputs.
+    ///   1. On creation, label `b"decree-name"`, with the name as the message
+    ///   2. When a stage's inputs are committed, and before the input values, label
+    ///      `b"decree-input-label"` once per input, with the input label as the message, in the
+    ///      same sorted order the values are added
+    ///   3. Immediately after those, label `b"decree-challenge-spec"` once per challenge, with the
+    ///      challenge label as the message, in schedule order
     ///
-    /// If `label` is the last value to be processed, and an error occurs during commitment.
+    /// The framed input values then follow as usual.
     ///
     /// # Tests
-    /// 
-    /// Test the "happy path"
-    /// 
+    ///
+    /// Strict binding matches a manually constructed Merlin transcript
     /// ```
     /// # use decree::decree::{Decree, InputLabel, ChallengeLabel};
-    /// # use decree::error::{Error, DecreeErrType, DecreeResult};
-    /// # use decree::Inscribe;
-    /// # use decree::inscribe::{INSCRIBE_LENGTH, InscribeBuffer};
-    /// #[derive(Inscribe)]
-    /// pub struct Point {
-    ///     #[inscribe(serialize)]
-    ///     x: i32,
-    ///     #[inscribe(serialize)]
-    ///     y: i32,
-    /// }
+    /// # use decree::error::DecreeResult;
+    /// # use merlin::Transcript;
     /// # fn main() -> DecreeResult<()> {
-    /// let input_data = "input_data_1";
-    /// let inputs: [InputLabel; 2] = ["input1", "input2"];
+    /// let inputs: [InputLabel; 2] = ["input2", "input1"];
     /// let challenges: [ChallengeLabel; 2] = ["challenge1", "challenge2"];
-    /// let mut my_decree = Decree::new("testname", &inputs, &challenges)?;
-    /// my_decree.add("input1", &Point{ x: 1i32, y: 2i32 })?;
+    /// let mut my_decree = Decree::with_strict_binding("testname", &inputs, &challenges)?;
+    /// my_decree.add_serial("input1", 10u32)?;
+    /// my_decree.add_serial("input2", 14u32)?;
+    /// let mut challenge1: [u8; 32] = [0u8; 32];
+    /// my_decree.get_challenge("challenge1", &mut challenge1)?;
+    ///
+    /// let mut transcript = Transcript::new(b"testname");
+    /// transcript.append_message(b"decree-name", b"testname");
+    /// transcript.append_message(b"decree-input-label", b"input1");
+    /// transcript.append_message(b"decree-input-label", b"input2");
+    /// transcript.append_message(b"decree-challenge-spec", b"challenge1");
+    /// transcript.append_message(b"decree-challenge-spec", b"challenge2");
+    /// transcript.append_message(b"decree-input-frame", &[6u64.to_le_bytes(), 4u64.to_le_bytes()].concat());
+    /// transcript.append_message(b"input1", &bcs::to_bytes(&10u32).unwrap());
+    /// transcript.append_message(b"decree-input-frame", &[6u64.to_le_bytes(), 4u64.to_le_bytes()].concat());
+    /// transcript.append_message(b"input2", &bcs::to_bytes(&14u32).unwrap());
+    /// let mut expected1: [u8; 32] = [0u8; 32];
+    /// transcript.challenge_bytes(b"challenge1", &mut expected1);
+    /// assert_eq!(challenge1, expected1);
     /// # Ok(())
     /// # }
     /// ```
-    pub fn add<T: Inscribe>(
-            &mut self,
-            label: InputLabel,
-            input: &T) -> DecreeResult<()> {
-        //let mut buf: InscribeBuffer = [0u8; INSCRIBE_LENGTH];
-        //input.get_inscription(&mut buf);
-        //let inscription_vec = buf.to_vec();
-        let inscription = input.get_inscription()?;
-        self.add_input(label, inscription)
+    ///
+    /// A verifier with a renamed challenge label gets a different challenge
+    /// ```
+    /// # use decree::decree::{Decree, InputLabel, ChallengeLabel};
+    /// # use decree::error::DecreeResult;
+    /// # fn main() -> DecreeResult<()> {
+    /// let inputs: [InputLabel; 1] = ["input1"];
+    /// let prover_challenges: [ChallengeLabel; 2] = ["challenge1", "challenge2"];
+    /// let verifier_challenges: [ChallengeLabel; 2] = ["challenge1", "renamed"];
+    ///
+    /// let first_challenge = |decree: &mut Decree| -> DecreeResult<[u8; 32]> {
+    ///     decree.add_serial("input1", 10u32)?;
+    ///     decree.challenge("challenge1")
+    /// };
+    ///
+    /// // Without strict binding, the divergence goes unnoticed
+    /// let prover = first_challenge(&mut Decree::new("testname", &inputs, &prover_challenges)?)?;
+    /// let verifier = first_challenge(&mut Decree::new("testname", &inputs, &verifier_challenges)?)?;
+    /// assert_eq!(prover, verifier);
+    ///
+    /// let prover = first_challenge(
+    ///     &mut Decree::with_strict_binding("testname", &inputs, &prover_challenges)?)?;
+    /// let verifier = first_challenge(
+    ///     &mut Decree::with_strict_binding("testname", &inputs, &verifier_challenges)?)?;
+    /// assert_ne!(prover, verifier);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn with_strict_binding(
+        name: &'static str,
+        inputs: &[InputLabel],
+        challenges: &[ChallengeLabel]) -> DecreeResult<Decree> {
+        let mut decree = Decree::new(name, inputs, challenges)?;
+        decree.strict_binding = true;
+        decree.initial_transcript.append_message(BINDING_NAME_LABEL, name.as_bytes());
+        decree.transcript = decree.initial_transcript.clone();
+        Ok(decree)
     }
 
 
-    /// The `get_challenge` method extracts a challenge value from the underlying Merlin
-    /// transcript. The `challenge` argument specifies which challenge to generate. As part of the
-    /// Fiat-Shamir enforcement system, the challenges _must_ be generated with labels given in
-    /// the same order they were provided in the most recent call to `new` or `extend`.
+    /// Creates a new `Decree` struct in manual commit mode. The requirements are the same as for
+    /// `new`. Normally, the inputs of a stage are committed as soon as the last one is added; in
+    /// manual mode, they are only committed by an explicit call to `commit`, in every stage. This
+    /// lets the caller decide where the commit boundary falls.
     ///
-    /// Challenges may not be generated PRIOR to all inputs being provided. This is to prevent
-    /// implementations from accidentally leaving out security-critical values.
-    /// 
-    /// # Panics
-    /// If the challenge label `challenge` is not specified during that last call to `new` or
-    /// `extend`.
+    /// # Tests
     ///
-    /// If the challenge label `challenge` does not match the next expected challenge
+    /// Challenges are only available after an explicit commit
+    /// ```
+    /// # use decree::decree::{Decree, InputLabel, ChallengeLabel};
+    /// # use decree::error::DecreeResult;
+    /// # fn main() -> DecreeResult<()> {
+    /// let inputs: [InputLabel; 1] = ["input1"];
+    /// let challenges: [ChallengeLabel; 1] = ["challenge1"];
+    /// let mut my_decree = Decree::new_manual("testname", &inputs, &challenges)?;
+    /// my_decree.add_serial("input1", 10u32)?;
+    /// assert!(!my_decree.is_committed());
+    /// assert!(my_decree.get_challenge_vec("challenge1", 32).is_err());
     ///
-    /// If no challenges remain to be generated
+    /// // The last input has been added, but raw messages can still be appended
+    /// my_decree.append_raw_message(b"protocol-version", &[2u8])?;
+    /// my_decree.commit()?;
+    /// assert!(my_decree.get_challenge_vec("challenge1", 32).is_ok());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn new_manual(
+        name: &'static str,
+        inputs: &[InputLabel],
+        challenges: &[ChallengeLabel]) -> DecreeResult<Decree> {
+        let mut decree = Decree::new(name, inputs, challenges)?;
+        decree.manual_commit = true;
+        Ok(decree)
+    }
+
+
+    /// Creates a new `Decree` struct bound to a protocol version. The requirements are the same as
+    /// for `new`. Right after the transcript is created, the version is appended under the label
+    /// `b"decree-version"`, as a 4-byte little-endian integer. A verifier expecting a different
+    /// version (e.g. one that has been downgraded) therefore derives different challenges from
+    /// the same inputs, without the protocol needing a version input of its own.
     ///
-    /// If no challenges can be generated because of incomplete inputs
-    /// 
-    /// # Examples
+    /// # Tests
     ///
-    /// The following code should succeed
+    /// Versions separate otherwise-identical transcripts
     /// ```
     /// # use decree::decree::{Decree, InputLabel, ChallengeLabel};
-    /// # use decree::error::{Error, DecreeErrType, DecreeResult};
-    /// # use decree::Inscribe;
-    /// # use decree::inscribe::{INSCRIBE_LENGTH, InscribeBuffer};
-    /// #[derive(Inscribe)]
-    /// pub struct Point {
-    ///     #[inscribe(serialize)]
-    ///     x: i32,
-    ///     #[inscribe(serialize)]
-    ///     y: i32,
-    /// }
+    /// # use decree::error::DecreeResult;
     /// # fn main() -> DecreeResult<()> {
-    /// let input_data = "input_data_1";
-    /// let inputs: [InputLabel; 2] = ["input1", "input2"];
-    /// let challenges: [ChallengeLabel; 2] = ["challenge1", "challenge2"];
-    /// let mut my_decree = Decree::new("testname", &inputs, &challenges)?;
-    /// my_decree.add("input1", &Point{ x: 1i32, y: 2i32 })?;
-    /// let mut challenge_out: [u8; 64] = [0u8; 64];
-    /// my_decree.get_challenge("challenge1", &mut challenge_out);
-    /// my_decree.get_challenge("challenge2", &mut challenge_out);
+    /// let inputs: [InputLabel; 1] = ["input1"];
+    /// let challenges: [ChallengeLabel; 1] = ["challenge1"];
+    ///
+    /// let mut outputs = Vec::new();
+    /// for version in [1u32, 2u32, 2u32] {
+    ///     let mut decree = Decree::with_version("testname", version, &inputs, &challenges)?;
+    ///     decree.add_serial("input1", 10u32)?;
+    ///     outputs.push(decree.get_challenge_vec("challenge1", 32)?);
+    /// }
+    /// assert_ne!(outputs[0], outputs[1]);
+    /// assert_eq!(outputs[1], outputs[2]);
+    ///
+    /// let mut unversioned = Decree::new("testname", &inputs, &challenges)?;
+    /// unversioned.add_serial("input1", 10u32)?;
+    /// assert_ne!(unversioned.get_challenge_vec("challenge1", 32)?, outputs[0]);
     /// # Ok(())
     /// # }
     /// ```
+    pub fn with_version(
+        name: &'static str,
+        version: u32,
+        inputs: &[InputLabel],
+        challenges: &[ChallengeLabel]) -> DecreeResult<Decree> {
+        let mut decree = Decree::new(name, inputs, challenges)?;
+        decree.initial_transcript.append_message(VERSION_LABEL, &version.to_le_bytes());
+        decree.transcript = decree.initial_transcript.clone();
+        #[cfg(feature = "serde")]
+        if let Some(history) = decree.history.as_mut() {
+            history.origin = Arc::new(Origin { version: Some(version) });
+        }
+        Ok(decree)
+    }
+
+
+    /// Creates a new `Decree` struct with some optional inputs, such as an optional nonce. The
+    /// `inputs` are required as usual; the `optional` inputs may be left out. The requirements are
+    /// the same as for `new`, with the `inputs` and `optional` labels together being distinct.
     ///
-    /// # Tests
+    /// When every input has been provided, the inputs are committed immediately, as with `new`.
+    /// Otherwise, the inputs are committed when the first challenge is generated, as long as every
+    /// required input has been provided; missing optional inputs can't be added after that point.
+    /// Each optional input is preceded in the transcript by a marker recording whether or not it is
+    /// present, so an absent input is never confused with any value.
     ///
-    /// The following code will not work, because the challenges are requested out of order
+    /// Specifically, immediately before the slot where an optional input's value would be added,
+    /// a Merlin `append_message` call is made with label `b"decree-optional-present"` and a single
+    /// byte message: `1` if the input is present, or `0` if it is absent. Absent inputs add nothing
+    /// else.
     ///
-    /// ```should_panic
+    /// # Tests
+    ///
+    /// Present and absent optional inputs give different challenges, and absent ones are
+    /// deterministic
+    /// ```
     /// # use decree::decree::{Decree, InputLabel, ChallengeLabel};
-    /// # use decree::error::{Error, DecreeErrType, DecreeResult};
-    /// # use decree::Inscribe;
-    /// # use decree::inscribe::{INSCRIBE_LENGTH, InscribeBuffer};
-    /// #[derive(Inscribe)]
-    /// pub struct Point {
-    ///     #[inscribe(serialize)]
-    ///     x: i32,
-    ///     #[inscribe(serialize)]
-    ///     y: i32,
-    /// }
+    /// # use decree::error::DecreeResult;
     /// # fn main() -> DecreeResult<()> {
-    /// let input_data = "input_data_1";
-    /// let inputs: [InputLabel; 2] = ["input1", "input2"];
-    /// let challenges: [ChallengeLabel; 2] = ["challenge1", "challenge2"];
-    /// let mut my_decree = Decree::new("testname", &inputs, &challenges)?;
-    /// my_decree.add("input1", &Point{ x: 1i32, y: 2i32 })?;
-    /// my_decree.add("input2", &Point{ x: 1i32, y: 2i32 })?;
-    /// let mut challenge_out: [u8; 64] = [0u8; 64];
-    /// my_decree.get_challenge("challenge2", &mut challenge_out)?;
+    /// let inputs: [InputLabel; 1] = ["input1"];
+    /// let optional: [InputLabel; 1] = ["nonce"];
+    /// let challenges: [ChallengeLabel; 1] = ["challenge1"];
+    ///
+    /// let mut present = Decree::with_optional_inputs("testname", &inputs, &optional, &challenges)?;
+    /// present.add_serial("nonce", 0u32)?;
+    /// present.add_serial("input1", 10u32)?;
+    /// assert!(present.is_committed());
+    /// let present_out: [u8; 32] = present.challenge("challenge1")?;
+    ///
+    /// let mut absent = Decree::with_optional_inputs("testname", &inputs, &optional, &challenges)?;
+    /// absent.add_serial("input1", 10u32)?;
+    /// assert!(absent.missing_inputs().is_empty());
+    /// assert!(!absent.is_committed());
+    /// let absent_out: [u8; 32] = absent.challenge("challenge1")?;
+    /// assert!(absent.add_serial("nonce", 0u32).is_err());
+    /// assert_ne!(present_out, absent_out);
+    ///
+    /// let mut verifier = absent.clone_for_verification();
+    /// let mut peeked: [u8; 32] = [0u8; 32];
+    /// verifier.add_serial("input1", 10u32)?;
+    /// verifier.peek_challenge("challenge1", &mut peeked)?;
+    /// assert_eq!(peeked, absent_out);
+    /// assert_eq!(verifier.challenge::<32>("challenge1")?, absent_out);
     /// # Ok(())
     /// # }
     /// ```
     ///
-    /// The following code will not work, because the challenge is not specified
-    ///
+    /// Required inputs are still required
     /// ```should_panic
     /// # use decree::decree::{Decree, InputLabel, ChallengeLabel};
-    /// # use decree::error::{Error, DecreeErrType, DecreeResult};
-    /// # use decree::Inscribe;
-    /// # use decree::inscribe::{INSCRIBE_LENGTH, InscribeBuffer};
-    /// #[derive(Inscribe)]
-    /// pub struct Point {
-    ///     #[inscribe(serialize)]
-    ///     x: i32,
-    ///     #[inscribe(serialize)]
-    ///     y: i32,
-    /// }
+    /// # use decree::error::DecreeResult;
     /// # fn main() -> DecreeResult<()> {
-    /// let input_data = "input_data_1";
-    /// let inputs: [InputLabel; 2] = ["input1", "input2"];
-    /// let challenges: [ChallengeLabel; 2] = ["challenge1", "challenge2"];
-    /// let mut my_decree = Decree::new("testname", &inputs, &challenges)?;
-    /// my_decree.add("input1", &Point{ x: 1i32, y: 2i32 })?;
-    /// my_decree.add("input2", &Point{ x: 1i32, y: 2i32 })?;
-    /// let mut challenge_out: [u8; 64] = [0u8; 64];
-    /// my_decree.get_challenge("invalid_challenge", &mut challenge_out)?;
+    /// let inputs: [InputLabel; 1] = ["input1"];
+    /// let optional: [InputLabel; 1] = ["nonce"];
+    /// let challenges: [ChallengeLabel; 1] = ["challenge1"];
+    /// let mut my_decree = Decree::with_optional_inputs("testname", &inputs, &optional, &challenges)?;
+    /// my_decree.add_serial("nonce", 0u32)?;
+    /// let _: [u8; 32] = my_decree.challenge("challenge1")?;
     /// # Ok(())
     /// # }
     /// ```
+    pub fn with_optional_inputs(
+        name: &'static str,
+        inputs: &[InputLabel],
+        optional: &[InputLabel],
+        challenges: &[ChallengeLabel]) -> DecreeResult<Decree> {
+        let all_inputs = [inputs, optional].concat();
+        let mut decree = Decree::new(name, &all_inputs, challenges)?;
+        decree.optional_inputs = static_labels(optional);
+        decree.initial_optional_inputs = decree.optional_inputs.clone();
+        Ok(decree)
+    }
+
+
+    /// The `extend` method is used to move from one phase of a protocol to the next while
+    /// maintaining Fiat-Shamir state. Calling `extend` should leave a `Decree` struct ready to
+    /// accept new inputs and generate new challenges, but without resetting the Merlin transcript.
     ///
-    /// The following code will not work, because a challenge is requested AFTER all specified
-    //// challenges are generated.
+    /// If you're used to working with Merlin transcripts directly, you can think of this as a step
+    /// that fits in between generating your latest challenge and adding your next input.
     ///
-    /// ```should_panic
-    /// # use decree::decree::{Decree, InputLabel, ChallengeLabel};
+    /// Aside from not needing a `name` input as in the `new` method, the inputs must meet the same
+    /// requirements as the `new` method.
+    ///
+    /// A stage may reuse the labels of the previous one, as in recursive protocols that run the
+    /// same round many times. Each round's challenges are still fresh, even if the inputs repeat
+    /// too: the transcript has absorbed every earlier input and challenge, so its state differs
+    /// from one round to the next. The challenge cache, if enabled, is cleared on `extend`.
+    ///
+    /// # Tests
+    /// 
+    /// Test the "happy path"
+    /// 
+    /// ```
+    /// # use decree::decree::Decree;
+    /// # use decree::decree::{InputLabel, ChallengeLabel};
     /// # use decree::error::{Error, DecreeErrType, DecreeResult};
-    /// # use decree::Inscribe;
-    /// # use decree::inscribe::{INSCRIBE_LENGTH, InscribeBuffer};
-    /// #[derive(Inscribe)]
-    /// pub struct Point {
-    ///     #[inscribe(serialize)]
-    ///     x: i32,
-    ///     #[inscribe(serialize)]
-    ///     y: i32,
-    /// }
     /// # fn main() -> DecreeResult<()> {
-    /// let input_data = "input_data_1";
+    /// # let mut challenge_out: [u8; 32] = [0u8; 32];
     /// let inputs: [InputLabel; 2] = ["input1", "input2"];
     /// let challenges: [ChallengeLabel; 2] = ["challenge1", "challenge2"];
     /// let mut my_decree = Decree::new("testname", &inputs, &challenges)?;
-    /// my_decree.add("input1", &Point{ x: 1i32, y: 2i32 })?;
-    /// my_decree.add("input2", &Point{ x: 1i32, y: 2i32 })?;
-    /// let mut challenge_out: [u8; 64] = [0u8; 64];
+    /// my_decree.add_serial("input1", "input_data_1")?;
+    /// my_decree.add_serial("input2", "input_data_2")?;
+    /// my_decree.get_challenge("challenge1", &mut challenge_out)?;
+    /// my_decree.get_challenge("challenge2", &mut challenge_out)?;
+    /// my_decree.extend(&inputs, &challenges)?;
+    /// my_decree.add_serial("input1", "input_data_3")?;
+    /// my_decree.add_serial("input2", "input_data_4")?;
     /// my_decree.get_challenge("challenge1", &mut challenge_out)?;
     /// my_decree.get_challenge("challenge2", &mut challenge_out)?;
-    /// my_decree.get_challenge("challenge_extra", &mut challenge_out)?;
     /// # Ok(())
     /// # }
     /// ```
+    pub fn extend(
+            &mut self,
+            inputs: &[InputLabel],
+            challenges: &[ChallengeLabel]) -> DecreeResult<()> {
+        self.extend_labels(static_labels(inputs), static_labels(challenges))
+    }
+
+
+    /// The `extend_owned` method behaves exactly like `extend`, but takes owned labels, as with
+    /// `new_owned`.
+    pub fn extend_owned(
+            &mut self,
+            inputs: &[String],
+            challenges: &[String]) -> DecreeResult<()> {
+        self.extend_labels(owned_labels(inputs), owned_labels(challenges))
+    }
+
+
+    /// The `extend_with_optional` method behaves exactly like `extend`, but with some optional
+    /// inputs for the new stage, as with `with_optional_inputs`.
+    pub fn extend_with_optional(
+            &mut self,
+            inputs: &[InputLabel],
+            optional: &[InputLabel],
+            challenges: &[ChallengeLabel]) -> DecreeResult<()> {
+        let all_inputs = [inputs, optional].concat();
+        self.extend_stage(
+            static_labels(&all_inputs),
+            static_labels(optional),
+            Vec::new(),
+            static_labels(challenges))
+    }
+
+
+    /// The `extend_keeping` method behaves like `extend`, but carries the values of the labels in
+    /// `keep` over from the previous stage. Kept labels are inputs of the new stage that are
+    /// already present, so they don't need to be added again; since their values were committed
+    /// in the previous stage, they aren't absorbed into the transcript a second time. Every kept
+    /// label must have been provided in the previous stage.
+    ///
+    /// # Tests
+    ///
+    /// A kept input doesn't need to be re-added
+    /// ```
+    /// # use decree::decree::{Decree, InputLabel, ChallengeLabel};
+    /// # use decree::error::{DecreeErrType, DecreeResult};
+    /// # fn main() -> DecreeResult<()> {
+    /// let inputs: [InputLabel; 2] = ["modulus", "commitment"];
+    /// let challenges: [ChallengeLabel; 1] = ["challenge1"];
+    /// let mut my_decree = Decree::new("testname", &inputs, &challenges)?;
+    /// my_decree.add_serial("modulus", 23u32)?;
+    /// my_decree.add_serial("commitment", 5u32)?;
+    /// my_decree.get_challenge_vec("challenge1", 32)?;
+    ///
+    /// let mut invalid = my_decree.clone();
+    /// let err = invalid.extend_keeping(&["response"], &["challenge2"], &["nonce"]).unwrap_err();
+    /// assert_eq!(err.get_type(), DecreeErrType::ExtendFail);
+    ///
+    /// my_decree.extend_keeping(&["response"], &["challenge2"], &["modulus"])?;
+    /// assert_eq!(my_decree.missing_inputs(), vec!["response"]);
+    /// my_decree.add_serial("response", 7u32)?;
+    /// my_decree.get_challenge_vec("challenge2", 32)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn extend_keeping(
+            &mut self,
+            inputs: &[InputLabel],
+            challenges: &[ChallengeLabel],
+            keep: &[InputLabel]) -> DecreeResult<()> {
+        let all_inputs = [inputs, keep].concat();
+        self.extend_stage(
+            static_labels(&all_inputs),
+            Vec::new(),
+            static_labels(keep),
+            static_labels(challenges))
+    }
+
+
+    // Starts the next stage with optional and kept inputs. `inputs` lists every input of the
+    // stage, including the `optional` and `keep` ones, and kept inputs carry their values over
+    // from the current stage.
+    fn extend_stage(
+            &mut self,
+            inputs: Vec<Label>,
+            optional: Vec<Label>,
+            keep: Vec<Label>,
+            challenges: Vec<Label>) -> DecreeResult<()> {
+        if optional.iter().chain(keep.iter()).any(|label| !inputs.contains(label)) {
+            return Err(Error::new_extend_fail("Optional and kept inputs must be inputs of the stage"));
+        }
+
+        let mut kept_values = Vec::with_capacity(keep.len());
+        for label in keep.iter() {
+            match self.values.get(label) {
+                Some(value) => kept_values.push((label.clone(), value.clone())),
+                None => {
+                    return Err(Error::new_extend_fail(format!(
+                        "Cannot keep input {}; it was not provided in the previous stage", label)));
+                },
+            }
+        }
+
+        self.extend_labels(inputs, challenges)?;
+        self.optional_inputs = optional;
+        self.kept_inputs = keep;
+        self.values.extend(kept_values);
+        Ok(())
+    }
+
+
+    // Validates the labels for the next stage and resets the stage state around them.
+    fn extend_labels(
+            &mut self,
+            inputs: Vec<Label>,
+            challenges: Vec<Label>) -> DecreeResult<()> {
+        // If we have pending challenges, or aren't in a committed state,
+        // bail. `is_extendable` can be used to check this ahead of time.
+        if !self.challenges.is_empty() {
+            return Err(Error::new_extend_fail(format!(
+                "Cannot extend Decree with {} unread challenge(s): {}",
+                self.challenges.len(),
+                self.challenges.join(", "))));
+        }
+        if !self.committed {
+            return Err(Error::new_extend_fail("Cannot extend Decree until all challenges generated"));
+        }
+        // Make sure we have at least one input and one output
+        if inputs.is_empty() {
+            return Err(Error::new_extend_fail("Must specify at least one input"));
+        }
+        if challenges.is_empty() {
+            return Err(Error::new_extend_fail("Must specify at least one challenge"));
+        }
+
+        // Make sure we're within our limits
+        if inputs.len() > self.limits.max_inputs {
+            return Err(Error::new_extend_fail("Too many inputs"));
+        }
+        if challenges.len() > self.limits.max_challenges {
+            return Err(Error::new_extend_fail("Too many challenges"));
+        }
+
+        // Make sure our inputs and challenges are unique
+        let mut input_labels = inputs;
+        if !vector_is_distinct(&input_labels) {
+            return Err(Error::new_extend_fail("Inputs must be distinct"));
+        }
+        if !vector_is_distinct(&challenges) {
+            return Err(Error::new_extend_fail("Challenges must be distinct"));
+        }
+
+        // We need  to sort the input labels to ensure that we have a
+        // consistent transcript.
+        input_labels.sort();
+
+        // The finished stage is only needed again if the transcript has to be replayed
+        #[cfg(feature = "serde")]
+        if let Some(history) = self.history.as_mut() {
+            let mut values = std::mem::take(&mut self.values);
+            let values = self.inputs
+                .iter()
+                .filter(|label| !self.kept_inputs.contains(label))
+                .filter_map(|label| values.remove(label).map(|value| (label.clone(), value)))
+                .collect();
+            history.finish_stage(StageSpec {
+                inputs: std::mem::take(&mut self.inputs),
+                optional_inputs: std::mem::take(&mut self.optional_inputs),
+                kept_inputs: std::mem::take(&mut self.kept_inputs),
+                challenges: std::mem::take(&mut self.stage_challenges),
+            }, values);
+        }
+
+        // Set up all the new values, leaving the transcript in place
+        self.inputs = input_labels;
+        self.optional_inputs = Vec::new();
+        self.kept_inputs = Vec::new();
+        self.stage_challenges = challenges.clone();
+        self.challenges = challenges;
+        self.values = HashMap::new();
+        self.committed = false;
+        self.challenge_index = 0;
+        if let Some(cache) = self.challenge_cache.as_mut() {
+            cache.clear();
+        }
+
+        Ok(())
+    }
+
+
+    /// The `clone_for_verification` method creates a fresh `Decree` with the same name, inputs,
+    /// and challenges that were passed to `new`, with no inputs provided. This is exactly the
+    /// state a verifier needs to re-absorb the public values it receives from the prover, without
+    /// re-listing the specification by hand.
+    ///
+    /// The returned `Decree` always starts at the first stage of the protocol, regardless of how
+    /// far `self` has progressed; stages added with `extend` must be re-extended by the verifier.
+    ///
+    /// # Tests
+    ///
+    /// Prover and verifier transcripts agree
+    /// ```
+    /// # use decree::decree::{Decree, InputLabel, ChallengeLabel};
+    /// # use decree::error::{Error, DecreeErrType, DecreeResult};
+    /// # fn main() -> DecreeResult<()> {
+    /// let inputs: [InputLabel; 2] = ["input1", "input2"];
+    /// let challenges: [ChallengeLabel; 2] = ["challenge1", "challenge2"];
+    /// let mut prover = Decree::new("testname", &inputs, &challenges)?;
+    /// let mut verifier = prover.clone_for_verification();
+    ///
+    /// let mut prover_out: [u8; 32] = [0u8; 32];
+    /// prover.add_serial("input1", 10u32)?;
+    /// prover.add_serial("input2", 14u32)?;
+    /// prover.get_challenge("challenge1", &mut prover_out)?;
+    ///
+    /// // Cloning after the prover has moved on still gives a fresh transcript
+    /// let mut late_verifier = prover.clone_for_verification();
+    ///
+    /// let mut verifier_out: [u8; 32] = [0u8; 32];
+    /// verifier.add_serial("input2", 14u32)?;
+    /// verifier.add_serial("input1", 10u32)?;
+    /// verifier.get_challenge("challenge1", &mut verifier_out)?;
+    /// assert_eq!(prover_out, verifier_out);
+    ///
+    /// let mut late_out: [u8; 32] = [0u8; 32];
+    /// late_verifier.add_serial("input1", 10u32)?;
+    /// late_verifier.add_serial("input2", 14u32)?;
+    /// late_verifier.get_challenge("challenge1", &mut late_out)?;
+    /// assert_eq!(prover_out, late_out);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn clone_for_verification(&self) -> Decree {
+        // The initial labels were validated and sorted by `new`
+        Decree{
+            name: self.name,
+            initial_inputs: self.initial_inputs.clone(),
+            initial_optional_inputs: self.initial_optional_inputs.clone(),
+            initial_challenges: self.initial_challenges.clone(),
+            inputs: self.initial_inputs.clone(),
+            optional_inputs: self.initial_optional_inputs.clone(),
+            kept_inputs: Vec::new(),
+            stage_challenges: self.initial_challenges.clone(),
+            challenges: self.initial_challenges.clone(),
+            values: HashMap::new(),
+            initial_transcript: self.initial_transcript.clone(),
+            transcript: self.initial_transcript.clone(),
+            committed: false,
+            challenge_cache: self.challenge_cache.as_ref().map(|_| Vec::new()),
+            strict_challenges: self.strict_challenges,
+            strict_binding: self.strict_binding,
+            manual_commit: self.manual_commit,
+            challenge_index: 0,
+            limits: self.limits,
+            input_bytes: 0,
+            #[cfg(feature = "serde")]
+            history: self.history.as_ref().map(History::restart)
+        }
+    }
+
+
+    /// The `reset` method returns a `Decree` to the state it was in when it was created, in place.
+    /// The transcript starts over, all provided inputs are discarded, and the inputs and
+    /// challenges from `new` are pending again. The existing label and value allocations are
+    /// reused, so this is cheaper than building a new `Decree` for each of many proofs with the
+    /// same shape, such as on a verification server.
+    ///
+    /// As with `clone_for_verification`, stages added with `extend` must be re-extended.
+    ///
+    /// # Tests
+    ///
+    /// A reset `Decree` produces the same challenges as a fresh one
+    /// ```
+    /// # use decree::decree::{Decree, InputLabel, ChallengeLabel};
+    /// # use decree::error::DecreeResult;
+    /// # fn main() -> DecreeResult<()> {
+    /// let inputs: [InputLabel; 2] = ["input1", "input2"];
+    /// let challenges: [ChallengeLabel; 1] = ["challenge1"];
+    /// let mut fresh = Decree::new("testname", &inputs, &challenges)?;
+    /// fresh.add_serial("input1", 10u32)?;
+    /// fresh.add_serial("input2", 14u32)?;
+    /// let mut fresh_out: [u8; 32] = [0u8; 32];
+    /// fresh.get_challenge("challenge1", &mut fresh_out)?;
+    ///
+    /// let mut reused = Decree::new("testname", &inputs, &challenges)?;
+    /// reused.add_serial("input1", 11u32)?;
+    /// reused.add_serial("input2", 15u32)?;
+    /// let mut reused_out: [u8; 32] = [0u8; 32];
+    /// reused.get_challenge("challenge1", &mut reused_out)?;
+    /// assert_ne!(fresh_out, reused_out);
+    ///
+    /// reused.reset();
+    /// assert_eq!(reused.missing_inputs(), vec!["input1", "input2"]);
+    /// reused.add_serial("input1", 10u32)?;
+    /// reused.add_serial("input2", 14u32)?;
+    /// reused.get_challenge("challenge1", &mut reused_out)?;
+    /// assert_eq!(fresh_out, reused_out);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn reset(&mut self) {
+        self.inputs.clone_from(&self.initial_inputs);
+        self.optional_inputs.clone_from(&self.initial_optional_inputs);
+        self.kept_inputs.clear();
+        self.stage_challenges.clone_from(&self.initial_challenges);
+        self.challenges.clone_from(&self.initial_challenges);
+        self.values.clear();
+        self.transcript = self.initial_transcript.clone();
+        self.committed = false;
+        if let Some(cache) = self.challenge_cache.as_mut() {
+            cache.clear();
+        }
+        self.challenge_index = 0;
+        self.input_bytes = 0;
+        #[cfg(feature = "serde")]
+        if let Some(history) = self.history.as_mut() {
+            *history = history.restart();
+        }
+    }
+
+
+    /// The `checkpoint` method captures the current Fiat-Shamir state, so that it can be returned
+    /// to later with `restore`. This allows tentative data to be absorbed and the resulting
+    /// challenges inspected, then rolled back if that branch fails. Unlike `extend`, which moves
+    /// the protocol forward, a checkpoint only ever rewinds it.
+    ///
+    /// # Tests
+    ///
+    /// Rolling back a tentative input reproduces the original challenge
+    /// ```
+    /// # use decree::decree::{Decree, InputLabel, ChallengeLabel};
+    /// # use decree::error::DecreeResult;
+    /// # fn main() -> DecreeResult<()> {
+    /// let inputs: [InputLabel; 2] = ["input1", "input2"];
+    /// let challenges: [ChallengeLabel; 1] = ["challenge1"];
+    /// let mut my_decree = Decree::new("testname", &inputs, &challenges)?;
+    /// my_decree.add_serial("input1", 10u32)?;
+    /// let checkpoint = my_decree.checkpoint();
+    ///
+    /// my_decree.add_serial("input2", 14u32)?;
+    /// let original = my_decree.get_challenge_vec("challenge1", 32)?;
+    ///
+    /// // Try a different value, then roll back
+    /// my_decree.restore(checkpoint.clone());
+    /// my_decree.add_serial("input2", 15u32)?;
+    /// let tentative = my_decree.get_challenge_vec("challenge1", 32)?;
+    /// assert_ne!(original, tentative);
+    ///
+    /// my_decree.restore(checkpoint);
+    /// assert_eq!(my_decree.missing_inputs(), vec!["input2"]);
+    /// my_decree.add_serial("input2", 14u32)?;
+    /// assert_eq!(my_decree.get_challenge_vec("challenge1", 32)?, original);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn checkpoint(&self) -> DecreeCheckpoint {
+        DecreeCheckpoint{
+            inputs: self.inputs.clone(),
+            optional_inputs: self.optional_inputs.clone(),
+            kept_inputs: self.kept_inputs.clone(),
+            stage_challenges: self.stage_challenges.clone(),
+            challenges: self.challenges.clone(),
+            values: self.values.clone(),
+            transcript: self.transcript.clone(),
+            committed: self.committed,
+            challenge_cache: self.challenge_cache.clone(),
+            challenge_index: self.challenge_index,
+            input_bytes: self.input_bytes,
+            #[cfg(feature = "serde")]
+            history: self.history.clone()
+        }
+    }
+
+
+    /// The `restore` method returns a `Decree` to the state captured by `checkpoint`. The
+    /// checkpoint should come from the same `Decree` (or a clone of it); restoring a checkpoint
+    /// from an unrelated `Decree` replaces its state wholesale.
+    pub fn restore(&mut self, checkpoint: DecreeCheckpoint) {
+        self.inputs = checkpoint.inputs;
+        self.optional_inputs = checkpoint.optional_inputs;
+        self.kept_inputs = checkpoint.kept_inputs;
+        self.stage_challenges = checkpoint.stage_challenges;
+        self.challenges = checkpoint.challenges;
+        self.values = checkpoint.values;
+        self.transcript = checkpoint.transcript;
+        self.committed = checkpoint.committed;
+        self.challenge_cache = checkpoint.challenge_cache;
+        self.challenge_index = checkpoint.challenge_index;
+        self.input_bytes = checkpoint.input_bytes;
+        // A checkpoint taken from an unrelated `Decree` has a history that this one can't replay
+        #[cfg(feature = "serde")]
+        {
+            self.history = match (self.history.take(), checkpoint.history) {
+                (Some(own), Some(restored)) if Arc::ptr_eq(&own.origin, &restored.origin) => Some(restored),
+                _ => None,
+            };
+        }
+    }
+
+
+    /// Returns `true` if the inputs for the current stage have been committed to the transcript.
+    /// This happens automatically once every specified input has been provided.
+    ///
+    /// # Tests
+    ///
+    /// Walk through the state transitions of a two-stage protocol
+    /// ```
+    /// # use decree::decree::{Decree, InputLabel, ChallengeLabel};
+    /// # use decree::error::{Error, DecreeErrType, DecreeResult};
+    /// # fn main() -> DecreeResult<()> {
+    /// let inputs: [InputLabel; 2] = ["input1", "input2"];
+    /// let challenges: [ChallengeLabel; 2] = ["challenge1", "challenge2"];
+    /// let mut challenge_out: [u8; 32] = [0u8; 32];
+    /// let mut my_decree = Decree::new("testname", &inputs, &challenges)?;
+    ///
+    /// // Waiting on inputs
+    /// assert!(!my_decree.is_committed());
+    /// assert!(my_decree.has_pending_challenges());
+    /// assert!(!my_decree.is_sealed());
+    /// assert!(!my_decree.is_extendable());
+    ///
+    /// // Still waiting on `input2`
+    /// my_decree.add_serial("input1", 10u32)?;
+    /// assert!(!my_decree.is_committed());
+    ///
+    /// // Inputs committed, but challenges are still pending
+    /// my_decree.add_serial("input2", 14u32)?;
+    /// assert!(my_decree.is_committed());
+    /// assert!(my_decree.has_pending_challenges());
+    /// assert!(!my_decree.is_sealed());
+    /// assert!(!my_decree.is_extendable());
+    ///
+    /// my_decree.get_challenge("challenge1", &mut challenge_out)?;
+    /// assert!(my_decree.has_pending_challenges());
+    /// assert!(!my_decree.is_sealed());
+    ///
+    /// // The stage is complete
+    /// my_decree.get_challenge("challenge2", &mut challenge_out)?;
+    /// assert!(my_decree.is_committed());
+    /// assert!(!my_decree.has_pending_challenges());
+    /// assert!(my_decree.is_sealed());
+    /// assert!(my_decree.is_extendable());
+    ///
+    /// // Extending starts the cycle over
+    /// my_decree.extend(&inputs, &challenges)?;
+    /// assert!(!my_decree.is_committed());
+    /// assert!(my_decree.has_pending_challenges());
+    /// assert!(!my_decree.is_sealed());
+    /// assert!(!my_decree.is_extendable());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn is_committed(&self) -> bool {
+        self.committed
+    }
+
+
+    /// Returns `true` if there are challenges in the current stage that have not been generated.
+    pub fn has_pending_challenges(&self) -> bool {
+        !self.challenges.is_empty()
+    }
+
+
+    /// Returns `true` if the current stage is complete: all inputs have been committed and all
+    /// challenges have been generated. A sealed `Decree` accepts no further inputs or challenge
+    /// requests until it is extended.
+    pub fn is_sealed(&self) -> bool {
+        self.committed && self.challenges.is_empty()
+    }
+
+
+    /// Returns `true` if a call to `extend` with valid labels would succeed. This allows callers to
+    /// branch on whether the current stage is complete, rather than on the error from `extend`.
+    pub fn is_extendable(&self) -> bool {
+        self.is_sealed()
+    }
+
+
+    /// Returns the challenges in the current stage that have not yet been generated, in the order
+    /// they must be requested.
+    ///
+    /// # Tests
+    ///
+    /// The pending challenges shrink in order, and are empty before a legal `extend`
+    /// ```
+    /// # use decree::decree::{Decree, InputLabel, ChallengeLabel};
+    /// # use decree::error::DecreeResult;
+    /// # fn main() -> DecreeResult<()> {
+    /// let inputs: [InputLabel; 1] = ["input1"];
+    /// let challenges: [ChallengeLabel; 3] = ["challenge1", "challenge2", "challenge3"];
+    /// let mut my_decree = Decree::new("testname", &inputs, &challenges)?;
+    /// assert_eq!(my_decree.remaining_challenges(), &challenges);
+    ///
+    /// my_decree.add_serial("input1", 1u32)?;
+    /// let mut challenge_out: [u8; 32] = [0u8; 32];
+    /// my_decree.get_challenge("challenge1", &mut challenge_out)?;
+    /// assert_eq!(my_decree.remaining_challenges(), &["challenge2", "challenge3"]);
+    /// my_decree.get_challenge("challenge2", &mut challenge_out)?;
+    /// assert_eq!(my_decree.remaining_challenges(), &["challenge3"]);
+    /// my_decree.get_challenge("challenge3", &mut challenge_out)?;
+    /// assert!(my_decree.remaining_challenges().is_empty());
+    ///
+    /// my_decree.extend(&inputs, &challenges)?;
+    /// assert_eq!(my_decree.remaining_challenges(), &challenges);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn remaining_challenges(&self) -> &[Label] {
+        &self.challenges
+    }
+
+
+    /// Returns the number of challenges in the current stage that have not yet been generated.
+    pub fn challenges_remaining_count(&self) -> usize {
+        self.challenges.len()
+    }
+
+
+    /// Returns the name the `Decree` was created with.
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+
+    /// Returns every input label in the current stage, whether or not a value has been provided,
+    /// in the order they are committed to the transcript. Optional inputs are included.
+    ///
+    /// # Tests
+    ///
+    /// The labels follow the most recent stage
+    /// ```
+    /// # use decree::decree::{Decree, InputLabel, ChallengeLabel};
+    /// # use decree::error::DecreeResult;
+    /// # fn main() -> DecreeResult<()> {
+    /// let inputs: [InputLabel; 2] = ["input2", "input1"];
+    /// let challenges: [ChallengeLabel; 2] = ["challenge1", "challenge2"];
+    /// let mut my_decree = Decree::new("testname", &inputs, &challenges)?;
+    /// assert_eq!(my_decree.name(), "testname");
+    /// assert_eq!(my_decree.input_labels(), vec!["input1", "input2"]);
+    /// assert_eq!(my_decree.challenge_labels(), vec!["challenge1", "challenge2"]);
+    ///
+    /// my_decree.add_serial("input1", 1u32)?;
+    /// my_decree.add_serial("input2", 2u32)?;
+    /// let _: [u8; 32] = my_decree.challenge("challenge1")?;
+    /// assert_eq!(my_decree.challenge_labels(), vec!["challenge1", "challenge2"]);
+    /// assert_eq!(my_decree.challenges_remaining_count(), 1);
+    /// let _: [u8; 32] = my_decree.challenge("challenge2")?;
+    /// assert_eq!(my_decree.challenges_remaining_count(), 0);
+    ///
+    /// my_decree.extend(&["input3"], &["challenge3"])?;
+    /// assert_eq!(my_decree.name(), "testname");
+    /// assert_eq!(my_decree.input_labels(), vec!["input3"]);
+    /// assert_eq!(my_decree.challenge_labels(), vec!["challenge3"]);
+    /// assert_eq!(my_decree.challenges_remaining_count(), 1);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn input_labels(&self) -> &[Label] {
+        &self.inputs
+    }
+
+
+    /// Returns every challenge label in the current stage, including those already generated, in
+    /// the order they must be requested. `remaining_challenges` lists only the pending ones.
+    pub fn challenge_labels(&self) -> &[Label] {
+        &self.stage_challenges
+    }
+
+
+    /// Returns the inputs in the current stage that do not yet have an associated value. Until
+    /// this is empty, `get_challenge` will fail.
+    ///
+    /// # Tests
+    ///
+    /// Partially and fully committed inputs
+    /// ```
+    /// # use decree::decree::{Decree, InputLabel, ChallengeLabel};
+    /// # use decree::error::DecreeResult;
+    /// # fn main() -> DecreeResult<()> {
+    /// let inputs: [InputLabel; 2] = ["input1", "input2"];
+    /// let challenges: [ChallengeLabel; 1] = ["challenge1"];
+    /// let mut my_decree = Decree::new("testname", &inputs, &challenges)?;
+    /// assert_eq!(my_decree.missing_inputs(), vec!["input1", "input2"]);
+    ///
+    /// my_decree.add_serial("input2", 1u32)?;
+    /// assert_eq!(my_decree.missing_inputs(), vec!["input1"]);
+    ///
+    /// my_decree.add_serial("input1", 2u32)?;
+    /// assert!(my_decree.missing_inputs().is_empty());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn missing_inputs(&self) -> Vec<&str> {
+        self.inputs
+            .iter()
+            .filter(|label| !self.values.contains_key(*label) && !self.optional_inputs.contains(label))
+            .map(|label| label.as_ref())
+            .collect()
+    }
+
+
+    /// Returns the number of inputs in the current stage that do not yet have an associated value;
+    /// this is the length of `missing_inputs`, without allocating. Optional inputs are not counted.
+    pub fn inputs_remaining(&self) -> usize {
+        self.inputs
+            .iter()
+            .filter(|label| !self.values.contains_key(*label) && !self.optional_inputs.contains(label))
+            .count()
+    }
+
+
+    // Commits the current stage if every required input has been provided, even if some optional
+    // inputs have not. This is called before challenges are generated.
+    fn commit_required(&mut self) -> DecreeResult<()> {
+        if self.committed || self.manual_commit {
+            return Ok(());
+        }
+        let required_present = self.inputs
+            .iter()
+            .all(|label| self.optional_inputs.contains(label) || self.values.contains_key(label));
+        if required_present {
+            self.commit_values()?;
+        }
+        Ok(())
+    }
+
+
+    /// The `commit` method commits the inputs of the current stage to the transcript. This is
+    /// required in manual commit mode (see `new_manual`); otherwise, inputs are committed
+    /// automatically. It is an error to commit twice, or while a required input is missing.
+    ///
+    /// # Tests
+    ///
+    /// Committing is rejected until every required input has been added
+    /// ```
+    /// # use decree::decree::{Decree, InputLabel, ChallengeLabel};
+    /// # use decree::error::DecreeResult;
+    /// # fn main() -> DecreeResult<()> {
+    /// let inputs: [InputLabel; 2] = ["input1", "input2"];
+    /// let challenges: [ChallengeLabel; 1] = ["challenge1"];
+    /// let mut my_decree = Decree::new_manual("testname", &inputs, &challenges)?;
+    /// my_decree.add_serial("input1", 10u32)?;
+    /// assert!(my_decree.commit().is_err());
+    /// my_decree.add_serial("input2", 14u32)?;
+    /// my_decree.commit()?;
+    /// assert!(my_decree.commit().is_err());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn commit(&mut self) -> DecreeResult<()> {
+        if self.committed {
+            return Err(Error::new_general("Inputs already committed"));
+        }
+        let missing = self.missing_inputs();
+        if !missing.is_empty() {
+            return Err(Error::new_general(format!("Cannot commit with missing input(s): {}", missing.join(", "))));
+        }
+        self.commit_values()
+    }
+
+
+    /// The `commit_now` method makes sure the inputs of the current stage are committed, so that
+    /// a commit failure surfaces at a call site of the caller's choosing, rather than in whichever
+    /// `add` call supplies the last input. Unlike `commit`, it succeeds if the inputs are already
+    /// committed, whether automatically or by an earlier call; it fails, listing the missing
+    /// inputs, if a required input hasn't been added.
+    pub fn commit_now(&mut self) -> DecreeResult<()> {
+        if self.committed {
+            return Ok(());
+        }
+        self.commit()
+    }
+
+
+    fn can_commit(&self) -> bool {
+        // If we already committed the current values, don't do it again
+        if self.committed {
+            return false;
+        }
+
+        // Bail if any specified input lacks an associated value
+        for label in self.inputs.iter() {
+            if !self.values.contains_key(label) {
+                return false;
+            }
+        }
+
+        // Otherwise, we have all our inputs and we can commit
+        true
+    }
+
+
+    // The `commit_values` method actually writes the Fiat-Shamir values into the transcript. It
+    // should only be called when every required element of the `inputs` vector has a matching
+    // entry in the `values` hash map.
+    fn commit_values(&mut self) -> DecreeResult<()> {
+        // In strict binding mode, the stage specification precedes the values
+        if self.strict_binding {
+            for input_label in self.inputs.iter() {
+                self.transcript.append_message(BINDING_INPUT_LABEL, input_label.as_bytes());
+            }
+            for challenge_label in self.challenges.iter() {
+                self.transcript.append_message(BINDING_CHALLENGE_LABEL, challenge_label.as_bytes());
+            }
+        }
+
+        // We iterate over the input label vector because the HashMap
+        // item iterator does not provide the elements in any guaranteed
+        // order.
+        for input_label in self.inputs.iter() {
+            // Kept inputs were committed in an earlier stage, and are already in the transcript
+            if self.kept_inputs.contains(input_label) {
+                continue;
+            }
+            let value = self.values.get(input_label);
+
+            // Optional inputs are preceded by whether or not they are present, so that an absent
+            // input can't be confused with any value
+            if self.optional_inputs.contains(input_label) {
+                self.transcript.append_message(OPTIONAL_PRESENCE_LABEL, &[value.is_some() as u8]);
+                if value.is_none() {
+                    continue;
+                }
+            }
+
+            let value = match value {
+                Some(a) => a,
+                None => { return Err(Error::new_general("Error in label processing")); }
+            };
+            let frame = [
+                (input_label.len() as u64).to_le_bytes(),
+                (value.len() as u64).to_le_bytes()].concat();
+            self.transcript.append_message(INPUT_FRAME_LABEL, &frame);
+            append_labeled(&mut self.transcript, input_label, value.as_slice());
+        }
+
+        // Set the committed flag
+        self.committed = true;
+
+        Ok(())
+    }
+
+    fn add_input(
+            &mut self,
+            label: &str,
+            input: FSInput) -> DecreeResult<()> {
+        // Take ownership first, so that the input is wiped on every path
+        let input = StoredInput::from(input);
+
+        // If we're already committed, we can't add new values
+        if self.committed {
+            return Err(Error::new_general("Cannot add values after commitment").with_label(label.to_owned()));
+        }
+
+        // Invalid inputs should result in an error
+        let key = match self.inputs.iter().find(|input| *input == label) {
+            Some(input) => input.clone(),
+            None => { return Err(Error::new_invalid_label("Invalid label").with_label(label.to_owned())); }
+        };
+
+        // Re-definition of an input should result in an error
+        if self.values.contains_key(label) {
+            return Err(Error::new_invalid_label("Label already used").with_label(key));
+        }
+
+        // Make sure we don't exceed our size limit
+        // The running total may already exceed the limit, e.g. after restoring a checkpoint taken
+        // under looser limits, so this can't subtract from the limit
+        if self.input_bytes.checked_add(input.len()).is_none_or(|total| total > self.limits.max_input_bytes) {
+            return Err(Error::new_general("Input size limit exceeded").with_label(key));
+        }
+        self.input_bytes += input.len();
+
+        // Add the input to the map
+        self.values.insert(
+            key,
+            input
+        );
+
+        // If this is the last input, go ahead and commit the values (unless the caller commits)
+        if !self.manual_commit && self.can_commit() {
+            self.commit_values()?;
+        }
+        Ok(())
+    }
+
+    /// The `add_serial` method associates the BCS serialization of a value with the given input
+    /// label. This should be used when a Fiat-Shamir input supports the `Serialize` trait, but
+    /// not the `Inscribe` trait.
+    ///
+    /// Since `Serialize` is implemented for references, the input can be passed by reference to
+    /// avoid moving or cloning it; BCS serializes `&T` exactly as it serializes `T`.
+    ///
+    /// # Panics
+    ///
+    /// If `label` is not a valid label specified in the most recent `new` or `extend` call.
+    ///
+    /// If `label` has already been used in a call to `add_serial`, or `add`
+    ///
+    /// If all inputs already have associated inputs.
+    ///
+    /// If `label` is the last value to be processed, and an error occurs during commitment.
+    ///
+    /// # Tests
+    /// 
+    /// Test the "happy path"
+    /// 
+    /// ```
+    /// # use decree::decree::Decree;
+    /// # use decree::decree::{InputLabel, ChallengeLabel};
+    /// # use decree::error::{Error, DecreeErrType, DecreeResult};
+    /// # use serde::Serialize;
+    /// #[derive(Serialize)]
+    /// pub struct Point {
+    ///     x: i32,
+    ///     y: i32,
+    /// }
+    /// # fn main() -> DecreeResult<()> {
+    /// let input_data = "input_data_1";
+    /// let inputs: [InputLabel; 2] = ["input1", "input2"];
+    /// let challenges: [ChallengeLabel; 2] = ["challenge1", "challenge2"];
+    /// let mut my_decree = Decree::new("testname", &inputs, &challenges)?;
+    /// my_decree.add_serial("input1", Point{ x: 1i32, y: 2i32 })?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn add_serial<T: Serialize>(
+            &mut self,
+            label: &str,
+            input: T) -> DecreeResult<()> {
+        let bytevec = match to_bytes::<T>(&input) {
+            Ok(a) => a,
+            Err(e) => {
+                return Err(Error::new_serialization(format!("Could not serialize input {}: {}", label, e)));
+            }
+        };
+        self.add_input(label, bytevec)
+    }
+
+
+    /// The `add_bytes` method associates a raw byte string with the given input label. The bytes
+    /// are contributed verbatim to the Merlin message, without BCS serialization; this is useful
+    /// for pre-hashed commitments or values that are already in a wire format.
+    ///
+    /// Note that `add_serial` on a `Vec<u8>` or `&[u8]` will prepend a BCS length prefix, so
+    /// the two methods do not produce the same transcript for the same bytes.
+    ///
+    /// # Panics
+    ///
+    /// If `label` is not a valid label specified in the most recent `new` or `extend` call.
+    ///
+    /// If `label` has already been used in a call to `add_serial`, `add_bytes`, or `add`
+    ///
+    /// If all inputs already have associated inputs.
+    ///
+    /// If `label` is the last value to be processed, and an error occurs during commitment.
+    ///
+    /// # Tests
+    ///
+    /// Test the "happy path"
+    ///
+    /// ```
+    /// # use decree::decree::{Decree, InputLabel, ChallengeLabel};
+    /// # use decree::error::DecreeResult;
+    /// # fn main() -> DecreeResult<()> {
+    /// let inputs: [InputLabel; 2] = ["input1", "input2"];
+    /// let challenges: [ChallengeLabel; 1] = ["challenge1"];
+    /// let mut my_decree = Decree::new("testname", &inputs, &challenges)?;
+    /// my_decree.add_bytes("input1", b"some commitment")?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn add_bytes(
+            &mut self,
+            label: &str,
+            bytes: &[u8]) -> DecreeResult<()> {
+        self.add_input(label, bytes.to_vec())
+    }
+
+
+    /// The `add` method associates the inscription of an object with the given input
+    /// label. This should always be used when a Fiat-Shamir input supports the `Inscribe`
+    /// trait.
+    ///
+    /// The input is borrowed, so large values (such as RSA moduli or vectors of group elements)
+    /// don't need to be cloned to be added, and remain usable afterwards.
+    ///
+    /// # Panics
+    ///
+    /// If `label` is not a valid label specified in the most recent `new` or `extend` call.
+    ///
+    /// If `label` has already been used in a call to `add_serial` or `add`
+    ///
+    /// If all inputs already have associated inputs.
+    ///
+    /// If `label` is the last value to be processed, and an error occurs during commitment.
+    ///
+    /// # Tests
+    /// 
+    /// Test the "happy path"
+    /// 
+    /// ```
+    /// # use decree::decree::{Decree, InputLabel, ChallengeLabel};
+    /// # use decree::error::{Error, DecreeErrType, DecreeResult};
+    /// # use decree::Inscribe;
+    /// # use decree::inscribe::{INSCRIBE_LENGTH, InscribeBuffer};
+    /// #[derive(Inscribe)]
+    /// pub struct Point {
+    ///     #[inscribe(serialize)]
+    ///     x: i32,
+    ///     #[inscribe(serialize)]
+    ///     y: i32,
+    /// }
+    /// # fn main() -> DecreeResult<()> {
+    /// let input_data = "input_data_1";
+    /// let inputs: [InputLabel; 2] = ["input1", "input2"];
+    /// let challenges: [ChallengeLabel; 2] = ["challenge1", "challenge2"];
+    /// let mut my_decree = Decree::new("testname", &inputs, &challenges)?;
+    /// my_decree.add("input1", &Point{ x: 1i32, y: 2i32 })?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn add<T: Inscribe + ?Sized>(
+            &mut self,
+            label: &str,
+            input: &T) -> DecreeResult<()> {
+        let inscription = input.get_inscription()?;
+        self.add_input(label, inscription)
+    }
+
+
+    /// The `absorb_subtranscript` method adds the final state of a completed sub-protocol's
+    /// `Decree` as the value of `label`, for protocols that run independent sub-proofs in parallel
+    /// and then combine them. A 64-byte digest is squeezed from a copy of the sub-transcript under
+    /// a label reserved for this purpose, so the digest is domain-separated from the
+    /// sub-protocol's own challenges, and `sub` itself is left unchanged. The sub-`Decree` must be
+    /// sealed: all of its inputs committed and all of its challenges generated.
+    ///
+    /// # Tests
+    ///
+    /// ```
+    /// # use decree::decree::{Decree, InputLabel, ChallengeLabel};
+    /// # use decree::error::DecreeResult;
+    /// # fn main() -> DecreeResult<()> {
+    /// let mut sub = Decree::new("subproof", &["commitment"], &["challenge"])?;
+    /// sub.add_serial("commitment", 7u32)?;
+    ///
+    /// // Pending challenges mean the sub-proof isn't finished
+    /// let mut parent = Decree::new("parent", &["subproof"], &["challenge1"])?;
+    /// assert!(parent.absorb_subtranscript("subproof", &sub).is_err());
+    ///
+    /// sub.get_challenge_vec("challenge", 32)?;
+    /// parent.absorb_subtranscript("subproof", &sub)?;
+    /// parent.get_challenge_vec("challenge1", 32)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn absorb_subtranscript(
+            &mut self,
+            label: &str,
+            sub: &Decree) -> DecreeResult<()> {
+        if !sub.is_sealed() {
+            return Err(Error::new_general("Subtranscript must be sealed before it is absorbed"));
+        }
+        let mut transcript = sub.transcript.clone();
+        let mut digest = vec![0u8; SUBTRANSCRIPT_DIGEST_LENGTH];
+        transcript.challenge_bytes(SUBTRANSCRIPT_LABEL, &mut digest);
+        self.add_input(label, digest)
+    }
+
+
+    /// The `add_iter` method associates a sequence of `Inscribe` values with a single input
+    /// label, such as all the leaves of a Merkle commitment. The value is the same as the
+    /// inscription of a `Vec` of the same elements, but each element is streamed into the hasher
+    /// as it is produced, so the sequence never needs to be collected. The count is absorbed
+    /// first, so the iterator must know its length up front.
+    ///
+    /// # Tests
+    ///
+    /// Streaming a sequence is the same as adding it as a `Vec`
+    /// ```
+    /// # use decree::decree::{Decree, InputLabel, ChallengeLabel};
+    /// # use decree::error::DecreeResult;
+    /// # fn main() -> DecreeResult<()> {
+    /// let inputs: [InputLabel; 1] = ["leaves"];
+    /// let challenges: [ChallengeLabel; 1] = ["challenge1"];
+    ///
+    /// let mut streamed = Decree::new("testname", &inputs, &challenges)?;
+    /// streamed.add_iter("leaves", (0u32..1000).map(|leaf| u64::from(leaf) * 3))?;
+    ///
+    /// let mut collected = Decree::new("testname", &inputs, &challenges)?;
+    /// let leaves: Vec<u64> = (0u32..1000).map(|leaf| u64::from(leaf) * 3).collect();
+    /// collected.add("leaves", &leaves)?;
+    ///
+    /// assert_eq!(streamed.get_challenge_vec("challenge1", 32)?, collected.get_challenge_vec("challenge1", 32)?);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn add_iter<T, I>(
+            &mut self,
+            label: &str,
+            iter: I) -> DecreeResult<()>
+        where T: Inscribe,
+              I: IntoIterator<Item = T>,
+              I::IntoIter: ExactSizeIterator {
+        let inscription = inscribe_sequence(iter.into_iter())?;
+        self.add_input(label, inscription)
+    }
+
+
+    /// The `absorb` method adds an input using the encoding that matches its type: `Inscribe`
+    /// values go through `add`, and values wrapped in `Serial` go through `add_serial`. Since
+    /// `add_serial` also accepts `Inscribe` types that happen to be `Serialize`, but encodes them
+    /// differently, `absorb` makes the choice explicit at the call site.
+    ///
+    /// # Tests
+    ///
+    /// `absorb` matches `add` and `add_serial`
+    /// ```
+    /// # use decree::decree::{Decree, InputLabel, ChallengeLabel, Serial};
+    /// # use decree::error::DecreeResult;
+    /// # use decree::Inscribe;
+    /// #[derive(Inscribe)]
+    /// pub struct Point {
+    ///     #[inscribe(serialize)]
+    ///     x: i32,
+    ///     #[inscribe(serialize)]
+    ///     y: i32,
+    /// }
+    /// # fn main() -> DecreeResult<()> {
+    /// let inputs: [InputLabel; 2] = ["point", "count"];
+    /// let challenges: [ChallengeLabel; 1] = ["challenge1"];
+    ///
+    /// let mut absorbed = Decree::new("testname", &inputs, &challenges)?;
+    /// absorbed.absorb("point", &Point { x: 1, y: 2 })?;
+    /// absorbed.absorb("count", &Serial(3u64))?;
+    ///
+    /// let mut added = Decree::new("testname", &inputs, &challenges)?;
+    /// added.add("point", &Point { x: 1, y: 2 })?;
+    /// added.add_serial("count", 3u64)?;
+    ///
+    /// assert_eq!(absorbed.get_challenge_vec("challenge1", 32)?, added.get_challenge_vec("challenge1", 32)?);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn absorb<T: IntoFSInput + ?Sized>(
+            &mut self,
+            label: &str,
+            input: &T) -> DecreeResult<()> {
+        input.absorb_into(self, label)
+    }
+
+
+    /// The `append_raw_message` method appends a message directly to the underlying Merlin
+    /// transcript, for data that doesn't fit the labeled-input model, such as a protocol version
+    /// byte. This is an escape hatch: the message bypasses label tracking entirely, so nothing
+    /// checks that the prover and verifier append the same raw messages. Prefer a declared input
+    /// wherever possible.
+    ///
+    /// Raw messages can only be appended while the current stage's inputs are uncommitted. Since
+    /// inputs are held until the stage is committed, raw messages always precede the stage's
+    /// inputs in the transcript, regardless of the order of calls.
+    ///
+    /// # Panics
+    ///
+    /// If the inputs for the current stage have already been committed.
+    ///
+    /// # Tests
+    ///
+    /// A raw message changes the challenge, whenever it is appended before commitment
+    /// ```
+    /// # use decree::decree::{Decree, InputLabel, ChallengeLabel};
+    /// # use decree::error::DecreeResult;
+    /// # fn main() -> DecreeResult<()> {
+    /// let inputs: [InputLabel; 2] = ["input1", "input2"];
+    /// let challenges: [ChallengeLabel; 1] = ["challenge1"];
+    ///
+    /// let mut plain = Decree::new("testname", &inputs, &challenges)?;
+    /// plain.add_serial("input1", 10u32)?;
+    /// plain.add_serial("input2", 14u32)?;
+    /// let plain_out: [u8; 32] = plain.challenge("challenge1")?;
+    ///
+    /// let mut early = Decree::new("testname", &inputs, &challenges)?;
+    /// early.append_raw_message(b"protocol-version", &[2u8])?;
+    /// early.add_serial("input1", 10u32)?;
+    /// early.add_serial("input2", 14u32)?;
+    /// let early_out: [u8; 32] = early.challenge("challenge1")?;
+    /// assert_ne!(plain_out, early_out);
+    ///
+    /// let mut late = Decree::new("testname", &inputs, &challenges)?;
+    /// late.add_serial("input1", 10u32)?;
+    /// late.append_raw_message(b"protocol-version", &[2u8])?;
+    /// late.add_serial("input2", 14u32)?;
+    /// let late_out: [u8; 32] = late.challenge("challenge1")?;
+    /// assert_eq!(early_out, late_out);
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// Raw messages can't be appended after commitment
+    /// ```should_panic
+    /// # use decree::decree::{Decree, InputLabel, ChallengeLabel};
+    /// # use decree::error::DecreeResult;
+    /// # fn main() -> DecreeResult<()> {
+    /// let inputs: [InputLabel; 1] = ["input1"];
+    /// let challenges: [ChallengeLabel; 1] = ["challenge1"];
+    /// let mut my_decree = Decree::new("testname", &inputs, &challenges)?;
+    /// my_decree.add_serial("input1", 10u32)?;
+    /// my_decree.append_raw_message(b"protocol-version", &[2u8])?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn append_raw_message(
+            &mut self,
+            label: &'static [u8],
+            message: &[u8]) -> DecreeResult<()> {
+        if self.committed {
+            return Err(Error::new_general("Cannot append raw messages after commitment"));
+        }
+        self.transcript.append_message(label, message);
+        #[cfg(feature = "serde")]
+        if let Some(history) = self.history.as_mut() {
+            history.raw_messages.push((label, message.to_vec()));
+        }
+        Ok(())
+    }
+
+
+    /// The `get_challenge` method extracts a challenge value from the underlying Merlin
+    /// transcript. The `challenge` argument specifies which challenge to generate. As part of the
+    /// Fiat-Shamir enforcement system, the challenges _must_ be generated with labels given in
+    /// the same order they were provided in the most recent call to `new` or `extend`.
+    ///
+    /// Challenges may not be generated PRIOR to all inputs being provided. This is to prevent
+    /// implementations from accidentally leaving out security-critical values.
+    /// 
+    /// # Panics
+    /// If the challenge label `challenge` is not specified during that last call to `new` or
+    /// `extend`.
+    ///
+    /// If the challenge label `challenge` does not match the next expected challenge
+    ///
+    /// If no challenges remain to be generated
+    ///
+    /// If no challenges can be generated because of incomplete inputs
+    ///
+    /// If `dest` is empty, since a zero-byte challenge is almost always a bug
+    /// 
+    /// # Examples
+    ///
+    /// The following code should succeed
+    /// ```
+    /// # use decree::decree::{Decree, InputLabel, ChallengeLabel};
+    /// # use decree::error::{Error, DecreeErrType, DecreeResult};
+    /// # use decree::Inscribe;
+    /// # use decree::inscribe::{INSCRIBE_LENGTH, InscribeBuffer};
+    /// #[derive(Inscribe)]
+    /// pub struct Point {
+    ///     #[inscribe(serialize)]
+    ///     x: i32,
+    ///     #[inscribe(serialize)]
+    ///     y: i32,
+    /// }
+    /// # fn main() -> DecreeResult<()> {
+    /// let input_data = "input_data_1";
+    /// let inputs: [InputLabel; 2] = ["input1", "input2"];
+    /// let challenges: [ChallengeLabel; 2] = ["challenge1", "challenge2"];
+    /// let mut my_decree = Decree::new("testname", &inputs, &challenges)?;
+    /// my_decree.add("input1", &Point{ x: 1i32, y: 2i32 })?;
+    /// let mut challenge_out: [u8; 64] = [0u8; 64];
+    /// my_decree.get_challenge("challenge1", &mut challenge_out);
+    /// my_decree.get_challenge("challenge2", &mut challenge_out);
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Tests
+    ///
+    /// The following code will not work, because the challenges are requested out of order
+    ///
+    /// ```should_panic
+    /// # use decree::decree::{Decree, InputLabel, ChallengeLabel};
+    /// # use decree::error::{Error, DecreeErrType, DecreeResult};
+    /// # use decree::Inscribe;
+    /// # use decree::inscribe::{INSCRIBE_LENGTH, InscribeBuffer};
+    /// #[derive(Inscribe)]
+    /// pub struct Point {
+    ///     #[inscribe(serialize)]
+    ///     x: i32,
+    ///     #[inscribe(serialize)]
+    ///     y: i32,
+    /// }
+    /// # fn main() -> DecreeResult<()> {
+    /// let input_data = "input_data_1";
+    /// let inputs: [InputLabel; 2] = ["input1", "input2"];
+    /// let challenges: [ChallengeLabel; 2] = ["challenge1", "challenge2"];
+    /// let mut my_decree = Decree::new("testname", &inputs, &challenges)?;
+    /// my_decree.add("input1", &Point{ x: 1i32, y: 2i32 })?;
+    /// my_decree.add("input2", &Point{ x: 1i32, y: 2i32 })?;
+    /// let mut challenge_out: [u8; 64] = [0u8; 64];
+    /// my_decree.get_challenge("challenge2", &mut challenge_out)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// The following code will not work, because the challenge is not specified
+    ///
+    /// ```should_panic
+    /// # use decree::decree::{Decree, InputLabel, ChallengeLabel};
+    /// # use decree::error::{Error, DecreeErrType, DecreeResult};
+    /// # use decree::Inscribe;
+    /// # use decree::inscribe::{INSCRIBE_LENGTH, InscribeBuffer};
+    /// #[derive(Inscribe)]
+    /// pub struct Point {
+    ///     #[inscribe(serialize)]
+    ///     x: i32,
+    ///     #[inscribe(serialize)]
+    ///     y: i32,
+    /// }
+    /// # fn main() -> DecreeResult<()> {
+    /// let input_data = "input_data_1";
+    /// let inputs: [InputLabel; 2] = ["input1", "input2"];
+    /// let challenges: [ChallengeLabel; 2] = ["challenge1", "challenge2"];
+    /// let mut my_decree = Decree::new("testname", &inputs, &challenges)?;
+    /// my_decree.add("input1", &Point{ x: 1i32, y: 2i32 })?;
+    /// my_decree.add("input2", &Point{ x: 1i32, y: 2i32 })?;
+    /// let mut challenge_out: [u8; 64] = [0u8; 64];
+    /// my_decree.get_challenge("invalid_challenge", &mut challenge_out)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// The following code will not work, because a challenge is requested AFTER all specified
+    //// challenges are generated.
+    ///
+    /// ```should_panic
+    /// # use decree::decree::{Decree, InputLabel, ChallengeLabel};
+    /// # use decree::error::{Error, DecreeErrType, DecreeResult};
+    /// # use decree::Inscribe;
+    /// # use decree::inscribe::{INSCRIBE_LENGTH, InscribeBuffer};
+    /// #[derive(Inscribe)]
+    /// pub struct Point {
+    ///     #[inscribe(serialize)]
+    ///     x: i32,
+    ///     #[inscribe(serialize)]
+    ///     y: i32,
+    /// }
+    /// # fn main() -> DecreeResult<()> {
+    /// let input_data = "input_data_1";
+    /// let inputs: [InputLabel; 2] = ["input1", "input2"];
+    /// let challenges: [ChallengeLabel; 2] = ["challenge1", "challenge2"];
+    /// let mut my_decree = Decree::new("testname", &inputs, &challenges)?;
+    /// my_decree.add("input1", &Point{ x: 1i32, y: 2i32 })?;
+    /// my_decree.add("input2", &Point{ x: 1i32, y: 2i32 })?;
+    /// let mut challenge_out: [u8; 64] = [0u8; 64];
+    /// my_decree.get_challenge("challenge1", &mut challenge_out)?;
+    /// my_decree.get_challenge("challenge2", &mut challenge_out)?;
+    /// my_decree.get_challenge("challenge_extra", &mut challenge_out)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// The following code will not work, because a challenge is requested when specified inputs
+    //// are missing.
+    ///
+    /// ```should_panic
+    /// # use decree::decree::{Decree, InputLabel, ChallengeLabel};
+    /// # use decree::error::{Error, DecreeErrType, DecreeResult};
+    /// # use decree::Inscribe;
+    /// # use decree::inscribe::{INSCRIBE_LENGTH, InscribeBuffer};
+    /// #[derive(Inscribe)]
+    /// pub struct Point {
+    ///     #[inscribe(serialize)]
+    ///     x: i32,
+    ///     #[inscribe(serialize)]
+    ///     y: i32,
+    /// }
+    /// # fn main() -> DecreeResult<()> {
+    /// let input_data = "input_data_1";
+    /// let inputs: [InputLabel; 2] = ["input1", "input2"];
+    /// let challenges: [ChallengeLabel; 2] = ["challenge1", "challenge2"];
+    /// let mut my_decree = Decree::new("testname", &inputs, &challenges)?;
+    /// my_decree.add("input1", &Point{ x: 1i32, y: 2i32 })?;
+    /// let mut challenge_out: [u8; 64] = [0u8; 64];
+    /// my_decree.get_challenge("challenge1", &mut challenge_out)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn get_challenge(
+            &mut self,
+            challenge: &str,
+            dest: &mut [u8]
+            ) -> DecreeResult<()> {
+        if dest.is_empty() {
+            return Err(Error::new_invalid_challenge("Zero-length challenge buffer"));
+        }
+
+        if self.copy_cached_challenge(challenge, dest)? {
+            return Ok(());
+        }
+
+        self.commit_required()?;
+        self.check_challenge(challenge)?;
+        let label = self.squeeze(dest);
+
+        if let Some(cache) = self.challenge_cache.as_mut() {
+            cache.push((label, dest.to_vec()));
+        }
+
+        Ok(())
+    }
+
+    // In caching mode, copies an already-generated challenge into `dest` and returns `true`. The
+    // next pending challenge is never looked up, so that a label scheduled more than once is
+    // drawn afresh.
+    fn copy_cached_challenge(&self, challenge: &str, dest: &mut [u8]) -> DecreeResult<bool> {
+        if self.challenges.first().map(|label| label.as_ref()) != Some(challenge) {
+            if let Some(cache) = self.challenge_cache.as_ref() {
+                if let Some((_, cached)) = cache.iter().find(|(label, _)| *label == challenge) {
+                    if cached.len() != dest.len() {
+                        return Err(Error::new_invalid_challenge("Repeated challenge length mismatch"));
+                    }
+                    dest.copy_from_slice(cached.as_slice());
+                    return Ok(true);
+                }
+            }
+        }
+        Ok(false)
+    }
+
+
+    /// The `get_challenges` method generates several challenges at once, filling each buffer in
+    /// `dests` with the challenge at the same position in `labels`. The labels must be the next
+    /// pending challenges, in schedule order. The whole sequence is checked before any challenge
+    /// is generated, so if it's out of order, the `Decree` is left untouched.
+    ///
+    /// # Tests
+    ///
+    /// Drawing challenges together matches drawing them one at a time, and an out-of-order
+    /// sequence draws nothing
+    /// ```
+    /// # use decree::decree::{Decree, InputLabel, ChallengeLabel};
+    /// # use decree::error::{DecreeErrType, DecreeResult};
+    /// # fn main() -> DecreeResult<()> {
+    /// let inputs: [InputLabel; 1] = ["input1"];
+    /// let challenges: [ChallengeLabel; 3] = ["c1", "c2", "c3"];
+    /// let mut one_at_a_time = Decree::new("testname", &inputs, &challenges)?;
+    /// one_at_a_time.add_serial("input1", 10u32)?;
+    /// let expected1: [u8; 32] = one_at_a_time.challenge("c1")?;
+    /// let expected2: [u8; 16] = one_at_a_time.challenge("c2")?;
+    /// let expected3: [u8; 64] = one_at_a_time.challenge("c3")?;
+    ///
+    /// let mut my_decree = Decree::new("testname", &inputs, &challenges)?;
+    /// my_decree.add_serial("input1", 10u32)?;
+    /// let mut c1 = [0u8; 32];
+    /// let mut c2 = [0u8; 16];
+    /// let mut c3 = [0u8; 64];
+    ///
+    /// let err = my_decree.get_challenges(&["c1", "c3"], &mut [&mut c1, &mut c3]).unwrap_err();
+    /// assert_eq!(err.get_type(), DecreeErrType::InvalidChallenge);
+    /// assert_eq!(my_decree.remaining_challenges(), &challenges);
+    ///
+    /// my_decree.get_challenges(&challenges, &mut [&mut c1, &mut c2, &mut c3])?;
+    /// assert_eq!((c1, c2, c3), (expected1, expected2, expected3));
+    /// assert!(my_decree.is_sealed());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn get_challenges(
+            &mut self,
+            labels: &[ChallengeLabel],
+            dests: &mut [&mut [u8]]
+            ) -> DecreeResult<()> {
+        if labels.len() != dests.len() {
+            return Err(Error::new_invalid_challenge("Each challenge needs exactly one destination"));
+        }
+        if dests.iter().any(|dest| dest.is_empty()) {
+            return Err(Error::new_invalid_challenge("Zero-length challenge buffer"));
+        }
+
+        self.commit_required()?;
+        if !self.committed {
+            return Err(Error::new_general("Missing transcript parameters"));
+        }
+        for (position, label) in labels.iter().enumerate() {
+            match self.challenges.get(position) {
+                Some(pending) if pending == label => {},
+                Some(_) if self.challenges.iter().any(|pending| pending == label) => {
+                    return Err(Error::new_invalid_challenge("Challenge order incorrect"));
+                },
+                Some(_) => {
+                    return Err(Error::new_invalid_challenge("Requested challenge not in spec"));
+                },
+                None => {
+                    return Err(Error::new_invalid_challenge("No remaining challenges"));
+                }
+            }
+        }
+
+        for (label, dest) in labels.iter().zip(dests.iter_mut()) {
+            self.get_challenge(label, dest)?;
+        }
+        Ok(())
+    }
+
+
+    /// The `peek_challenge` method returns the bytes that the next call to `get_challenge` with
+    /// the same label and buffer length would return, without advancing the transcript. The
+    /// ordering and commitment rules are identical to `get_challenge`, so only the next pending
+    /// challenge can be peeked. With challenge caching enabled, an already-generated challenge can
+    /// be peeked too, and returns its cached bytes, as `get_challenge` would.
+    ///
+    /// # Tests
+    ///
+    /// Peeking matches the real challenge, and doesn't disturb later challenges
+    /// ```
+    /// # use decree::decree::{Decree, InputLabel, ChallengeLabel};
+    /// # use decree::error::DecreeResult;
+    /// # fn main() -> DecreeResult<()> {
+    /// let inputs: [InputLabel; 1] = ["input1"];
+    /// let challenges: [ChallengeLabel; 2] = ["challenge1", "challenge2"];
+    ///
+    /// let mut reference = Decree::new("testname", &inputs, &challenges)?;
+    /// reference.add_serial("input1", 10u32)?;
+    /// let mut reference_first: [u8; 32] = [0u8; 32];
+    /// let mut reference_second: [u8; 32] = [0u8; 32];
+    /// reference.get_challenge("challenge1", &mut reference_first)?;
+    /// reference.get_challenge("challenge2", &mut reference_second)?;
+    ///
+    /// let mut my_decree = Decree::with_strict_challenges("testname", &inputs, &challenges)?;
+    /// let mut strict_reference = my_decree.clone();
+    /// my_decree.add_serial("input1", 10u32)?;
+    /// strict_reference.add_serial("input1", 10u32)?;
+    ///
+    /// let mut peeked: [u8; 32] = [0u8; 32];
+    /// let mut first: [u8; 32] = [0u8; 32];
+    /// let mut second: [u8; 32] = [0u8; 32];
+    /// my_decree.peek_challenge("challenge1", &mut peeked)?;
+    /// my_decree.peek_challenge("challenge1", &mut peeked)?;
+    /// my_decree.get_challenge("challenge1", &mut first)?;
+    /// assert_eq!(peeked, first);
+    /// my_decree.get_challenge("challenge2", &mut second)?;
+    ///
+    /// let mut strict_second: [u8; 32] = [0u8; 32];
+    /// strict_reference.get_challenge("challenge1", &mut first)?;
+    /// strict_reference.get_challenge("challenge2", &mut strict_second)?;
+    /// assert_eq!(second, strict_second);
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// Only the next challenge can be peeked
+    /// ```should_panic
+    /// # use decree::decree::{Decree, InputLabel, ChallengeLabel};
+    /// # use decree::error::DecreeResult;
+    /// # fn main() -> DecreeResult<()> {
+    /// let inputs: [InputLabel; 1] = ["input1"];
+    /// let challenges: [ChallengeLabel; 2] = ["challenge1", "challenge2"];
+    /// let mut my_decree = Decree::new("testname", &inputs, &challenges)?;
+    /// my_decree.add_serial("input1", 10u32)?;
+    /// let mut peeked: [u8; 32] = [0u8; 32];
+    /// my_decree.peek_challenge("challenge2", &mut peeked)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn peek_challenge(
+            &self,
+            challenge: &str,
+            dest: &mut [u8]
+            ) -> DecreeResult<()> {
+        if dest.is_empty() {
+            return Err(Error::new_invalid_challenge("Zero-length challenge buffer"));
+        }
+        if self.copy_cached_challenge(challenge, dest)? {
+            return Ok(());
+        }
+
+        // Absent optional inputs are only committed when a challenge is generated
+        if !self.committed {
+            let mut committed = self.clone();
+            committed.commit_required()?;
+            if committed.committed {
+                return committed.peek_challenge(challenge, dest);
+            }
+        }
+        self.check_challenge(challenge)?;
+
+        // Squeeze a copy of the transcript, exactly as `squeeze` would
+        let label = &self.challenges[0];
+        let mut transcript = self.transcript.clone();
+        if self.strict_challenges {
+            bind_strict(&mut transcript, label, self.challenge_index);
+        }
+        challenge_labeled(&mut transcript, label, dest);
+        Ok(())
+    }
+
+
+    /// The `batch_challenges` method derives `count` coefficients for batch verification. Every
+    /// input of the current stage (i.e., every proof in the batch) must be present, and each
+    /// coefficient is drawn from its own fork of the committed transcript. Since no coefficient
+    /// exists until all of the proofs have been absorbed, a prover can't pick a later proof to
+    /// cancel out the coefficient of an earlier one, which is possible when coefficients are
+    /// derived one at a time as each proof is added.
+    ///
+    /// The `labels` must be the next pending challenges, in order; they are bound into each fork
+    /// along with the coefficient's index. The `Decree` itself is left unchanged, so the labels
+    /// remain pending.
+    ///
+    /// # Tests
+    ///
+    /// Coefficients are distinct, and depend on every proof in the batch
+    /// ```
+    /// # use decree::decree::{Decree, InputLabel, ChallengeLabel};
+    /// # use decree::error::DecreeResult;
+    /// # fn main() -> DecreeResult<()> {
+    /// let inputs: [InputLabel; 2] = ["proof1", "proof2"];
+    /// let challenges: [ChallengeLabel; 1] = ["batch"];
+    /// let mut my_decree = Decree::new("testname", &inputs, &challenges)?;
+    /// my_decree.add_serial("proof1", 10u32)?;
+    /// let mut other_decree = my_decree.clone();
+    ///
+    /// // Nothing can be drawn until the whole batch is present
+    /// assert!(my_decree.batch_challenges(&challenges, 2).is_err());
+    ///
+    /// my_decree.add_serial("proof2", 14u32)?;
+    /// other_decree.add_serial("proof2", 15u32)?;
+    /// let coefficients = my_decree.batch_challenges(&challenges, 2)?;
+    /// let other_coefficients = other_decree.batch_challenges(&challenges, 2)?;
+    /// assert_eq!(coefficients.len(), 2);
+    /// assert_ne!(coefficients[0], coefficients[1]);
+    /// assert_ne!(coefficients[0], other_coefficients[0]);
+    /// assert_eq!(coefficients, my_decree.batch_challenges(&challenges, 2)?);
+    /// assert_eq!(my_decree.remaining_challenges(), &challenges);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn batch_challenges(
+            &self,
+            labels: &[ChallengeLabel],
+            count: usize
+            ) -> DecreeResult<Vec<[u8; 32]>> {
+        if labels.is_empty() {
+            return Err(Error::new_invalid_challenge("Must specify at least one challenge"));
+        }
+
+        // Check the labels against the schedule on a copy, leaving our own state alone
+        let mut committed = self.clone();
+        committed.commit_required()?;
+        for label in labels.iter() {
+            committed.check_challenge(label)?;
+            committed.challenges.remove(0);
+        }
+
+        let mut coefficients = Vec::with_capacity(count);
+        for index in 0..count {
+            let mut fork = committed.transcript.clone();
+            for label in labels.iter() {
+                fork.append_message(BATCH_LABEL_LABEL, label.as_bytes());
+            }
+            fork.append_message(BATCH_INDEX_LABEL, &(index as u64).to_le_bytes());
+            let mut coefficient = [0u8; 32];
+            fork.challenge_bytes(BATCH_COEFFICIENT_LABEL, &mut coefficient);
+            coefficients.push(coefficient);
+        }
+        Ok(coefficients)
+    }
+
+
+    /// The `get_challenge_vec` method behaves like `get_challenge`, but allocates and returns a
+    /// challenge of `len` bytes rather than filling a caller-provided buffer. This is useful when
+    /// the challenge size depends on a runtime security parameter. Ordering and commitment rules
+    /// are identical to `get_challenge`.
+    ///
+    /// Note that Merlin binds the requested length into the transcript, so a challenge of `len`
+    /// bytes is _not_ a prefix of a longer challenge with the same label.
+    ///
+    /// # Tests
+    ///
+    /// Out-of-order requests still fail
+    /// ```should_panic
+    /// # use decree::decree::{Decree, InputLabel, ChallengeLabel};
+    /// # use decree::error::DecreeResult;
+    /// # fn main() -> DecreeResult<()> {
+    /// let inputs: [InputLabel; 1] = ["input1"];
+    /// let challenges: [ChallengeLabel; 2] = ["challenge1", "challenge2"];
+    /// let mut my_decree = Decree::new("testname", &inputs, &challenges)?;
+    /// my_decree.add_serial("input1", 10u32)?;
+    /// my_decree.get_challenge_vec("challenge2", 32)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn get_challenge_vec(
+            &mut self,
+            challenge: &str,
+            len: usize
+            ) -> DecreeResult<Vec<u8>> {
+        let mut dest = vec![0u8; len];
+        self.get_challenge(challenge, &mut dest)?;
+        Ok(dest)
+    }
+
+
+    /// The `get_challenge_bits` method generates a challenge as a sequence of `num_bits` bits, for
+    /// protocols such as range proofs and cut-and-choose that consume a challenge bit by bit. It
+    /// draws `ceil(num_bits / 8)` bytes, exactly as `get_challenge_vec` would, and expands them
+    /// least significant bit first: bit `i` is bit `i % 8` of byte `i / 8`. Any unused high bits of
+    /// the last byte are discarded. The ordering and commitment rules are identical to
+    /// `get_challenge`.
+    ///
+    /// # Tests
+    ///
+    /// Bits are taken from the bytes of a draw of the same length
+    /// ```
+    /// # use decree::decree::{Decree, InputLabel, ChallengeLabel};
+    /// # use decree::error::DecreeResult;
+    /// # fn main() -> DecreeResult<()> {
+    /// let inputs: [InputLabel; 1] = ["input1"];
+    /// let challenges: [ChallengeLabel; 1] = ["challenge1"];
+    ///
+    /// let mut decree_a = Decree::new("testname", &inputs, &challenges)?;
+    /// decree_a.add_serial("input1", 10u32)?;
+    /// let bits = decree_a.get_challenge_bits("challenge1", 3)?;
+    ///
+    /// let mut decree_b = Decree::new("testname", &inputs, &challenges)?;
+    /// decree_b.add_serial("input1", 10u32)?;
+    /// let byte = decree_b.get_challenge_vec("challenge1", 1)?[0];
+    /// assert_eq!(bits, vec![byte & 1 == 1, byte & 2 == 2, byte & 4 == 4]);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn get_challenge_bits(
+            &mut self,
+            challenge: &str,
+            num_bits: usize
+            ) -> DecreeResult<Vec<bool>> {
+        let bytes = self.get_challenge_vec(challenge, num_bits.div_ceil(8))?;
+        Ok((0..num_bits).map(|bit| (bytes[bit / 8] >> (bit % 8)) & 1 == 1).collect())
+    }
+
+
+    /// The `challenge` method behaves like `get_challenge`, but returns the challenge as a
+    /// fixed-size array, so that callers can write `let c: [u8; 32] = decree.challenge("c")?;`.
+    /// Ordering and commitment rules are identical to `get_challenge`.
+    ///
+    /// As with `get_challenge_vec`, the length is bound into the transcript: an `N`-byte challenge
+    /// is _not_ a prefix of a longer challenge with the same label.
+    ///
+    /// # Tests
+    ///
+    /// Arrays match `get_challenge` at the same length, but not prefixes of longer draws
+    /// ```
+    /// # use decree::decree::{Decree, InputLabel, ChallengeLabel};
+    /// # use decree::error::DecreeResult;
+    /// # fn main() -> DecreeResult<()> {
+    /// let inputs: [InputLabel; 1] = ["input1"];
+    /// let challenges: [ChallengeLabel; 3] = ["c16", "c32", "c64"];
+    /// let mut arrays = Decree::new("testname", &inputs, &challenges)?;
+    /// let mut buffers = arrays.clone();
+    /// arrays.add_serial("input1", 10u32)?;
+    /// buffers.add_serial("input1", 10u32)?;
+    ///
+    /// let c16: [u8; 16] = arrays.challenge("c16")?;
+    /// let c32: [u8; 32] = arrays.challenge("c32")?;
+    /// let c64: [u8; 64] = arrays.challenge("c64")?;
+    ///
+    /// let mut b16: [u8; 16] = [0u8; 16];
+    /// let mut b32: [u8; 32] = [0u8; 32];
+    /// let mut b64: [u8; 64] = [0u8; 64];
+    /// buffers.get_challenge("c16", &mut b16)?;
+    /// buffers.get_challenge("c32", &mut b32)?;
+    /// buffers.get_challenge("c64", &mut b64)?;
+    /// assert_eq!((c16, c32, c64), (b16, b32, b64));
+    ///
+    /// // A shorter draw is not a prefix of a longer one
+    /// let mut short = Decree::new("testname", &inputs, &challenges)?;
+    /// short.add_serial("input1", 10u32)?;
+    /// let short16: [u8; 16] = short.challenge("c16")?;
+    /// let mut long = Decree::new("testname", &inputs, &challenges)?;
+    /// long.add_serial("input1", 10u32)?;
+    /// let long64: [u8; 64] = long.challenge("c16")?;
+    /// assert_ne!(short16[..], long64[..16]);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn challenge<const N: usize>(
+            &mut self,
+            challenge: &str
+            ) -> DecreeResult<[u8; N]> {
+        let mut dest = [0u8; N];
+        self.get_challenge(challenge, &mut dest)?;
+        Ok(dest)
+    }
+
+
+    /// The `get_challenge_as` method generates a challenge and converts it to a typed value using
+    /// the `ChallengeFrom` trait. Exactly `T::CHALLENGE_LENGTH` bytes are drawn from the
+    /// transcript, and the ordering and commitment rules are identical to `get_challenge`.
+    ///
+    /// # Tests
+    ///
+    /// Typed challenges are deterministic, and match the raw challenge bytes
+    /// ```
+    /// # use decree::decree::{Decree, InputLabel, ChallengeLabel};
+    /// # use decree::error::DecreeResult;
+    /// # use num_bigint::BigUint;
+    /// # fn main() -> DecreeResult<()> {
+    /// let inputs: [InputLabel; 1] = ["input1"];
+    /// let challenges: [ChallengeLabel; 1] = ["challenge1"];
+    ///
+    /// let mut decree_a = Decree::new("testname", &inputs, &challenges)?;
+    /// decree_a.add_serial("input1", 10u32)?;
+    /// let challenge_a: BigUint = decree_a.get_challenge_as("challenge1")?;
+    ///
+    /// let mut decree_b = Decree::new("testname", &inputs, &challenges)?;
+    /// decree_b.add_serial("input1", 10u32)?;
+    /// let challenge_b: BigUint = decree_b.get_challenge_as("challenge1")?;
+    /// assert_eq!(challenge_a, challenge_b);
+    ///
+    /// let mut decree_c = Decree::new("testname", &inputs, &challenges)?;
+    /// decree_c.add_serial("input1", 10u32)?;
+    /// let mut challenge_bytes: [u8; 32] = [0u8; 32];
+    /// decree_c.get_challenge("challenge1", &mut challenge_bytes)?;
+    /// assert_eq!(challenge_a, BigUint::from_bytes_le(&challenge_bytes));
+    ///
+    /// let mut decree_d = Decree::new("testname", &inputs, &challenges)?;
+    /// decree_d.add_serial("input1", 10u32)?;
+    /// let challenge_array: [u8; 32] = decree_d.get_challenge_as("challenge1")?;
+    /// assert_eq!(challenge_array, challenge_bytes);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn get_challenge_as<T: ChallengeFrom>(
+            &mut self,
+            challenge: &str
+            ) -> DecreeResult<T> {
+        self.get_challenge_as_ordered(challenge, ByteOrder::LittleEndian)
+    }
+
+
+    /// The `get_challenge_as_ordered` method works like `get_challenge_as`, but hands the
+    /// challenge bytes to `T::from_challenge` in the given order. `ChallengeFrom` conversions read
+    /// their bytes as little-endian, so `ByteOrder::BigEndian` reverses the squeezed bytes first;
+    /// for a `[u8; 32]`, this just reverses the array. `get_challenge_as` is the same as passing
+    /// `ByteOrder::LittleEndian`.
+    ///
+    /// # Tests
+    ///
+    /// ```
+    /// # use decree::decree::{Decree, InputLabel, ChallengeLabel};
+    /// # use decree::challenge::ByteOrder;
+    /// # use decree::error::DecreeResult;
+    /// # use num_bigint::BigUint;
+    /// # fn main() -> DecreeResult<()> {
+    /// let inputs: [InputLabel; 1] = ["input1"];
+    /// let challenges: [ChallengeLabel; 1] = ["challenge1"];
+    /// let mut raw = Decree::new("testname", &inputs, &challenges)?;
+    /// raw.add_serial("input1", 10u32)?;
+    /// let bytes = raw.get_challenge_vec("challenge1", 32)?;
+    ///
+    /// let mut big = Decree::new("testname", &inputs, &challenges)?;
+    /// big.add_serial("input1", 10u32)?;
+    /// let challenge: BigUint = big.get_challenge_as_ordered("challenge1", ByteOrder::BigEndian)?;
+    /// assert_eq!(challenge, BigUint::from_bytes_be(&bytes));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn get_challenge_as_ordered<T: ChallengeFrom>(
+            &mut self,
+            challenge: &str,
+            order: ByteOrder
+            ) -> DecreeResult<T> {
+        let mut bytes = self.get_challenge_vec(challenge, T::CHALLENGE_LENGTH)?;
+        if order == ByteOrder::BigEndian {
+            bytes.reverse();
+        }
+        T::from_challenge(&bytes)
+    }
+
+
+    /// With the `ristretto` feature, the `get_challenge_scalar` method generates a challenge as a
+    /// Ristretto255 scalar. This draws 64 bytes from the transcript and reduces them with
+    /// `Scalar::from_bytes_mod_order_wide`; it is equivalent to `get_challenge_as::<Scalar>`.
+    /// The ordering and commitment rules are identical to `get_challenge`.
+    ///
+    /// # Tests
+    ///
+    /// Scalars are deterministic for a fixed transcript
+    /// ```
+    /// # use decree::decree::{Decree, InputLabel, ChallengeLabel};
+    /// # use decree::error::DecreeResult;
+    /// # use curve25519_dalek::Scalar;
+    /// # fn main() -> DecreeResult<()> {
+    /// let inputs: [InputLabel; 1] = ["input1"];
+    /// let challenges: [ChallengeLabel; 1] = ["challenge1"];
+    ///
+    /// let mut decree_a = Decree::new("testname", &inputs, &challenges)?;
+    /// decree_a.add_serial("input1", 10u32)?;
+    /// let scalar_a = decree_a.get_challenge_scalar("challenge1")?;
+    ///
+    /// let mut decree_b = Decree::new("testname", &inputs, &challenges)?;
+    /// decree_b.add_serial("input1", 10u32)?;
+    /// let mut wide: [u8; 64] = [0u8; 64];
+    /// decree_b.get_challenge("challenge1", &mut wide)?;
+    /// assert_eq!(scalar_a, Scalar::from_bytes_mod_order_wide(&wide));
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// Ordering is still enforced
+    /// ```should_panic
+    /// # use decree::decree::{Decree, InputLabel, ChallengeLabel};
+    /// # use decree::error::DecreeResult;
+    /// # fn main() -> DecreeResult<()> {
+    /// let inputs: [InputLabel; 1] = ["input1"];
+    /// let challenges: [ChallengeLabel; 2] = ["challenge1", "challenge2"];
+    /// let mut my_decree = Decree::new("testname", &inputs, &challenges)?;
+    /// my_decree.add_serial("input1", 10u32)?;
+    /// my_decree.get_challenge_scalar("challenge2")?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(feature = "ristretto")]
+    pub fn get_challenge_scalar(
+            &mut self,
+            challenge: &str
+            ) -> DecreeResult<curve25519_dalek::Scalar> {
+        self.get_challenge_as(challenge)
+    }
+
+
+    /// With the `ristretto` feature, the `fold_challenge` method derives the challenge for one
+    /// folding step of a recursive proof system (such as Nova), where two or more instances are
+    /// combined using a random scalar. All of the `instances` are absorbed under the single input
+    /// label `input`, exactly as `add_iter` would, and the stage is committed before the challenge
+    /// is drawn with `get_challenge_scalar`. Since the challenge can only be drawn after every
+    /// instance is bound, a prover can't choose an instance after seeing the challenge that folds
+    /// it. The other inputs of the stage must already be present.
+    ///
+    /// # Tests
+    ///
+    /// The folding challenge depends on every instance, and on their order
+    /// ```
+    /// # use decree::decree::{Decree, InputLabel, ChallengeLabel};
+    /// # use decree::error::DecreeResult;
+    /// # fn main() -> DecreeResult<()> {
+    /// let inputs: [InputLabel; 1] = ["instances"];
+    /// let challenges: [ChallengeLabel; 1] = ["fold"];
+    /// let fold = |instances: &[&u64]| -> DecreeResult<_> {
+    ///     let mut my_decree = Decree::new("testname", &inputs, &challenges)?;
+    ///     my_decree.fold_challenge("instances", "fold", instances)
+    /// };
+    ///
+    /// let folded = fold(&[&1u64, &2u64])?;
+    /// assert_eq!(folded, fold(&[&1u64, &2u64])?);
+    /// assert_ne!(folded, fold(&[&1u64, &3u64])?);
+    /// assert_ne!(folded, fold(&[&3u64, &2u64])?);
+    /// assert_ne!(folded, fold(&[&2u64, &1u64])?);
+    ///
+    /// // The same as absorbing the instances and drawing a scalar by hand
+    /// let mut by_hand = Decree::new("testname", &inputs, &challenges)?;
+    /// by_hand.add_iter("instances", [&1u64, &2u64])?;
+    /// assert_eq!(folded, by_hand.get_challenge_scalar("fold")?);
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// Folding nothing is an error
+    /// ```should_panic
+    /// # use decree::decree::{Decree, InputLabel, ChallengeLabel};
+    /// # use decree::error::DecreeResult;
+    /// # fn main() -> DecreeResult<()> {
+    /// let inputs: [InputLabel; 1] = ["instances"];
+    /// let challenges: [ChallengeLabel; 1] = ["fold"];
+    /// let mut my_decree = Decree::new("testname", &inputs, &challenges)?;
+    /// let instances: [&u64; 0] = [];
+    /// my_decree.fold_challenge("instances", "fold", &instances)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(feature = "ristretto")]
+    pub fn fold_challenge<T: Inscribe + ?Sized>(
+            &mut self,
+            input: &str,
+            challenge: &str,
+            instances: &[&T]
+            ) -> DecreeResult<curve25519_dalek::Scalar> {
+        if instances.is_empty() {
+            return Err(Error::new_general("Cannot fold an empty set of instances"));
+        }
+        self.add_iter(input, instances.iter().copied())?;
+        self.commit_now()?;
+        self.get_challenge_scalar(challenge)
+    }
+
+
+    /// With the `arkworks` feature, the `get_challenge_field` method generates a challenge as an
+    /// element of any arkworks prime field, such as `ark_bn254::Fr`. This draws 128 bits more
+    /// than the size of the field modulus, so that reducing with `F::from_le_bytes_mod_order`
+    /// leaves a negligible bias. The ordering and commitment rules are identical to
+    /// `get_challenge`.
+    ///
+    /// # Tests
+    ///
+    /// Field elements are deterministic for a fixed transcript
+    /// ```
+    /// # use decree::decree::{Decree, InputLabel, ChallengeLabel};
+    /// # use decree::error::DecreeResult;
+    /// # use ark_bn254::Fr;
+    /// # use ark_ff::PrimeField;
+    /// # fn main() -> DecreeResult<()> {
+    /// let inputs: [InputLabel; 1] = ["input1"];
+    /// let challenges: [ChallengeLabel; 1] = ["challenge1"];
+    ///
+    /// let mut decree_a = Decree::new("testname", &inputs, &challenges)?;
+    /// decree_a.add_serial("input1", 10u32)?;
+    /// let field_a: Fr = decree_a.get_challenge_field("challenge1")?;
+    ///
+    /// // The BN254 scalar field has a 254-bit modulus, so 48 bytes are drawn
+    /// let mut decree_b = Decree::new("testname", &inputs, &challenges)?;
+    /// decree_b.add_serial("input1", 10u32)?;
+    /// let mut wide: [u8; 48] = [0u8; 48];
+    /// decree_b.get_challenge("challenge1", &mut wide)?;
+    /// assert_eq!(field_a, Fr::from_le_bytes_mod_order(&wide));
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// Ordering is still enforced
+    /// ```should_panic
+    /// # use decree::decree::{Decree, InputLabel, ChallengeLabel};
+    /// # use decree::error::DecreeResult;
+    /// # use ark_bn254::Fr;
+    /// # fn main() -> DecreeResult<()> {
+    /// let inputs: [InputLabel; 1] = ["input1"];
+    /// let challenges: [ChallengeLabel; 2] = ["challenge1", "challenge2"];
+    /// let mut my_decree = Decree::new("testname", &inputs, &challenges)?;
+    /// my_decree.add_serial("input1", 10u32)?;
+    /// let _: Fr = my_decree.get_challenge_field("challenge2")?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(feature = "arkworks")]
+    pub fn get_challenge_field<F: ark_ff::PrimeField>(
+            &mut self,
+            challenge: &str
+            ) -> DecreeResult<F> {
+        let length = (F::MODULUS_BIT_SIZE as usize + 128).div_ceil(8);
+        let bytes = self.get_challenge_vec(challenge, length)?;
+        Ok(F::from_le_bytes_mod_order(&bytes))
+    }
+
+
+    /// With the `generic-array` feature, the `get_challenge_ga` method generates a challenge as a
+    /// `GenericArray`, for use with RustCrypto hashers and ciphers. The result is identical to an
+    /// `N`-byte `get_challenge` draw, and the ordering and commitment rules are the same.
+    ///
+    /// # Tests
+    ///
+    /// `GenericArray` draws match array draws of the same length
+    /// ```
+    /// # use decree::decree::{Decree, InputLabel, ChallengeLabel};
+    /// # use decree::error::DecreeResult;
+    /// # use generic_array::GenericArray;
+    /// # use generic_array::typenum::U32;
+    /// # fn main() -> DecreeResult<()> {
+    /// let inputs: [InputLabel; 1] = ["input1"];
+    /// let challenges: [ChallengeLabel; 1] = ["challenge1"];
+    ///
+    /// let mut decree_a = Decree::new("testname", &inputs, &challenges)?;
+    /// decree_a.add_serial("input1", 10u32)?;
+    /// let generic: GenericArray<u8, U32> = decree_a.get_challenge_ga("challenge1")?;
+    ///
+    /// let mut decree_b = Decree::new("testname", &inputs, &challenges)?;
+    /// decree_b.add_serial("input1", 10u32)?;
+    /// let array: [u8; 32] = decree_b.challenge("challenge1")?;
+    /// assert_eq!(generic.as_slice(), &array);
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(feature = "generic-array")]
+    pub fn get_challenge_ga<N: generic_array::ArrayLength<u8>>(
+            &mut self,
+            challenge: &str
+            ) -> DecreeResult<generic_array::GenericArray<u8, N>> {
+        let mut dest = generic_array::GenericArray::default();
+        self.get_challenge(challenge, &mut dest)?;
+        Ok(dest)
+    }
+
+
+    /// The `get_challenge_mod` method generates a challenge that is uniformly distributed in the
+    /// range `[0, modulus)`. Reducing a fixed-width challenge modulo a group order introduces a
+    /// bias toward small residues; this method avoids that with rejection sampling.
+    ///
+    /// Each draw is as many bytes as `modulus` (interpreted as a little-endian integer). Draws at
+    /// or above the largest multiple of `modulus` that fits in that width are rejected, and the
+    /// transcript is squeezed again under the same label. The redraws are deterministic, so the
+    /// prover and verifier always agree. At least half of all draws are accepted. The ordering
+    /// and commitment rules are identical to `get_challenge`, but the result is not cached.
+    ///
+    /// # Tests
+    ///
+    /// Challenges are deterministic and in range
+    /// ```
+    /// # use decree::decree::{Decree, InputLabel, ChallengeLabel};
+    /// # use decree::error::DecreeResult;
+    /// # use num_bigint::BigUint;
+    /// # fn main() -> DecreeResult<()> {
+    /// let inputs: [InputLabel; 1] = ["input1"];
+    /// let challenges: [ChallengeLabel; 1] = ["challenge1"];
+    /// let modulus = BigUint::parse_bytes(
+    ///     b"7237005577332262213973186563042994240857116359379907606001950938285454250989", 10).unwrap();
+    ///
+    /// let mut decree_a = Decree::new("testname", &inputs, &challenges)?;
+    /// decree_a.add_serial("input1", 10u32)?;
+    /// let challenge_a = decree_a.get_challenge_mod("challenge1", &modulus)?;
+    ///
+    /// let mut decree_b = Decree::new("testname", &inputs, &challenges)?;
+    /// decree_b.add_serial("input1", 10u32)?;
+    /// let challenge_b = decree_b.get_challenge_mod("challenge1", &modulus)?;
+    ///
+    /// assert_eq!(challenge_a, challenge_b);
+    /// assert!(challenge_a < modulus);
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// A zero modulus is rejected
+    /// ```should_panic
+    /// # use decree::decree::{Decree, InputLabel, ChallengeLabel};
+    /// # use decree::error::DecreeResult;
+    /// # use num_bigint::BigUint;
+    /// # fn main() -> DecreeResult<()> {
+    /// let inputs: [InputLabel; 1] = ["input1"];
+    /// let challenges: [ChallengeLabel; 1] = ["challenge1"];
+    /// let mut my_decree = Decree::new("testname", &inputs, &challenges)?;
+    /// my_decree.add_serial("input1", 10u32)?;
+    /// my_decree.get_challenge_mod("challenge1", &BigUint::from(0u32))?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn get_challenge_mod(
+            &mut self,
+            challenge: &str,
+            modulus: &BigUint
+            ) -> DecreeResult<BigUint> {
+        self.get_challenge_mod_ordered(challenge, modulus, ByteOrder::LittleEndian)
+    }
+
+
+    /// The `get_challenge_mod_ordered` method works like `get_challenge_mod`, but reads each draw
+    /// in the given byte order. `get_challenge_mod` is the same as passing
+    /// `ByteOrder::LittleEndian`.
+    pub fn get_challenge_mod_ordered(
+            &mut self,
+            challenge: &str,
+            modulus: &BigUint,
+            order: ByteOrder
+            ) -> DecreeResult<BigUint> {
+        if *modulus == BigUint::from(0u32) {
+            return Err(Error::new_invalid_challenge("Modulus must be nonzero"));
+        }
+        self.commit_required()?;
+        self.check_challenge(challenge)?;
+
+        Ok(self.squeeze_with(None, |transcript, label| squeeze_mod(transcript, label, modulus, false, order)))
+    }
+
+
+    /// The `get_challenge_nonzero` method works like `get_challenge_mod`, but never returns zero,
+    /// for protocols where a zero challenge would let any response verify. Draws that reduce to
+    /// zero are rejected along with the biased ones, and the transcript is squeezed again, so the
+    /// challenge is uniformly distributed in `[1, modulus)`. Compared to `get_challenge_mod`, this
+    /// only changes the outcome of the `1 / modulus` fraction of draws that would have been zero,
+    /// which is negligible for cryptographically sized moduli. The modulus must be at least 2.
+    ///
+    /// # Tests
+    ///
+    /// Even with a tiny modulus, challenges are never zero, and prover and verifier agree
+    /// ```
+    /// # use decree::decree::{Decree, InputLabel, ChallengeLabel};
+    /// # use decree::error::DecreeResult;
+    /// # use num_bigint::BigUint;
+    /// # fn main() -> DecreeResult<()> {
+    /// let inputs: [InputLabel; 1] = ["input1"];
+    /// let challenges: [ChallengeLabel; 1] = ["challenge1"];
+    /// let modulus = BigUint::from(2u32);
+    /// for input in 0u32..64 {
+    ///     let mut prover = Decree::new("testname", &inputs, &challenges)?;
+    ///     prover.add_serial("input1", input)?;
+    ///     let mut verifier = Decree::new("testname", &inputs, &challenges)?;
+    ///     verifier.add_serial("input1", input)?;
+    ///
+    ///     let challenge = prover.get_challenge_nonzero("challenge1", &modulus)?;
+    ///     assert_eq!(challenge, BigUint::from(1u32));
+    ///     assert_eq!(challenge, verifier.get_challenge_nonzero("challenge1", &modulus)?);
+    /// }
+    ///
+    /// let mut my_decree = Decree::new("testname", &inputs, &challenges)?;
+    /// my_decree.add_serial("input1", 10u32)?;
+    /// assert!(my_decree.get_challenge_nonzero("challenge1", &BigUint::from(1u32)).is_err());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn get_challenge_nonzero(
+            &mut self,
+            challenge: &str,
+            modulus: &BigUint
+            ) -> DecreeResult<BigUint> {
+        if *modulus <= BigUint::from(1u32) {
+            return Err(Error::new_invalid_challenge("Modulus must be at least 2"));
+        }
+        self.commit_required()?;
+        self.check_challenge(challenge)?;
+
+        Ok(self.squeeze_with(None, |transcript, label| squeeze_mod(transcript, label, modulus, true, ByteOrder::LittleEndian)))
+    }
+
+
+    /// The `build_rng` method starts building a Merlin `TranscriptRng`, for prover randomness
+    /// (such as the randomizer in a Schnorr proof) that is bound to the current transcript state.
+    /// The caller should rekey the builder with any secret witness bytes using
+    /// `rekey_with_witness_bytes`, then finalize it with an external RNG. The resulting nonces
+    /// depend on the transcript, the witness, and the external randomness, so a failure of any
+    /// one source alone does not lead to nonce reuse.
+    ///
+    /// This does not change the transcript, and is not subject to the input and challenge
+    /// ordering rules.
+    ///
+    /// # Tests
+    ///
+    /// Identical transcripts and witnesses give identical nonces; different witnesses diverge
+    /// ```
+    /// # use decree::decree::{Decree, InputLabel, ChallengeLabel};
+    /// # use decree::error::DecreeResult;
+    /// # use rand::{RngCore, SeedableRng};
+    /// # use rand::rngs::StdRng;
+    /// # fn main() -> DecreeResult<()> {
+    /// let inputs: [InputLabel; 1] = ["input1"];
+    /// let challenges: [ChallengeLabel; 1] = ["challenge1"];
+    /// let mut decree_a = Decree::new("testname", &inputs, &challenges)?;
+    /// let mut decree_b = Decree::new("testname", &inputs, &challenges)?;
+    /// decree_a.add_serial("input1", 10u32)?;
+    /// decree_b.add_serial("input1", 10u32)?;
+    ///
+    /// let mut nonce_a: [u8; 32] = [0u8; 32];
+    /// let mut nonce_b: [u8; 32] = [0u8; 32];
+    /// let mut nonce_c: [u8; 32] = [0u8; 32];
+    /// decree_a.build_rng()
+    ///     .rekey_with_witness_bytes(b"witness", b"secret one")
+    ///     .finalize(&mut StdRng::seed_from_u64(7))
+    ///     .fill_bytes(&mut nonce_a);
+    /// decree_b.build_rng()
+    ///     .rekey_with_witness_bytes(b"witness", b"secret one")
+    ///     .finalize(&mut StdRng::seed_from_u64(7))
+    ///     .fill_bytes(&mut nonce_b);
+    /// decree_b.build_rng()
+    ///     .rekey_with_witness_bytes(b"witness", b"secret two")
+    ///     .finalize(&mut StdRng::seed_from_u64(7))
+    ///     .fill_bytes(&mut nonce_c);
+    ///
+    /// assert_eq!(nonce_a, nonce_b);
+    /// assert_ne!(nonce_a, nonce_c);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn build_rng(&self) -> TranscriptRngBuilder {
+        self.transcript.build_rng()
+    }
+
+
+    /// The `set_challenge_caching` method controls what happens when `get_challenge` is called
+    /// with a challenge that has already been generated in the current stage. By default, this
+    /// is an error. With caching enabled, the previously generated bytes are returned instead,
+    /// without touching the transcript; the request must use a buffer of the same length as the
+    /// original. This is a convenience for drivers with retry logic that may over-request.
+    ///
+    /// Cached challenges are discarded by `extend`. Only `get_challenge` consults the cache.
+    ///
+    /// # Tests
+    ///
+    /// Repeated challenges are returned from the cache
+    /// ```
+    /// # use decree::decree::{Decree, InputLabel, ChallengeLabel};
+    /// # use decree::error::{Error, DecreeErrType, DecreeResult};
+    /// # fn main() -> DecreeResult<()> {
+    /// let inputs: [InputLabel; 1] = ["input1"];
+    /// let challenges: [ChallengeLabel; 2] = ["challenge1", "challenge2"];
+    /// let mut my_decree = Decree::new("testname", &inputs, &challenges)?;
+    /// my_decree.set_challenge_caching(true);
+    /// my_decree.add_serial("input1", 10u32)?;
+    ///
+    /// let mut first: [u8; 32] = [0u8; 32];
+    /// let mut repeat: [u8; 32] = [0u8; 32];
+    /// let mut second: [u8; 32] = [0u8; 32];
+    /// my_decree.get_challenge("challenge1", &mut first)?;
+    /// my_decree.get_challenge("challenge1", &mut repeat)?;
+    /// assert_eq!(first, repeat);
+    ///
+    /// // The transcript is unaffected by the repeated request
+    /// let mut reference = Decree::new("testname", &inputs, &challenges)?;
+    /// reference.add_serial("input1", 10u32)?;
+    /// let mut reference_first: [u8; 32] = [0u8; 32];
+    /// let mut reference_second: [u8; 32] = [0u8; 32];
+    /// reference.get_challenge("challenge1", &mut reference_first)?;
+    /// reference.get_challenge("challenge2", &mut reference_second)?;
+    /// my_decree.get_challenge("challenge2", &mut second)?;
+    /// assert_eq!(second, reference_second);
+    ///
+    /// // Both challenges can still be re-requested
+    /// my_decree.get_challenge("challenge1", &mut repeat)?;
+    /// assert_eq!(first, repeat);
+    /// my_decree.get_challenge("challenge2", &mut repeat)?;
+    /// assert_eq!(second, repeat);
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// Repeated challenges are an error by default
+    /// ```should_panic
+    /// # use decree::decree::{Decree, InputLabel, ChallengeLabel};
+    /// # use decree::error::{Error, DecreeErrType, DecreeResult};
+    /// # fn main() -> DecreeResult<()> {
+    /// let inputs: [InputLabel; 1] = ["input1"];
+    /// let challenges: [ChallengeLabel; 2] = ["challenge1", "challenge2"];
+    /// let mut my_decree = Decree::new("testname", &inputs, &challenges)?;
+    /// my_decree.add_serial("input1", 10u32)?;
+    /// let mut challenge_out: [u8; 32] = [0u8; 32];
+    /// my_decree.get_challenge("challenge1", &mut challenge_out)?;
+    /// my_decree.get_challenge("challenge1", &mut challenge_out)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn set_challenge_caching(&mut self, enabled: bool) {
+        self.challenge_cache = if enabled {
+            Some(self.challenge_cache.take().unwrap_or_default())
+        } else {
+            None
+        };
+    }
+
+
+    /// The `enable_trace` method starts recording every message absorbed by the transcript and
+    /// every challenge drawn from it, in order, for retrieval with `trace`. Comparing a prover's
+    /// trace with a verifier's locates the first point where their transcripts diverge.
+    ///
+    /// Each entry holds the Merlin label, the length of the value, and its SHA3-256 digest; the
+    /// value itself is never stored. Labels include the ones `Decree` uses internally, such as
+    /// `decree-input-frame`. Tracing continues across `extend`, and `reset` and
+    /// `clone_for_verification` start a new, empty trace. Traces aren't serialized.
+    ///
+    /// # Tests
+    ///
+    /// Inputs are traced in sorted order, regardless of the order in which they're added
+    /// ```
+    /// # use decree::decree::{Decree, InputLabel, ChallengeLabel, TraceKind};
+    /// # use decree::error::DecreeResult;
+    /// # fn main() -> DecreeResult<()> {
+    /// let inputs: [InputLabel; 2] = ["input2", "input1"];
+    /// let challenges: [ChallengeLabel; 1] = ["challenge1"];
+    /// let mut my_decree = Decree::new("testname", &inputs, &challenges)?;
+    /// my_decree.enable_trace();
+    /// my_decree.add_serial("input2", 14u32)?;
+    /// my_decree.add_serial("input1", 10u64)?;
+    /// let _: [u8; 32] = my_decree.challenge("challenge1")?;
+    ///
+    /// let labels: Vec<&str> = my_decree.trace().iter().map(|entry| entry.label.as_str()).collect();
+    /// assert_eq!(labels, vec!["decree-input-frame", "input1", "decree-input-frame", "input2", "challenge1"]);
+    /// assert_eq!(my_decree.trace()[1].length, 8);
+    /// assert_eq!(my_decree.trace()[3].length, 4);
+    /// assert_eq!(my_decree.trace()[4].kind, TraceKind::Challenge);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn enable_trace(&mut self) {
+        self.initial_transcript.enable_trace();
+        self.transcript.enable_trace();
+    }
+
+
+    /// Returns the operations recorded since `enable_trace` was called, or an empty slice if
+    /// tracing isn't enabled.
+    pub fn trace(&self) -> &[TraceEntry] {
+        self.transcript.trace()
+    }
+
+
+    /// Returns the number of message bytes absorbed into the transcript so far. This counts
+    /// everything `Decree` appends, not just input values: each committed input adds a 16-byte
+    /// frame (see the `Decree` docs) along with its value, and raw messages, owned labels, and
+    /// the messages added by strict and versioned modes count as well. Merlin labels and the
+    /// transcript's own framing are not included. A `Decree` built around an existing Merlin
+    /// transcript counts from that point on.
+    ///
+    /// # Tests
+    ///
+    /// ```
+    /// # use decree::decree::{Decree, InputLabel, ChallengeLabel};
+    /// # use decree::error::DecreeResult;
+    /// # fn main() -> DecreeResult<()> {
+    /// let inputs: [InputLabel; 1] = ["input1"];
+    /// let challenges: [ChallengeLabel; 1] = ["challenge1"];
+    /// let mut my_decree = Decree::new("testname", &inputs, &challenges)?;
+    /// my_decree.append_raw_message(b"protocol-version", &[2u8])?;
+    /// assert_eq!(my_decree.bytes_absorbed(), 1);
+    /// my_decree.add_bytes("input1", &[0u8; 32])?;
+    /// assert_eq!(my_decree.bytes_absorbed(), 1 + 16 + 32);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn bytes_absorbed(&self) -> usize {
+        self.transcript.absorbed()
+    }
+
+
+    /// The `get_challenge_domained` method works like `get_challenge`, except that the `domain`
+    /// bytes are appended to the Merlin transcript (under the challenge label) immediately before
+    /// the challenge is generated. This allows for per-challenge domain separation (e.g. a round
+    /// index) beyond the static challenge label, without changing the challenge schedule.
+    ///
+    /// Both the prover and the verifier must use the same `domain` bytes for a given challenge, or
+    /// they will generate different challenges.
+    ///
+    /// # Panics
+    /// Under the same conditions as `get_challenge`.
+    ///
+    /// # Tests
+    ///
+    /// Different domains yield different challenges from the same transcript state
+    /// ```
+    /// # use decree::decree::{Decree, InputLabel, ChallengeLabel};
+    /// # use decree::error::{Error, DecreeErrType, DecreeResult};
+    /// # fn main() -> DecreeResult<()> {
+    /// let inputs: [InputLabel; 1] = ["input1"];
+    /// let challenges: [ChallengeLabel; 1] = ["challenge1"];
+    /// let mut decree_a = Decree::new("testname", &inputs, &challenges)?;
+    /// let mut decree_b = Decree::new("testname", &inputs, &challenges)?;
+    /// let mut decree_c = Decree::new("testname", &inputs, &challenges)?;
+    /// decree_a.add_serial("input1", 10u32)?;
+    /// decree_b.add_serial("input1", 10u32)?;
+    /// decree_c.add_serial("input1", 10u32)?;
+    ///
+    /// let mut challenge_a: [u8; 32] = [0u8; 32];
+    /// let mut challenge_b: [u8; 32] = [0u8; 32];
+    /// let mut challenge_c: [u8; 32] = [0u8; 32];
+    /// decree_a.get_challenge_domained("challenge1", b"round 1", &mut challenge_a)?;
+    /// decree_b.get_challenge_domained("challenge1", b"round 2", &mut challenge_b)?;
+    /// decree_c.get_challenge_domained("challenge1", b"round 1", &mut challenge_c)?;
+    /// assert_ne!(challenge_a, challenge_b);
+    /// assert_eq!(challenge_a, challenge_c);
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// Challenge order is still enforced
+    /// ```should_panic
+    /// # use decree::decree::{Decree, InputLabel, ChallengeLabel};
+    /// # use decree::error::{Error, DecreeErrType, DecreeResult};
+    /// # fn main() -> DecreeResult<()> {
+    /// let inputs: [InputLabel; 1] = ["input1"];
+    /// let challenges: [ChallengeLabel; 2] = ["challenge1", "challenge2"];
+    /// let mut my_decree = Decree::new("testname", &inputs, &challenges)?;
+    /// my_decree.add_serial("input1", 10u32)?;
+    /// let mut challenge_out: [u8; 32] = [0u8; 32];
+    /// my_decree.get_challenge_domained("challenge2", b"round 1", &mut challenge_out)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn get_challenge_domained(
+            &mut self,
+            challenge: &str,
+            domain: &[u8],
+            dest: &mut [u8]
+            ) -> DecreeResult<()> {
+        if dest.is_empty() {
+            return Err(Error::new_invalid_challenge("Zero-length challenge buffer"));
+        }
+        self.commit_required()?;
+        self.check_challenge(challenge)?;
+
+        self.squeeze_with(Some(domain), |transcript, label| challenge_labeled(transcript, label, dest));
+
+        Ok(())
+    }
+
+
+    /// The `into_proof` method bundles challenges generated by this `Decree` into a serializable
+    /// `Proof`, along with the transcript name. The `challenges` should be given in the order they
+    /// were generated; it is up to the caller to pass the bytes that `get_challenge` produced.
     ///
-    /// The following code will not work, because a challenge is requested when specified inputs
-    //// are missing.
+    /// # Tests
     ///
-    /// ```should_panic
+    /// Round trip a proof through serialization and verify it
+    /// ```
     /// # use decree::decree::{Decree, InputLabel, ChallengeLabel};
     /// # use decree::error::{Error, DecreeErrType, DecreeResult};
-    /// # use decree::Inscribe;
-    /// # use decree::inscribe::{INSCRIBE_LENGTH, InscribeBuffer};
-    /// #[derive(Inscribe)]
-    /// pub struct Point {
-    ///     #[inscribe(serialize)]
-    ///     x: i32,
-    ///     #[inscribe(serialize)]
-    ///     y: i32,
-    /// }
+    /// # use decree::proof::Proof;
     /// # fn main() -> DecreeResult<()> {
-    /// let input_data = "input_data_1";
     /// let inputs: [InputLabel; 2] = ["input1", "input2"];
     /// let challenges: [ChallengeLabel; 2] = ["challenge1", "challenge2"];
-    /// let mut my_decree = Decree::new("testname", &inputs, &challenges)?;
-    /// my_decree.add("input1", &Point{ x: 1i32, y: 2i32 })?;
-    /// let mut challenge_out: [u8; 64] = [0u8; 64];
-    /// my_decree.get_challenge("challenge1", &mut challenge_out)?;
+    /// let mut prover = Decree::new("testname", &inputs, &challenges)?;
+    /// let mut verifier = prover.clone_for_verification();
+    ///
+    /// prover.add_serial("input1", 10u32)?;
+    /// prover.add_serial("input2", 14u32)?;
+    /// let mut challenge1: [u8; 32] = [0u8; 32];
+    /// let mut challenge2: [u8; 16] = [0u8; 16];
+    /// prover.get_challenge("challenge1", &mut challenge1)?;
+    /// prover.get_challenge("challenge2", &mut challenge2)?;
+    /// let proof = prover.into_proof(vec![
+    ///     ("challenge1", challenge1.to_vec()),
+    ///     ("challenge2", challenge2.to_vec())]);
+    ///
+    /// let proof_bytes = bcs::to_bytes(&proof).unwrap();
+    /// let received: Proof = bcs::from_bytes(&proof_bytes).unwrap();
+    /// assert_eq!(received, proof);
+    /// assert_eq!(received.challenge("challenge2"), Some(&challenge2[..]));
+    ///
+    /// verifier.add_serial("input1", 10u32)?;
+    /// verifier.add_serial("input2", 14u32)?;
+    /// verifier.verify_challenges(&received)?;
     /// # Ok(())
     /// # }
     /// ```
-    pub fn get_challenge(
+    pub fn into_proof(self, challenges: Vec<(&str, Vec<u8>)>) -> Proof {
+        let labeled = challenges.into_iter()
+            .map(|(label, bytes)| (label.to_string(), bytes))
+            .collect();
+        Proof::new(self.name, labeled)
+    }
+
+
+    /// The `verify_challenges` method regenerates each challenge in `proof`, in order, and checks
+    /// that it matches the bytes the prover sent. All inputs for the current stage must have been
+    /// provided, and the challenges in `proof` must be exactly the pending challenges for this
+    /// stage, in order; a multi-stage protocol should use one `Proof` per stage.
+    ///
+    /// # Panics
+    /// If the name of the transcript doesn't match the name in `proof`.
+    ///
+    /// If `proof` contains no challenges, or leaves any pending challenge of the stage unchecked.
+    ///
+    /// If a challenge in `proof` could not be generated, under the same conditions as
+    /// `get_challenge`.
+    ///
+    /// If a regenerated challenge doesn't match the corresponding challenge in `proof`.
+    ///
+    /// # Tests
+    ///
+    /// A verifier with different inputs rejects the proof
+    /// ```should_panic
+    /// # use decree::decree::{Decree, InputLabel, ChallengeLabel};
+    /// # use decree::error::{Error, DecreeErrType, DecreeResult};
+    /// # fn main() -> DecreeResult<()> {
+    /// let inputs: [InputLabel; 1] = ["input1"];
+    /// let challenges: [ChallengeLabel; 1] = ["challenge1"];
+    /// let mut prover = Decree::new("testname", &inputs, &challenges)?;
+    /// let mut verifier = prover.clone_for_verification();
+    ///
+    /// prover.add_serial("input1", 10u32)?;
+    /// let mut challenge1: [u8; 32] = [0u8; 32];
+    /// prover.get_challenge("challenge1", &mut challenge1)?;
+    /// let proof = prover.into_proof(vec![("challenge1", challenge1.to_vec())]);
+    ///
+    /// verifier.add_serial("input1", 11u32)?;
+    /// verifier.verify_challenges(&proof)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// An empty proof checks nothing, so it is rejected
+    /// ```should_panic
+    /// # use decree::decree::{Decree, InputLabel, ChallengeLabel};
+    /// # use decree::error::{Error, DecreeErrType, DecreeResult};
+    /// # fn main() -> DecreeResult<()> {
+    /// let inputs: [InputLabel; 1] = ["input1"];
+    /// let challenges: [ChallengeLabel; 1] = ["challenge1"];
+    /// let prover = Decree::new("testname", &inputs, &challenges)?;
+    /// let mut verifier = prover.clone_for_verification();
+    /// let proof = prover.into_proof(vec![]);
+    ///
+    /// verifier.add_serial("input1", 10u32)?;
+    /// verifier.verify_challenges(&proof)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// A proof that only covers some of the stage's challenges is rejected
+    /// ```should_panic
+    /// # use decree::decree::{Decree, InputLabel, ChallengeLabel};
+    /// # use decree::error::{Error, DecreeErrType, DecreeResult};
+    /// # fn main() -> DecreeResult<()> {
+    /// let inputs: [InputLabel; 1] = ["input1"];
+    /// let challenges: [ChallengeLabel; 2] = ["challenge1", "challenge2"];
+    /// let mut prover = Decree::new("testname", &inputs, &challenges)?;
+    /// let mut verifier = prover.clone_for_verification();
+    ///
+    /// prover.add_serial("input1", 10u32)?;
+    /// let challenge1: [u8; 32] = prover.challenge("challenge1")?;
+    /// let proof = prover.into_proof(vec![("challenge1", challenge1.to_vec())]);
+    ///
+    /// verifier.add_serial("input1", 10u32)?;
+    /// verifier.verify_challenges(&proof)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn verify_challenges(&mut self, proof: &Proof) -> DecreeResult<()> {
+        if proof.name() != self.name {
+            return Err(Error::new_invalid_challenge("Proof transcript name mismatch"));
+        }
+        if proof.challenges().is_empty() {
+            return Err(Error::new_invalid_challenge("Proof contains no challenges"));
+        }
+
+        for (label, expected) in proof.challenges() {
+            if self.challenges.first().map(|next| next.as_ref()) != Some(label.as_str()) {
+                return Err(Error::new_invalid_challenge("Challenge order incorrect"));
+            }
+
+            let mut regenerated = vec![0u8; expected.len()];
+            self.get_challenge(label, regenerated.as_mut_slice())?;
+            if &regenerated != expected {
+                return Err(Error::new_invalid_challenge("Challenge mismatch"));
+            }
+        }
+
+        // Every challenge of the stage must have been checked
+        if !self.challenges.is_empty() {
+            return Err(Error::new_invalid_challenge(format!(
+                "Proof leaves challenge(s) unchecked: {}", self.challenges.join(", "))));
+        }
+        Ok(())
+    }
+
+
+    // Generates the next challenge from the transcript and removes it from the pending list,
+    // returning its label. The caller is responsible for calling `check_challenge` first.
+    fn squeeze(&mut self, dest: &mut [u8]) -> Label {
+        self.squeeze_with(None, |transcript, label| {
+            challenge_labeled(transcript, label, dest);
+            label.clone()
+        })
+    }
+
+
+    // Runs `draw` against the transcript as the generation of the next challenge, which may
+    // squeeze the transcript more than once. If a `domain` is given, it is absorbed under the
+    // challenge's label first. The caller must have already checked the challenge.
+    fn squeeze_with<R>(
             &mut self,
-            challenge: ChallengeLabel,
-            dest: &mut [u8]
-            ) -> DecreeResult<()> {
+            domain: Option<&[u8]>,
+            draw: impl FnOnce(&mut LoggedTranscript, &Label) -> R) -> R {
+        let label = self.challenges.remove(0);
+        if let Some(domain) = domain {
+            append_labeled(&mut self.transcript, &label, domain);
+        }
+        if self.strict_challenges {
+            bind_strict(&mut self.transcript, &label, self.challenge_index);
+        }
+
+        let result = draw(&mut self.transcript, &label);
+
+        #[cfg(feature = "serde")]
+        {
+            let squeezed = self.transcript.take_squeezed();
+            if let Some(history) = self.history.as_mut() {
+                history.draws.push(DrawRecord { domain: domain.map(<[u8]>::to_vec), squeezed });
+            }
+        }
+
+        self.challenge_index += 1;
+        result
+    }
+
+
+    // Checks that `challenge` is the next challenge to be generated, and that all inputs have
+    // been committed. Errors carry the requested label.
+    fn check_challenge(&self, challenge: &str) -> DecreeResult<()> {
+        self.check_challenge_order(challenge)
+            .map_err(|err| err.with_label(challenge.to_owned()))
+    }
+
+    fn check_challenge_order(&self, challenge: &str) -> DecreeResult<()> {
         if !self.committed {
             return Err(Error::new_general("Missing transcript parameters"));
         }
         if self.challenges.is_empty() {
             return Err(Error::new_invalid_challenge("No remaining challenges"));
         }
-        if !self.challenges.contains(&challenge) {
+        if !self.challenges.iter().any(|label| label == challenge) {
             return Err(Error::new_invalid_challenge("Requested challenge not in spec"));
         }
         if self.challenges[0] != challenge {
             return Err(Error::new_invalid_challenge("Challenge order incorrect"));
         }
+        Ok(())
+    }
+}
 
-        self.transcript.challenge_bytes(challenge.as_bytes(), dest);
 
-        self.challenges.remove(0);
+/// `Debug` output lists the name, the current stage's input labels, and the pending challenges.
+/// Input values are never printed, since they may be secret witness material; each input is shown
+/// as `<committed>`, `<pending>` (provided but not yet committed), or `<missing>`.
+impl fmt::Debug for Decree {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut sorted_inputs: Vec<&Label> = self.inputs.iter().collect();
+        sorted_inputs.sort();
+        let inputs = sorted_inputs.into_iter().map(|label| {
+            let status = match (self.values.contains_key(label), self.committed) {
+                (true, true) => format_args!("<committed>"),
+                (true, false) => format_args!("<pending>"),
+                (false, _) => format_args!("<missing>"),
+            };
+            (label.as_ref(), status)
+        });
 
-        Ok(())
+        f.debug_struct("Decree")
+            .field("name", &self.name)
+            .field("inputs", &DebugMap(inputs.collect()))
+            .field("committed", &self.committed)
+            .field("pending_challenges", &self.remaining_challenges())
+            .finish()
+    }
+}
+
+// Formats a list of pairs as a map, without requiring the values to be stored anywhere.
+struct DebugMap<'a>(Vec<(&'a str, fmt::Arguments<'a>)>);
+
+impl fmt::Debug for DebugMap<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.0.iter().map(|(label, status)| (label, status))).finish()
+    }
+}
+
+
+// The longest challenge that a deserialized `Decree` will squeeze while replaying its transcript,
+// so that untrusted state can't make it allocate without bound. A `Decree` that has drawn a longer
+// challenge can't be serialized.
+#[cfg(feature = "serde")]
+const MAX_REPLAYED_CHALLENGE_LENGTH: usize = 1 << 16;
+
+// How the transcript of a `Decree` was built. Merlin transcripts can't be serialized, so with the
+// `serde` feature a `Decree` keeps this instead, and a deserialized `Decree` is rebuilt by running
+// it back through the usual stage, commit and challenge paths. Only what those paths need is kept:
+// the specification and inputs of each finished stage, any raw messages, and the lengths squeezed
+// for each challenge; the current stage's inputs are the `Decree`'s own. Finished stages are
+// shared between clones and checkpoints rather than copied.
+#[cfg(feature = "serde")]
+#[derive(Clone)]
+struct History {
+    // Shared by every history descended from the same `Decree`
+    origin: Arc<Origin>,
+    stages: Vec<Arc<StageRecord>>,
+    // The raw messages and challenges of the current stage
+    raw_messages: Vec<(&'static [u8], Vec<u8>)>,
+    draws: Vec<DrawRecord>,
+}
+
+// What a `Decree` absorbs before its first stage, besides its name and strict binding.
+#[cfg(feature = "serde")]
+struct Origin {
+    version: Option<u32>,
+}
+
+#[cfg(feature = "serde")]
+struct StageSpec {
+    inputs: Vec<Label>,
+    optional_inputs: Vec<Label>,
+    kept_inputs: Vec<Label>,
+    challenges: Vec<Label>,
+}
+
+#[cfg(feature = "serde")]
+struct StageRecord {
+    spec: StageSpec,
+    raw_messages: Vec<(&'static [u8], Vec<u8>)>,
+    // The inputs provided in the stage, in label order. Kept inputs belong to an earlier stage.
+    values: Vec<(Label, StoredInput)>,
+    draws: Vec<DrawRecord>,
+}
+
+#[cfg(feature = "serde")]
+#[derive(Clone)]
+struct DrawRecord {
+    domain: Option<Vec<u8>>,
+    squeezed: Vec<usize>,
+}
+
+#[cfg(feature = "serde")]
+impl History {
+    fn new() -> History {
+        History {
+            origin: Arc::new(Origin { version: None }),
+            stages: Vec::new(),
+            raw_messages: Vec::new(),
+            draws: Vec::new(),
+        }
+    }
+
+    // The history of the same `Decree`, back at the start of its first stage.
+    fn restart(&self) -> History {
+        History {
+            origin: self.origin.clone(),
+            stages: Vec::new(),
+            raw_messages: Vec::new(),
+            draws: Vec::new(),
+        }
+    }
+
+    fn finish_stage(&mut self, spec: StageSpec, values: Vec<(Label, StoredInput)>) {
+        self.stages.push(Arc::new(StageRecord {
+            spec,
+            raw_messages: std::mem::take(&mut self.raw_messages),
+            values,
+            draws: std::mem::take(&mut self.draws),
+        }));
+    }
+}
+
+// Labels are serialized with whether they were owned, since owned labels are absorbed differently.
+#[cfg(feature = "serde")]
+#[derive(serde::Serialize, serde::Deserialize)]
+struct SerialLabel {
+    label: String,
+    owned: bool,
+}
+
+#[cfg(feature = "serde")]
+impl SerialLabel {
+    fn from_label(label: &Label) -> SerialLabel {
+        SerialLabel {
+            label: label.to_string(),
+            owned: matches!(label, Cow::Owned(_)),
+        }
+    }
+
+    fn into_label(self) -> DecreeResult<Label> {
+        if self.owned {
+            Ok(Cow::Owned(self.label))
+        } else {
+            intern_str(&self.label).map(Cow::Borrowed).ok_or_else(intern_error)
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+fn intern_error() -> Error {
+    Error::new_serialization("Too many distinct labels in deserialized state")
+}
+
+#[cfg(feature = "serde")]
+fn serial_labels(labels: &[Label]) -> Vec<SerialLabel> {
+    labels.iter().map(SerialLabel::from_label).collect()
+}
+
+#[cfg(feature = "serde")]
+fn deserial_labels(labels: Vec<SerialLabel>) -> DecreeResult<Vec<Label>> {
+    labels.into_iter().map(SerialLabel::into_label).collect()
+}
+
+// The serialized form of a `Decree`: its configuration, and every stage so far, the current one
+// last.
+#[cfg(feature = "serde")]
+#[derive(serde::Serialize, serde::Deserialize)]
+struct DecreeState {
+    name: String,
+    version: Option<u32>,
+    strict_challenges: bool,
+    strict_binding: bool,
+    manual_commit: bool,
+    limits: DecreeLimits,
+    stages: Vec<StageState>,
+    // The labels of the cached challenges. Their bytes are regenerated by the replay, rather
+    // than taken from the serialized state.
+    challenge_cache: Option<Vec<SerialLabel>>,
+}
+
+#[cfg(feature = "serde")]
+#[derive(serde::Serialize, serde::Deserialize)]
+struct StageState {
+    inputs: Vec<SerialLabel>,
+    optional_inputs: Vec<SerialLabel>,
+    kept_inputs: Vec<SerialLabel>,
+    challenges: Vec<SerialLabel>,
+    raw_messages: Vec<(Vec<u8>, Vec<u8>)>,
+    values: Vec<(String, Vec<u8>)>,
+    committed: bool,
+    draws: Vec<DrawState>,
+}
+
+#[cfg(feature = "serde")]
+#[derive(serde::Serialize, serde::Deserialize)]
+struct DrawState {
+    domain: Option<Vec<u8>>,
+    squeezed: Vec<u64>,
+}
+
+// Serialized inputs are copies, so they are wiped along with the originals.
+#[cfg(all(feature = "serde", feature = "zeroize"))]
+impl Drop for StageState {
+    fn drop(&mut self) {
+        use zeroize::Zeroize;
+        for (_, value) in self.values.iter_mut() {
+            value.zeroize();
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl StageState {
+    fn new<'a>(
+            spec: &StageSpec,
+            raw_messages: &[(&'static [u8], Vec<u8>)],
+            values: impl Iterator<Item = (&'a Label, &'a StoredInput)>,
+            committed: bool,
+            draws: &[DrawRecord]) -> StageState {
+        StageState {
+            inputs: serial_labels(&spec.inputs),
+            optional_inputs: serial_labels(&spec.optional_inputs),
+            kept_inputs: serial_labels(&spec.kept_inputs),
+            challenges: serial_labels(&spec.challenges),
+            raw_messages: raw_messages.iter().map(|(label, message)| (label.to_vec(), message.clone())).collect(),
+            values: values.map(|(label, value)| (label.to_string(), value.to_vec())).collect(),
+            committed,
+            draws: draws.iter().map(|draw| DrawState {
+                domain: draw.domain.clone(),
+                squeezed: draw.squeezed.iter().map(|length| *length as u64).collect(),
+            }).collect(),
+        }
+    }
+
+    // Runs the stage's raw messages, inputs and challenges through `decree`, which must already be
+    // set up for the stage. Returns the bytes of the challenges drawn with a single squeeze and no
+    // domain, as `get_challenge` draws them, for rebuilding the challenge cache.
+    fn replay_into(mut self, decree: &mut Decree) -> DecreeResult<Vec<(Label, Vec<u8>)>> {
+        for (label, message) in self.raw_messages.iter() {
+            let label = intern(label).ok_or_else(intern_error)?;
+            decree.append_raw_message(label, message)?;
+        }
+        for (label, value) in std::mem::take(&mut self.values) {
+            decree.add_input(&label, value)?;
+        }
+        if self.committed {
+            decree.commit_now()?;
+        }
+        if decree.committed != self.committed {
+            return Err(Error::new_serialization("Stage commitment does not match its inputs"));
+        }
+
+        let mut drawn = Vec::new();
+        for draw in self.draws.iter() {
+            let label = match decree.challenges.first() {
+                Some(label) => label.clone(),
+                None => { return Err(Error::new_invalid_challenge("No remaining challenges")); }
+            };
+            decree.check_challenge(&label)?;
+
+            let lengths = draw.squeezed
+                .iter()
+                .map(|length| usize::try_from(*length)
+                    .ok()
+                    .filter(|length| (1..=MAX_REPLAYED_CHALLENGE_LENGTH).contains(length)))
+                .collect::<Option<Vec<usize>>>()
+                .filter(|lengths| !lengths.is_empty())
+                .ok_or_else(|| Error::new_serialization("Invalid challenge length").with_label(label.clone()))?;
+            let mut buf = vec![0u8; lengths.iter().copied().max().unwrap_or(0)];
+            let plain = draw.domain.is_none() && lengths.len() == 1;
+            decree.squeeze_with(draw.domain.as_deref(), |transcript, label| {
+                for length in lengths {
+                    challenge_labeled(transcript, label, &mut buf[..length]);
+                }
+            });
+            if plain {
+                drawn.push((label, buf));
+            }
+        }
+        Ok(drawn)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl DecreeState {
+    // Rebuilds a `Decree` by running each recorded stage through the same paths that built the
+    // original, so that malformed state is rejected just as the equivalent calls would be.
+    fn rebuild(self) -> DecreeResult<Decree> {
+        let mut stages = self.stages.into_iter();
+        let mut first = stages.next().ok_or_else(|| Error::new_serialization("Missing initial stage"))?;
+        let name = intern_str(&self.name).ok_or_else(intern_error)?;
+        check_name(name)?;
+
+        let mut decree = Decree::assemble(
+            name,
+            LoggedTranscript::new(name.as_bytes()),
+            deserial_labels(std::mem::take(&mut first.inputs))?,
+            deserial_labels(std::mem::take(&mut first.challenges))?,
+            self.limits)?;
+        decree.strict_challenges = self.strict_challenges;
+        decree.manual_commit = self.manual_commit;
+        // As in `with_strict_binding` and `with_version`
+        if self.strict_binding {
+            decree.strict_binding = true;
+            decree.initial_transcript.append_message(BINDING_NAME_LABEL, name.as_bytes());
+        }
+        if let Some(version) = self.version {
+            decree.initial_transcript.append_message(VERSION_LABEL, &version.to_le_bytes());
+        }
+        decree.transcript = decree.initial_transcript.clone();
+        if let Some(history) = decree.history.as_mut() {
+            history.origin = Arc::new(Origin { version: self.version });
+        }
+
+        let optional = deserial_labels(std::mem::take(&mut first.optional_inputs))?;
+        if optional.iter().any(|label| !decree.inputs.contains(label)) {
+            return Err(Error::new_init_fail("Optional inputs must be inputs of the stage"));
+        }
+        if !first.kept_inputs.is_empty() {
+            return Err(Error::new_init_fail("The first stage can't keep inputs"));
+        }
+        decree.initial_optional_inputs.clone_from(&optional);
+        decree.optional_inputs = optional;
+        let mut drawn = first.replay_into(&mut decree)?;
+
+        for mut stage in stages {
+            decree.extend_stage(
+                deserial_labels(std::mem::take(&mut stage.inputs))?,
+                deserial_labels(std::mem::take(&mut stage.optional_inputs))?,
+                deserial_labels(std::mem::take(&mut stage.kept_inputs))?,
+                deserial_labels(std::mem::take(&mut stage.challenges))?)?;
+            drawn = stage.replay_into(&mut decree)?;
+        }
+
+        // The cache only ever holds challenges drawn by `get_challenge` in the current stage
+        if let Some(cache) = self.challenge_cache {
+            let mut entries = Vec::with_capacity(cache.len());
+            for label in cache {
+                let label = label.into_label()?;
+                let position = drawn.iter().position(|(drawn_label, _)| *drawn_label == label);
+                match position {
+                    Some(position) => entries.push(drawn.swap_remove(position)),
+                    None => {
+                        return Err(Error::new_serialization("Cached challenge was not generated").with_label(label));
+                    }
+                }
+            }
+            decree.challenge_cache = Some(entries);
+        }
+
+        decree.check_invariants()?;
+        Ok(decree)
+    }
+}
+
+/// With the `serde` feature, a `Decree` can be serialized, e.g. to checkpoint a prover to disk
+/// between rounds. Since Merlin transcripts aren't serializable, what built the transcript is
+/// stored instead: the `Decree`'s configuration, the labels and inputs of every stage so far, any
+/// raw messages, and the length of each challenge drawn. On deserialization, these are run back
+/// through the same paths as the original calls, so the transcript is rebuilt exactly, and
+/// malformed or inconsistent state is rejected with the error the equivalent call would return.
+/// Only the labels of cached challenges are stored; their bytes are the ones the replay draws.
+/// A serialized `Decree` grows with the protocol, and contains every input absorbed so far; it
+/// should be protected accordingly.
+///
+/// To make this possible, a `Decree` holds on to the inputs of its finished stages (but not the
+/// messages derived from them) when the `serde` feature is enabled. Finished stages are shared
+/// between clones and checkpoints, not copied.
+///
+/// A `Decree` created with `from_transcript` can't be serialized, since the state of the
+/// transcript it was given is unknown, and neither can one that has restored a checkpoint of an
+/// unrelated `Decree`. Neither can a `Decree` that has drawn a challenge longer than 64 KiB, which
+/// is the most that deserialization will squeeze. Borrowed labels must be `&'static` again once
+/// deserialized, so they are interned; each distinct label is allocated once, and deserialization
+/// fails once 64 KiB of distinct labels have been interned.
+///
+/// # Tests
+///
+/// A deserialized `Decree` continues exactly where the original left off
+/// ```
+/// # use decree::decree::{Decree, InputLabel, ChallengeLabel};
+/// # use decree::error::DecreeResult;
+/// # fn main() -> DecreeResult<()> {
+/// let inputs: [InputLabel; 2] = ["input1", "input2"];
+/// let challenges: [ChallengeLabel; 2] = ["challenge1", "challenge2"];
+/// let mut original = Decree::new("testname", &inputs, &challenges)?;
+/// original.add_serial("input1", 10u32)?;
+///
+/// let saved = bcs::to_bytes(&original).unwrap();
+/// let mut restored: Decree = bcs::from_bytes(&saved).unwrap();
+///
+/// for decree in [&mut original, &mut restored] {
+///     decree.add_serial("input2", 14u32)?;
+/// }
+/// let original_out: [u8; 32] = original.challenge("challenge1")?;
+/// let restored_out: [u8; 32] = restored.challenge("challenge1")?;
+/// assert_eq!(original_out, restored_out);
+/// # Ok(())
+/// # }
+/// ```
+///
+/// Decrees built on external transcripts can't be serialized
+/// ```
+/// # use decree::decree::{Decree, InputLabel, ChallengeLabel};
+/// # use decree::error::DecreeResult;
+/// # use merlin::Transcript;
+/// # fn main() -> DecreeResult<()> {
+/// let inputs: [InputLabel; 1] = ["input1"];
+/// let challenges: [ChallengeLabel; 1] = ["challenge1"];
+/// let external = Decree::from_transcript(Transcript::new(b"outer protocol"), &inputs, &challenges)?;
+/// assert!(bcs::to_bytes(&external).is_err());
+/// # Ok(())
+/// # }
+/// ```
+///
+/// Malformed state is rejected rather than trusted: a value for an input that isn't in the stage,
+/// and a challenge length too long to replay
+/// ```
+/// # use decree::decree::{Decree, InputLabel, ChallengeLabel};
+/// # use decree::error::DecreeResult;
+/// # fn main() -> DecreeResult<()> {
+/// # fn replace_last(bytes: &mut [u8], from: &[u8], to: &[u8]) {
+/// #     let start = (0..=bytes.len() - from.len()).rev().find(|i| bytes[*i..].starts_with(from)).unwrap();
+/// #     bytes[start..start + from.len()].copy_from_slice(to);
+/// # }
+/// let inputs: [InputLabel; 1] = ["input1"];
+/// let challenges: [ChallengeLabel; 2] = ["challenge1", "challenge2"];
+/// let mut my_decree = Decree::new("testname", &inputs, &challenges)?;
+/// my_decree.add_serial("input1", 10u32)?;
+/// let _ = my_decree.get_challenge_vec("challenge1", 0xabcd)?;
+/// let saved = bcs::to_bytes(&my_decree).unwrap();
+/// assert!(bcs::from_bytes::<Decree>(&saved).is_ok());
+///
+/// // The value is listed after the stage's labels
+/// let mut unknown_input = saved.clone();
+/// replace_last(&mut unknown_input, b"input1", b"inputX");
+/// assert!(bcs::from_bytes::<Decree>(&unknown_input).is_err());
+///
+/// let mut huge_challenge = saved.clone();
+/// replace_last(&mut huge_challenge, &0xabcdu64.to_le_bytes(), &u64::MAX.to_le_bytes());
+/// assert!(bcs::from_bytes::<Decree>(&huge_challenge).is_err());
+/// # Ok(())
+/// # }
+/// ```
+///
+/// Challenges can't be recorded before the inputs are committed
+/// ```
+/// # use decree::decree::{Decree, InputLabel, ChallengeLabel};
+/// # use decree::error::DecreeResult;
+/// # fn main() -> DecreeResult<()> {
+/// let inputs: [InputLabel; 1] = ["input1"];
+/// let challenges: [ChallengeLabel; 1] = ["challenge1"];
+/// let mut my_decree = Decree::new_manual("testname", &inputs, &challenges)?;
+/// my_decree.add_serial("input1", 10u32)?;
+/// my_decree.commit()?;
+/// let _: [u8; 32] = my_decree.challenge("challenge1")?;
+/// let mut saved = bcs::to_bytes(&my_decree).unwrap();
+///
+/// // The stage's `committed` flag is followed by its one challenge (a count, no domain, a count
+/// // and a length) and by the empty challenge cache
+/// let flag = saved.len() - (1 + 1 + 1 + 8) - 1 - 1;
+/// assert_eq!(saved[flag..], [1, 1, 0, 1, 32, 0, 0, 0, 0, 0, 0, 0, 0]);
+/// saved[flag] = 0;
+/// assert!(bcs::from_bytes::<Decree>(&saved).is_err());
+/// # Ok(())
+/// # }
+/// ```
+#[cfg(feature = "serde")]
+impl Serialize for Decree {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::Error as _;
+
+        let history = match self.history.as_ref() {
+            Some(history) => history,
+            None => {
+                return Err(S::Error::custom("Cannot serialize a Decree whose transcript was built elsewhere"));
+            }
+        };
+        let drawn = history.stages
+            .iter()
+            .flat_map(|stage| stage.draws.iter())
+            .chain(history.draws.iter());
+        if drawn.flat_map(|draw| draw.squeezed.iter()).any(|length| *length > MAX_REPLAYED_CHALLENGE_LENGTH) {
+            return Err(S::Error::custom("Cannot serialize a Decree with a challenge longer than 64 KiB"));
+        }
+
+        let mut stages: Vec<StageState> = history.stages
+            .iter()
+            .map(|stage| StageState::new(
+                &stage.spec,
+                &stage.raw_messages,
+                stage.values.iter().map(|(label, value)| (label, value)),
+                true,
+                &stage.draws))
+            .collect();
+        let current = StageSpec {
+            inputs: self.inputs.clone(),
+            optional_inputs: self.optional_inputs.clone(),
+            kept_inputs: self.kept_inputs.clone(),
+            challenges: self.stage_challenges.clone(),
+        };
+        let values = self.inputs
+            .iter()
+            .filter(|label| !self.kept_inputs.contains(label))
+            .filter_map(|label| self.values.get(label).map(|value| (label, value)));
+        stages.push(StageState::new(&current, &history.raw_messages, values, self.committed, &history.draws));
+
+        let challenge_cache = self.challenge_cache.as_ref().map(|cache| {
+            cache.iter().map(|(label, _)| SerialLabel::from_label(label)).collect()
+        });
+
+        DecreeState {
+            name: self.name.to_string(),
+            version: history.origin.version,
+            strict_challenges: self.strict_challenges,
+            strict_binding: self.strict_binding,
+            manual_commit: self.manual_commit,
+            limits: self.limits,
+            stages,
+            challenge_cache,
+        }.serialize(serializer)
     }
-}
\ No newline at end of file
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Decree {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Decree, D::Error> {
+        use serde::de::Error as _;
+
+        DecreeState::deserialize(deserializer)?
+            .rebuild()
+            .map_err(D::Error::custom)
+    }
+}
+
+
+/// With the `zeroize` feature, the inputs a `Decree` (or `DecreeCheckpoint`) holds for the current
+/// stage are wiped when it is dropped, as is the Merlin transcript state. With the `serde` feature,
+/// a `Decree` also holds the inputs of its finished stages so that it can be serialized; these are
+/// shared with its clones and checkpoints, and wiped when the last of them is dropped, as are the
+/// copies made while serializing and deserializing. Values returned to the caller, such as
+/// challenges, and the serialized bytes themselves are the caller's responsibility.
+#[cfg(feature = "zeroize")]
+impl zeroize::ZeroizeOnDrop for Decree {}
+
+#[cfg(feature = "zeroize")]
+impl zeroize::ZeroizeOnDrop for DecreeCheckpoint {}
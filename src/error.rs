@@ -1,3 +1,4 @@
+use std::borrow::Cow;
 use std::fmt;
 
 #[derive(Clone, Copy, Debug, PartialEq)]
@@ -6,13 +7,15 @@ pub enum DecreeErrType {
     InvalidLabel,
     InvalidChallenge,
     ExtendFail,
+    Serialization,
     General,
 }
 
-#[derive(Debug)]
+#[derive(Clone, Debug, PartialEq)]
 pub struct Error {
     err_type: DecreeErrType,
-    err_string: &'static str,
+    err_string: Cow<'static, str>,
+    label: Option<Cow<'static, str>>,
 }
 
 impl Error {
@@ -20,8 +23,29 @@ impl Error {
         self.err_type
     }
 
-    pub fn get_str(&self) -> &'static str {
-        self.err_string
+    pub fn get_str(&self) -> &str {
+        &self.err_string
+    }
+
+    /// Returns the input or challenge label the error concerns, if one was attached.
+    pub fn get_label(&self) -> Option<&str> {
+        self.label.as_deref()
+    }
+
+    /// Attaches the input or challenge label the error concerns. `Decree` attaches the label to
+    /// errors about a specific input or challenge, so that the offending label shows up in the
+    /// message without changing the message itself.
+    ///
+    /// ```
+    ///     use decree::error::{DecreeErrType, Error};
+    ///     let l_err = Error::new_invalid_label("Invalid label").with_label("input3");
+    ///     assert_eq!(l_err.get_label(), Some("input3"));
+    ///     assert_eq!(l_err.get_str(), "Invalid label");
+    ///     assert_eq!(format!("{}", l_err), "Invalid label: Invalid label (label `input3`)");
+    /// ```
+    pub fn with_label(mut self, label: impl Into<Cow<'static, str>>) -> Error {
+        self.label = Some(label.into());
+        self
     }
 
     /// ```
@@ -29,10 +53,11 @@ impl Error {
     ///     let l_err = Error::new(DecreeErrType::InitFail, "Duplicate labels");
     ///     println!("{}", l_err);
     /// ```
-    pub fn new(e_type: DecreeErrType, msg: &'static str) -> Error {
+    pub fn new(e_type: DecreeErrType, msg: impl Into<Cow<'static, str>>) -> Error {
         Error {
             err_type : e_type,
-            err_string : msg,
+            err_string : msg.into(),
+            label : None,
         }
     }
 
@@ -42,7 +67,7 @@ impl Error {
     ///     assert_eq!(l_err.get_type(), DecreeErrType::InvalidLabel);
     ///     println!("{}", l_err);
     /// ```
-    pub fn new_invalid_label(msg: &'static str) -> Error {
+    pub fn new_invalid_label(msg: impl Into<Cow<'static, str>>) -> Error {
         Self::new(DecreeErrType::InvalidLabel, msg)
     }
 
@@ -52,7 +77,7 @@ impl Error {
     ///     assert_eq!(l_err.get_type(), DecreeErrType::InvalidChallenge);
     ///     println!("{}", l_err);
     /// ```
-    pub fn new_invalid_challenge(msg: &'static str) -> Error {
+    pub fn new_invalid_challenge(msg: impl Into<Cow<'static, str>>) -> Error {
         Self::new(DecreeErrType::InvalidChallenge, msg)
     }
 
@@ -62,7 +87,7 @@ impl Error {
     ///     assert_eq!(l_err.get_type(), DecreeErrType::InitFail);
     ///     println!("{}", l_err);
     /// ```
-    pub fn new_init_fail(msg: &'static str) -> Error {
+    pub fn new_init_fail(msg: impl Into<Cow<'static, str>>) -> Error {
         Self::new(DecreeErrType::InitFail, msg)
     }
 
@@ -72,17 +97,31 @@ impl Error {
     ///     assert_eq!(l_err.get_type(), DecreeErrType::ExtendFail);
     ///     println!("{}", l_err);
     /// ```
-    pub fn new_extend_fail(msg: &'static str) -> Error {
+    pub fn new_extend_fail(msg: impl Into<Cow<'static, str>>) -> Error {
         Self::new(DecreeErrType::ExtendFail, msg)
     }
 
     /// ```
     ///     use decree::error::{DecreeErrType, Error};
-    ///     let l_err = Error::new_general("Failed serialization");
+    ///     let l_err = Error::new_serialization("Could not serialize input input1");
+    ///     assert_eq!(l_err.get_type(), DecreeErrType::Serialization);
+    ///     assert_eq!(format!("{}", l_err), "Serialization failure: Could not serialize input input1");
+    /// ```
+    pub fn new_serialization(msg: impl Into<Cow<'static, str>>) -> Error {
+        Self::new(DecreeErrType::Serialization, msg)
+    }
+
+    /// ```
+    ///     use decree::error::{DecreeErrType, Error};
+    ///     let l_err = Error::new_general("Failed operation");
     ///     assert_eq!(l_err.get_type(), DecreeErrType::General);
     ///     println!("{}", l_err);
+    ///
+    ///     let label = "input1";
+    ///     let l_err = Error::new_general(format!("Could not process input {}", label));
+    ///     assert_eq!(l_err.get_str(), "Could not process input input1");
     /// ```
-    pub fn new_general(msg: &'static str) -> Error {
+    pub fn new_general(msg: impl Into<Cow<'static, str>>) -> Error {
         Self::new(DecreeErrType::General, msg)
     }
 }
@@ -94,9 +133,37 @@ impl fmt::Display for Error {
             DecreeErrType::InvalidLabel => {write!(f, "Invalid label")?; },
             DecreeErrType::InvalidChallenge => {write!(f, "Invalid challenge")?; },
             DecreeErrType::ExtendFail => {write!(f, "Extend failure")?; },
+            DecreeErrType::Serialization => {write!(f, "Serialization failure")?; },
             DecreeErrType::General => {write!(f, "General failure")?; },
         }
-        write!(f, ": {}", self.get_str())
+        write!(f, ": {}", self.get_str())?;
+        if let Some(label) = self.get_label() {
+            write!(f, " (label `{}`)", label)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for Error {}
+
+/// Errors compare equal to a string when their message matches it, regardless of their type or
+/// attached label.
+///
+/// ```
+///     use decree::error::Error;
+///     let l_err = Error::new_invalid_label("Invalid label");
+///     assert_eq!(l_err, "Invalid label");
+///     assert_ne!(l_err, "Label already used");
+/// ```
+impl PartialEq<str> for Error {
+    fn eq(&self, other: &str) -> bool {
+        self.err_string == other
+    }
+}
+
+impl PartialEq<&str> for Error {
+    fn eq(&self, other: &&str) -> bool {
+        self.err_string == *other
     }
 }
 
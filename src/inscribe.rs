@@ -1,8 +1,38 @@
+use std::collections::{BTreeMap, HashMap};
+use std::hash::BuildHasher;
+use std::marker::PhantomData;
+use std::ops::Range;
+use serde::Serialize;
+use num_bigint::{BigInt, BigUint, Sign};
+use tiny_keccak::{Hasher, TupleHash};
+use crate::backend::HashBackend;
 use crate::decree::FSInput;
-use crate::error::DecreeResult;
+use crate::error::{Error, DecreeResult};
 pub const INSCRIBE_LENGTH: usize = 64;
 pub type InscribeBuffer = [u8; INSCRIBE_LENGTH];
 
+/// The mark used by the byte string impls (`[u8]`, `Vec<u8>`, and `[u8; N]`). Hand-written impls
+/// for byte-like types can use it to inscribe the same way.
+pub const INSCRIBE_MARK_U8_SLICE: &str = "bytes";
+/// The mark used by `Serialized`, for inscriptions of a `bcs` serialization.
+pub const INSCRIBE_MARK_SERIALIZE: &str = "serde_bcs_serialized";
+
+const MARKED_MARK: &str = "decree_marked";
+const RANGE_MARK: &str = "range";
+const OPTION_MARK: &str = "option";
+const VEC_MARK: &str = "vec";
+const ARRAY_MARK: &str = "array";
+const TUPLE_MARK: &str = "tuple";
+const BTREE_MAP_MARK: &str = "btree_map";
+const HASH_MAP_MARK: &str = "hash_map";
+const BOOL_MARK: &str = "bool";
+const PHANTOM_DATA_MARK: &str = "phantom_data";
+const STRING_MARK: &str = "string";
+const BIG_UINT_MARK: &str = "big_uint";
+const BIG_INT_MARK: &str = "big_int";
+#[cfg(feature = "either")]
+const EITHER_MARK: &str = "either";
+
 
 /// The `Inscribe` trait is a derivable trait for structs that makes it easy to incorporate
 /// contextual data into Fiat-Shamir transcripts. There are two main methods that the trait
@@ -16,20 +46,129 @@ pub type InscribeBuffer = [u8; INSCRIBE_LENGTH];
 ///
 /// For derived structs, the `get_inscription` method will do the following:
 ///     - Initialize a TupleHash with the results of `get_mark`
-///     - For each member of the struct, do one of three things:
+///     - For each member of the struct, do one of five things:
 ///         + For `Inscribe` implementers, call `get_inscription` and add the results to the
 ///             TupleHash
 ///         + Use the `bcs` library to serialize the member and add the results to the TupleHash
+///         + For `AsRef<[u8]>` implementers tagged with `#[inscribe(bytes)]`, add the raw bytes
+///             to the TupleHash, without the length prefix that `bcs` would add
 ///         + Skip the item entirely
+///         + For collections tagged with `#[inscribe(skip_if_empty)]`, skip the member if its
+///             `is_empty` method returns `true`, and otherwise serialize it with `bcs` as above
 ///     - At the end, the TupleHash result is returned
 ///
 /// By default, struct members are assumed to implement the `Inscribe` trait, but this can be
-/// overridden using `inscribe` attributes:
+/// overridden using `inscribe` attributes. Members may use derived or hand-written `Inscribe`
+/// implementations interchangeably, but the inscription of every member must be exactly as long
+/// as that member's `inscription_length`; otherwise, `get_inscription` returns an error.
+///
+/// Inscriptions are `INSCRIBE_LENGTH` (64) bytes long by default. A derived struct or enum can
+/// use `#[inscribe_length(N)]` to produce an `N`-byte inscription instead, e.g. 32 bytes to save
+/// space, or 128 for a higher security margin. This changes the TupleHash output length, and
+/// therefore the whole inscription, not just its size. Members with different lengths can be
+/// mixed freely: a member's inscription is absorbed as a single TupleHash item of that member's
+/// own `inscription_length`, and a type's length only sets the size of its own output. A 32-byte
+/// child therefore contributes the same 32 bytes whether its parent is 64 or 128 bytes long.
+///
+/// The length is a runtime property of each implementation, returned by `inscription_length`,
+/// rather than an associated const or a const generic parameter of the trait. A trait with an
+/// associated const can't be used as a trait object, and `Decree::replay` takes its values as
+/// `&dyn Inscribe` so that values of different types and lengths can be passed together.
+///
+/// Derived implementations absorb their members with TupleHash by default. Another hash function
+/// can be selected with `#[inscribe_backend(...)]`; see the `backend` module.
+///
+/// A member can also be tagged with `#[inscribe(compute_with = method)]`, in which case the
+/// member's own value is ignored, and the inscription of the value returned by `self.method()`
+/// (which must implement `Inscribe`) is added in its place. The computed value occupies the
+/// member's position in the sort order, so `inscribe_name` can be used to move it around. This
+/// is useful for binding derived values (such as a commitment recomputed from a witness) without
+/// storing them; the member itself is typically a unit or `PhantomData` placeholder.
+///
+/// When `bcs` isn't the right encoding for a member, such as a curve point with a canonical
+/// compressed form, tag it with `#[inscribe(serialize_with = function)]` (or
+/// `serialize_with = "path::to::function"`). The function takes a reference to the member and
+/// returns `Result<Vec<u8>, Error>`, and its output is added to the TupleHash in place of the
+/// `bcs` serialization.
+///
+/// The `skip_if_empty` handling is meant for compatibility with older proof formats, where a
+/// collection member (such as a `Vec` or `String`) was added later: empty values give the same
+/// inscription as a struct without the member. Note that this means an empty member can't be
+/// distinguished from a missing one, so this shouldn't be used where that matters.
+///
+/// Enums can be derived as well. A variant is inscribed as a TupleHash, customized with the mark,
+/// over these items in order:
+///
+/// 1. the variant's discriminant, as a 4-byte little-endian `u32`;
+/// 2. the UTF-8 bytes of the variant's name;
+/// 3. the variant's members, under the same rules as struct members;
+/// 4. the additional data, as absorbed by `get_additional_into`.
+///
+/// By default the discriminant is the variant's declaration-order index and the name is its
+/// identifier, so unit variants are distinguished by their index and name alone.
+///
+/// Where an external specification fixes the variant tags, a variant can be tagged with
+/// `#[inscribe_discriminant(N)]` to hash `N` in place of its index. Variants without the attribute
+/// keep their index, and every variant's discriminant must be distinct. The hashed name can be
+/// replaced with `#[inscribe_name(...)]` on the variant, so that renaming the Rust identifier
+/// doesn't change the inscription; `#[inscribe_name("")]` hashes an empty name, leaving the
+/// discriminant as the only tag.
+///
+/// Tuple structs and tuple variants are supported too. Their members are inscribed in positional
+/// order, unless renamed with `inscribe_name`; renamed members are sorted by name after all
+/// positional members.
+///
+/// Members are sorted by their field names by default, and derived marks are the name of the
+/// type. A member can be given a different sort name with `#[inscribe_name(name)]`, and a
+/// derived type can take its mark from a method with `#[inscribe_mark(method)]`. Both attributes
+/// also accept a string literal, as in `#[inscribe_name("commitment A")]` or
+/// `#[inscribe_mark("pedersen-commitment v1")]`, for text that isn't a valid identifier; a
+/// literal mark is used as the mark directly.
+///
+/// To absorb members in declaration order instead of sorting them, e.g. to match an externally
+/// specified layout, tag the type with `#[inscribe(ordered)]`. Sort names are then ignored, and
+/// reordering the members' declarations changes the inscription.
+///
+/// Skipping a member leaves it out of the transcript, so a skip can state why with
+/// `#[inscribe(skip, reason = "...")]`. The reason doesn't change the inscription, but it is shown
+/// by `describe`. Tagging the type with `#[inscribe(deny_unjustified_skip)]` makes a skip without
+/// a reason a compile error:
+///
+/// ```compile_fail
+/// # use decree::Inscribe;
+/// #[derive(Inscribe)]
+/// #[inscribe(deny_unjustified_skip)]
+/// pub struct Commitment {
+///     #[inscribe(serialize)]
+///     value: u64,
+///     #[inscribe(skip)]
+///     cache: Vec<u8>,
+/// }
+/// ```
+///
+/// ```
+/// # use decree::Inscribe;
+/// #[derive(Inscribe)]
+/// #[inscribe(deny_unjustified_skip)]
+/// pub struct Commitment {
+///     #[inscribe(serialize)]
+///     value: u64,
+///     #[inscribe(skip, reason = "derived from value")]
+///     cache: Vec<u8>,
+/// }
+/// ```
+///
+/// By default, the inscription depends on members' values and their order, but not on their
+/// names, so two types whose members hold the same values in the same order can collide. Tagging
+/// the type with `#[inscribe(bind_names)]` absorbs each member's sort name (its field name,
+/// position, or `inscribe_name`) as a TupleHash item of its own, just ahead of the member's value.
+/// Members that add nothing to the TupleHash add no name either: skipped members, empty
+/// `skip_if_empty` members, and members whose `inscription_length` is zero, such as `PhantomData`.
 ///
 /// Examples:
 ///
 /// This following code should fail to compile, as the default behavior is to call
-/// `get_inscription` on `x` and `y`, even though the `i32` type doesn't implement the `Inscribe`
+/// `get_inscription` on `x` and `y`, even though the `f64` type doesn't implement the `Inscribe`
 /// trait.
 ///
 /// ```compile_fail
@@ -37,13 +176,14 @@ pub type InscribeBuffer = [u8; INSCRIBE_LENGTH];
 /// # use decree::inscribe::InscribeBuffer;
 /// #[derive(Inscribe)]
 /// pub struct Point {
-///     x: i32,
-///     y: i32,
+///     x: f64,
+///     y: f64,
 /// }
 /// ```
 ///
-/// On the other hand, if we annotate both `x` and `y` with `inscribe(serialize)`, the code will
-/// compile just fine, with both values being serialized using the `bcs` library.
+/// Integers, `bool`, strings, and byte strings implement `Inscribe` themselves, so they can be
+/// left unannotated. If we annotate both `x` and `y` with `inscribe(serialize)`, they will be
+/// serialized using the `bcs` library instead.
 ///
 /// ```
 /// # use decree::Inscribe;
@@ -71,6 +211,66 @@ pub type InscribeBuffer = [u8; INSCRIBE_LENGTH];
 /// }
 /// ```
 ///
+/// Additional data can also be supplied by `#[inscribe_addl_bytes(method)]`, where `method`
+/// returns `Result<&[u8], Error>`. The borrowed bytes are added directly to the TupleHash, which
+/// avoids an allocation when the additional data is already stored in the struct. The resulting
+/// inscription is the same as if `method` returned an owned copy via `inscribe_addl`.
+///
+/// A struct can list several `inscribe_addl` and `inscribe_addl_bytes` attributes. The bytes from
+/// each method are added to the TupleHash as separate items, after the members, in the order the
+/// attributes are written; reordering the attributes changes the inscription. In this case,
+/// `get_additional` returns the bytes from every method in the same order, each preceded by its
+/// length as an 8-byte little-endian integer, so that different splits of the same bytes can't be
+/// confused. Since the inscription absorbs each method's bytes as an item of its own, the result
+/// of `get_additional` is a summary of the additional data, not what the inscription absorbs;
+/// callers that need the latter should use `get_additional_into`.
+///
+/// ```
+/// # use decree::Inscribe;
+/// # use decree::error::Error;
+/// #[derive(Inscribe)]
+/// #[inscribe_addl_bytes(domain_bytes)]
+/// pub struct Tagged {
+///     #[inscribe(serialize)]
+///     value: u64,
+///     #[inscribe(skip)]
+///     domain: Vec<u8>,
+/// }
+///
+/// impl Tagged {
+///     fn domain_bytes(&self) -> Result<&[u8], Error> {
+///         Ok(self.domain.as_slice())
+///     }
+/// }
+/// ```
+///
+/// A computed member, whose inscription is that of the `Point` returned by `double`:
+///
+/// ```
+/// # use decree::Inscribe;
+/// # use decree::inscribe::InscribeBuffer;
+/// #[derive(Inscribe)]
+/// pub struct Point {
+///     #[inscribe(serialize)]
+///     x: i32,
+///     #[inscribe(serialize)]
+///     y: i32,
+/// }
+///
+/// #[derive(Inscribe)]
+/// pub struct Doubled {
+///     base: Point,
+///     #[inscribe(compute_with = double)]
+///     doubled: (),
+/// }
+///
+/// impl Doubled {
+///     fn double(&self) -> Point {
+///         Point { x: 2 * self.base.x, y: 2 * self.base.y }
+///     }
+/// }
+/// ```
+///
 /// # Tests
 ///
 /// ```
@@ -130,4 +330,804 @@ pub trait Inscribe {
         let x: Vec<u8> = Vec::new();
         Ok(x)
     }
-}
\ No newline at end of file
+
+    /// Absorbs the inscription into `hasher`, as a single item. Derived implementations compute
+    /// the inscription on the stack and stream it straight into `hasher`, and they and the
+    /// provided containers use this method to absorb their members and elements, so inscribing a
+    /// deeply nested struct doesn't allocate at every level. The default absorbs the result of
+    /// `get_inscription`, after checking that its length matches `inscription_length`.
+    fn get_inscription_into(&self, hasher: &mut dyn HashBackend) -> DecreeResult<()> {
+        let inscription = self.get_inscription()?;
+        if inscription.len() != self.inscription_length() {
+            return Err(Error::new_general("Invalid inscription length"));
+        }
+        hasher.update(inscription.as_slice());
+        Ok(())
+    }
+
+    /// Absorbs the additional data into `hasher`. Derived implementations call this rather than
+    /// `get_additional`, so that an implementation can stream borrowed bytes straight into the
+    /// hasher without building a `Vec`. The default absorbs the result of `get_additional` as a
+    /// single item; derived implementations with several additional data methods absorb each
+    /// method's bytes as an item of its own.
+    fn get_additional_into(&self, hasher: &mut dyn HashBackend) -> DecreeResult<()> {
+        let additional = self.get_additional()?;
+        hasher.update(additional.as_slice());
+        Ok(())
+    }
+
+    /// Returns the length, in bytes, of the value returned by `get_inscription`. Derived
+    /// implementations use the length given by `#[inscribe_length(N)]`, if any.
+    fn inscription_length(&self) -> usize {
+        INSCRIBE_LENGTH
+    }
+
+    /// Returns a stable, multi-line description of what goes into the inscription: the mark on
+    /// the first line, a `bind_names` line if member names are bound, then one line per
+    /// participating member (sort name, member name, and
+    /// handling) in the order they are added, then the additional data method, if any. Skipped
+    /// members are listed as `skip`, along with their reason if one was given, even though they
+    /// add nothing. Hand-written implementations only describe their mark by default.
+    #[cfg(feature = "describe")]
+    fn describe(&self) -> String {
+        format!("{}\n", self.get_mark())
+    }
+}
+
+
+/// The `Marked` wrapper adds a mark that is only known at runtime (such as a party index in an
+/// n-party protocol) to an `Inscribe` value. Since `get_mark` returns a `&'static str`, this is
+/// the way to domain-separate otherwise-identical values by a computed mark.
+///
+/// The inscription of a `Marked` value is a TupleHash (customized with a fixed mark) over the
+/// runtime mark, followed by the inscription of the inner value.
+///
+/// # Tests
+///
+/// Equal values with different runtime marks inscribe differently
+/// ```
+/// # use decree::Inscribe;
+/// # use decree::inscribe::Marked;
+/// # use decree::error::DecreeResult;
+/// #[derive(Inscribe)]
+/// pub struct Share {
+///     #[inscribe(serialize)]
+///     value: u64,
+/// }
+///
+/// # fn main() -> DecreeResult<()> {
+/// let party_a = format!("party {}", 1);
+/// let party_b = format!("party {}", 2);
+/// let share_a = Marked::new(party_a.as_bytes(), Share { value: 8675309 });
+/// let share_b = Marked::new(party_b.as_bytes(), Share { value: 8675309 });
+/// let share_c = Marked::new(party_a.as_bytes(), Share { value: 8675309 });
+/// assert_ne!(share_a.get_inscription()?, share_b.get_inscription()?);
+/// assert_eq!(share_a.get_inscription()?, share_c.get_inscription()?);
+///
+/// // The wrapper is distinct from the bare value
+/// assert_ne!(share_a.get_inscription()?, share_a.inner().get_inscription()?);
+/// # Ok(())
+/// # }
+/// ```
+pub struct Marked<T: Inscribe> {
+    mark: Vec<u8>,
+    inner: T,
+}
+
+impl<T: Inscribe> Marked<T> {
+    /// Wraps `inner` with the runtime mark `mark`.
+    pub fn new(mark: &[u8], inner: T) -> Marked<T> {
+        Marked {
+            mark: mark.to_vec(),
+            inner,
+        }
+    }
+
+    /// Returns the runtime mark.
+    pub fn mark(&self) -> &[u8] {
+        self.mark.as_slice()
+    }
+
+    /// Returns a reference to the wrapped value.
+    pub fn inner(&self) -> &T {
+        &self.inner
+    }
+
+    /// Unwraps the value.
+    pub fn into_inner(self) -> T {
+        self.inner
+    }
+}
+
+impl<T: Inscribe> Inscribe for Marked<T> {
+    fn get_mark(&self) -> &'static str {
+        MARKED_MARK
+    }
+
+    fn get_inscription(&self) -> DecreeResult<FSInput> {
+        inscribe_streamed(self.get_mark(), |hasher| {
+            hasher.update(self.mark.as_slice());
+            self.inner.get_inscription_into(hasher)
+        })
+    }
+}
+
+
+/// The `Serialized` wrapper inscribes a value that implements `Serialize` but not `Inscribe`,
+/// such as a timestamp or identifier type from another crate. The inscription is a TupleHash,
+/// customized with the mark `INSCRIBE_MARK_SERIALIZE`, over the `bcs` serialization of the inner
+/// value. Since it implements `Inscribe`, a `Serialized` member can use the default handling in a
+/// derived struct.
+///
+/// # Tests
+///
+/// ```
+/// # use decree::Inscribe;
+/// # use decree::inscribe::Serialized;
+/// # use decree::error::DecreeResult;
+/// #[derive(serde::Serialize)]
+/// pub struct SessionId(u64, u64);
+///
+/// #[derive(Inscribe)]
+/// pub struct Session {
+///     id: Serialized<SessionId>,
+///     round: u32,
+/// }
+///
+/// # fn main() -> DecreeResult<()> {
+/// let session_a = Session { id: Serialized(SessionId(1, 2)), round: 0 };
+/// let session_b = Session { id: Serialized(SessionId(2, 1)), round: 0 };
+/// assert_ne!(session_a.get_inscription()?, session_b.get_inscription()?);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Serialized<T: Serialize>(pub T);
+
+impl<T: Serialize> Inscribe for Serialized<T> {
+    fn get_mark(&self) -> &'static str {
+        INSCRIBE_MARK_SERIALIZE
+    }
+
+    fn get_inscription(&self) -> DecreeResult<FSInput> {
+        let serialized = bcs::to_bytes(&self.0)
+            .map_err(|e| Error::new_serialization(format!("Could not serialize value: {}", e)))?;
+        Ok(inscribe_items(self.get_mark(), &[serialized.as_slice()]))
+    }
+}
+
+
+/// Ranges are inscribed as a TupleHash over the inscriptions of `start` and `end`, so `a..b` and
+/// `b..a` inscribe differently.
+///
+/// # Tests
+///
+/// ```
+/// # use decree::Inscribe;
+/// # use decree::decree::{Decree, InputLabel, ChallengeLabel};
+/// # use decree::error::DecreeResult;
+/// #[derive(Inscribe)]
+/// pub struct Bound {
+///     #[inscribe(serialize)]
+///     value: u64,
+/// }
+///
+/// # fn main() -> DecreeResult<()> {
+/// let forward = Bound { value: 0 }..Bound { value: 1 << 32 };
+/// let backward = Bound { value: 1 << 32 }..Bound { value: 0 };
+/// assert_ne!(forward.get_inscription()?, backward.get_inscription()?);
+///
+/// let inputs: [InputLabel; 1] = ["range"];
+/// let challenges: [ChallengeLabel; 1] = ["challenge"];
+/// let mut transcript = Decree::new("range proof", &inputs, &challenges)?;
+/// transcript.add("range", &forward)?;
+/// let mut challenge_out: [u8; 32] = [0u8; 32];
+/// transcript.get_challenge("challenge", &mut challenge_out)?;
+/// # Ok(())
+/// # }
+/// ```
+impl<T: Inscribe> Inscribe for Range<T> {
+    fn get_mark(&self) -> &'static str {
+        RANGE_MARK
+    }
+
+    fn get_inscription(&self) -> DecreeResult<FSInput> {
+        inscribe_streamed(self.get_mark(), |hasher| {
+            self.start.get_inscription_into(hasher)?;
+            self.end.get_inscription_into(hasher)
+        })
+    }
+}
+
+
+/// Options are inscribed as a TupleHash over a single tag byte (`0` for `None`, `1` for `Some`),
+/// followed by the inscription of the inner value for `Some`. The tag keeps `None` distinct from
+/// any `Some` value, and the `option` mark keeps `Some(x)` distinct from `x`.
+///
+/// # Tests
+///
+/// ```
+/// # use decree::Inscribe;
+/// # use decree::error::DecreeResult;
+/// # fn main() -> DecreeResult<()> {
+/// let none: Option<String> = None;
+/// let empty: Option<String> = Some(String::new());
+/// assert_ne!(none.get_inscription()?, empty.get_inscription()?);
+///
+/// let value = String::from("value");
+/// let some = Some(value.clone());
+/// assert_ne!(some.get_inscription()?, value.get_inscription()?);
+/// assert_eq!(some.get_inscription()?, Some(value).get_inscription()?);
+/// # Ok(())
+/// # }
+/// ```
+impl<T: Inscribe> Inscribe for Option<T> {
+    fn get_mark(&self) -> &'static str {
+        OPTION_MARK
+    }
+
+    fn get_inscription(&self) -> DecreeResult<FSInput> {
+        inscribe_streamed(self.get_mark(), |hasher| match self {
+            None => {
+                hasher.update(&[0u8]);
+                Ok(())
+            },
+            Some(inner) => {
+                hasher.update(&[1u8]);
+                inner.get_inscription_into(hasher)
+            },
+        })
+    }
+}
+
+
+/// Vectors are inscribed as a TupleHash over the number of elements (as an 8-byte little-endian
+/// integer), followed by the inscription of each element in order. Binding the count up front
+/// means that a vector can't be confused with a longer or shorter one, or with a vector whose
+/// elements are split differently.
+///
+/// `Vec<u8>` is inscribed as a byte string instead; see the implementation above.
+///
+/// # Tests
+///
+/// ```
+/// # use decree::Inscribe;
+/// # use decree::error::DecreeResult;
+/// # fn main() -> DecreeResult<()> {
+/// let a = String::from("a");
+/// let b = String::from("b");
+/// let c = String::from("c");
+/// let ab = String::from("ab");
+///
+/// let pair = vec![a.clone(), b.clone()];
+/// let triple = vec![a.clone(), b.clone(), c];
+/// let joined = vec![ab];
+/// assert_ne!(pair.get_inscription()?, triple.get_inscription()?);
+/// assert_ne!(pair.get_inscription()?, joined.get_inscription()?);
+/// assert_eq!(pair.get_inscription()?, vec![a, b].get_inscription()?);
+///
+/// let empty: Vec<String> = Vec::new();
+/// assert_ne!(empty.get_inscription()?, pair.get_inscription()?);
+/// # Ok(())
+/// # }
+/// ```
+impl<T: Inscribe> Inscribe for Vec<T> {
+    fn get_mark(&self) -> &'static str {
+        VEC_MARK
+    }
+
+    fn get_inscription(&self) -> DecreeResult<FSInput> {
+        inscribe_sequence(self.iter())
+    }
+}
+
+
+/// Fixed-size arrays of `Inscribe` values, such as `[RistrettoPoint; 3]`, are inscribed as a
+/// TupleHash over the inscription of each element. Unlike a `Vec`, the length is fixed by the type,
+/// so no count is needed; the array mark keeps arrays separate from vectors with the same
+/// elements.
+///
+/// # Tests
+///
+/// ```
+/// # use decree::Inscribe;
+/// # use decree::error::DecreeResult;
+/// # fn main() -> DecreeResult<()> {
+/// let a = String::from("a");
+/// let b = String::from("b");
+///
+/// let array = [a.clone(), b.clone()];
+/// let vector = vec![a.clone(), b.clone()];
+/// assert_ne!(array.get_inscription()?, vector.get_inscription()?);
+/// assert_ne!(array.get_inscription()?, [b.clone(), a.clone()].get_inscription()?);
+/// assert_eq!(array.get_inscription()?, [a, b].get_inscription()?);
+/// # Ok(())
+/// # }
+/// ```
+impl<T: Inscribe, const N: usize> Inscribe for [T; N] {
+    fn get_mark(&self) -> &'static str {
+        ARRAY_MARK
+    }
+
+    fn get_inscription(&self) -> DecreeResult<FSInput> {
+        inscribe_streamed(self.get_mark(), |hasher| {
+            self.iter().try_for_each(|element| element.get_inscription_into(hasher))
+        })
+    }
+}
+
+
+/// `BTreeMap`s are inscribed as a TupleHash over the number of entries (as an 8-byte
+/// little-endian integer), followed by the inscriptions of each key and its value, in key order.
+///
+/// # Tests
+///
+/// ```
+/// # use decree::Inscribe;
+/// # use decree::error::DecreeResult;
+/// # use std::collections::BTreeMap;
+/// # fn main() -> DecreeResult<()> {
+/// let mut forward = BTreeMap::new();
+/// forward.insert(1u32, String::from("a"));
+/// forward.insert(2u32, String::from("b"));
+///
+/// let mut backward = BTreeMap::new();
+/// backward.insert(2u32, String::from("b"));
+/// backward.insert(1u32, String::from("a"));
+/// assert_eq!(forward.get_inscription()?, backward.get_inscription()?);
+///
+/// backward.insert(2u32, String::from("c"));
+/// assert_ne!(forward.get_inscription()?, backward.get_inscription()?);
+/// # Ok(())
+/// # }
+/// ```
+impl<K: Inscribe + Ord, V: Inscribe> Inscribe for BTreeMap<K, V> {
+    fn get_mark(&self) -> &'static str {
+        BTREE_MAP_MARK
+    }
+
+    fn get_inscription(&self) -> DecreeResult<FSInput> {
+        inscribe_streamed(self.get_mark(), |hasher| {
+            hasher.update(&(self.len() as u64).to_le_bytes());
+            for (key, value) in self.iter() {
+                key.get_inscription_into(hasher)?;
+                value.get_inscription_into(hasher)?;
+            }
+            Ok(())
+        })
+    }
+}
+
+
+/// `HashMap`s are inscribed like `BTreeMap`s, except that since their iteration order isn't
+/// deterministic (and their keys needn't be `Ord`), the entries are sorted by the inscriptions of
+/// their keys.
+///
+/// # Tests
+///
+/// ```
+/// # use decree::Inscribe;
+/// # use decree::error::DecreeResult;
+/// # use std::collections::HashMap;
+/// # fn main() -> DecreeResult<()> {
+/// let forward: HashMap<u32, u64> = (0u32..32).map(|i| (i, i as u64 * 3)).collect();
+/// let backward: HashMap<u32, u64> = (0u32..32).rev().map(|i| (i, i as u64 * 3)).collect();
+/// assert_eq!(forward.get_inscription()?, backward.get_inscription()?);
+/// # Ok(())
+/// # }
+/// ```
+impl<K: Inscribe, V: Inscribe, S: BuildHasher> Inscribe for HashMap<K, V, S> {
+    fn get_mark(&self) -> &'static str {
+        HASH_MAP_MARK
+    }
+
+    fn get_inscription(&self) -> DecreeResult<FSInput> {
+        // The inscriptions are only used to sort the entries; each entry is still absorbed with
+        // `get_inscription_into`, as in any other container
+        let mut entries = self.iter()
+            .map(|(key, value)| Ok((key.get_inscription()?, value.get_inscription()?, key, value)))
+            .collect::<DecreeResult<Vec<_>>>()?;
+        entries.sort_by(|(lhs_key, lhs_value, _, _), (rhs_key, rhs_value, _, _)| {
+            (lhs_key, lhs_value).cmp(&(rhs_key, rhs_value))
+        });
+
+        inscribe_streamed(self.get_mark(), |hasher| {
+            hasher.update(&(self.len() as u64).to_le_bytes());
+            for (_, _, key, value) in entries.iter() {
+                key.get_inscription_into(hasher)?;
+                value.get_inscription_into(hasher)?;
+            }
+            Ok(())
+        })
+    }
+}
+
+
+/// Tuples of up to eight `Inscribe` values are inscribed as a TupleHash over the inscription of
+/// each element in order. This allows heterogeneous values to be grouped ad hoc, as in
+/// `decree.add("proof", &(h, u, z))`, without a wrapper struct. Unlike a derived struct, a tuple's
+/// elements are positional, so swapping two elements changes the inscription.
+///
+/// # Tests
+///
+/// ```
+/// # use decree::Inscribe;
+/// # use decree::error::DecreeResult;
+/// # fn main() -> DecreeResult<()> {
+/// let a = String::from("a");
+/// let b = String::from("b");
+///
+/// let pair = (a.clone(), b.clone());
+/// assert_ne!(pair.get_inscription()?, (b.clone(), a.clone()).get_inscription()?);
+/// assert_ne!(pair.get_inscription()?, [a.clone(), b.clone()].get_inscription()?);
+/// assert_ne!(pair.get_inscription()?, (a.clone(), b.clone(), 0u32).get_inscription()?);
+/// assert_eq!(pair.get_inscription()?, (a, b).get_inscription()?);
+/// # Ok(())
+/// # }
+/// ```
+macro_rules! inscribe_tuple {
+    ($(($($element:ident),+)),*) => {
+        $(
+            impl<$($element: Inscribe),+> Inscribe for ($($element,)+) {
+                fn get_mark(&self) -> &'static str {
+                    TUPLE_MARK
+                }
+
+                #[allow(non_snake_case)]
+                fn get_inscription(&self) -> DecreeResult<FSInput> {
+                    let ($($element,)+) = self;
+                    inscribe_streamed(self.get_mark(), |hasher| {
+                        $($element.get_inscription_into(hasher)?;)+
+                        Ok(())
+                    })
+                }
+            }
+        )*
+    };
+}
+
+inscribe_tuple!(
+    (A, B),
+    (A, B, C),
+    (A, B, C, D),
+    (A, B, C, D, E),
+    (A, B, C, D, E, F),
+    (A, B, C, D, E, F, G),
+    (A, B, C, D, E, F, G, H)
+);
+
+
+/// With the `either` feature, `Either` values are inscribed as a TupleHash over a single tag byte
+/// (`0` for `Left`, `1` for `Right`) followed by the inscription of the inner value, so `Left(x)`
+/// and `Right(x)` inscribe differently even when both sides have the same type.
+#[cfg(feature = "either")]
+impl<L: Inscribe, R: Inscribe> Inscribe for either::Either<L, R> {
+    fn get_mark(&self) -> &'static str {
+        EITHER_MARK
+    }
+
+    fn get_inscription(&self) -> DecreeResult<FSInput> {
+        inscribe_streamed(self.get_mark(), |hasher| match self {
+            either::Either::Left(left) => {
+                hasher.update(&[0u8]);
+                left.get_inscription_into(hasher)
+            },
+            either::Either::Right(right) => {
+                hasher.update(&[1u8]);
+                right.get_inscription_into(hasher)
+            },
+        })
+    }
+}
+
+
+/// References and boxes are inscribed exactly like the value they point to, so boxed or borrowed
+/// members (e.g. in recursive protocol trees) can be derived with the default handling.
+///
+/// # Tests
+///
+/// ```
+/// # use decree::Inscribe;
+/// # use decree::error::DecreeResult;
+/// #[derive(Inscribe)]
+/// pub struct Node {
+///     value: u32,
+///     children: Vec<Box<Node>>,
+/// }
+/// # fn main() -> DecreeResult<()> {
+/// let leaf = Node { value: 2, children: vec![] };
+/// let tree = Node { value: 1, children: vec![Box::new(Node { value: 2, children: vec![] })] };
+/// assert_eq!(Box::new(&leaf).get_inscription()?, leaf.get_inscription()?);
+/// assert_ne!(tree.get_inscription()?, leaf.get_inscription()?);
+/// # Ok(())
+/// # }
+/// ```
+macro_rules! inscribe_pointer {
+    ($($pointer:ty),*) => {
+        $(
+            impl<T: Inscribe + ?Sized> Inscribe for $pointer {
+                fn get_mark(&self) -> &'static str {
+                    (**self).get_mark()
+                }
+
+                fn get_inscription(&self) -> DecreeResult<FSInput> {
+                    (**self).get_inscription()
+                }
+
+                fn get_additional(&self) -> DecreeResult<FSInput> {
+                    (**self).get_additional()
+                }
+
+                fn get_inscription_into(&self, hasher: &mut dyn HashBackend) -> DecreeResult<()> {
+                    (**self).get_inscription_into(hasher)
+                }
+
+                fn get_additional_into(&self, hasher: &mut dyn HashBackend) -> DecreeResult<()> {
+                    (**self).get_additional_into(hasher)
+                }
+
+                fn inscription_length(&self) -> usize {
+                    (**self).inscription_length()
+                }
+
+                #[cfg(feature = "describe")]
+                fn describe(&self) -> String {
+                    (**self).describe()
+                }
+            }
+        )*
+    };
+}
+
+inscribe_pointer!(&T, Box<T>);
+
+
+/// Integers are inscribed as a TupleHash over their fixed-width little-endian encoding, with the
+/// type name as the mark, so values of different widths never collide. `usize` and `isize` are
+/// encoded as 64-bit values so that inscriptions don't depend on the platform.
+///
+/// `u8` is deliberately left out, so that `Vec<u8>` and `[u8; N]` can be inscribed as byte
+/// strings; use `#[inscribe(serialize)]` for single-byte members.
+///
+/// # Tests
+///
+/// ```
+/// # use decree::Inscribe;
+/// # use decree::error::DecreeResult;
+/// # fn main() -> DecreeResult<()> {
+/// // Inscriptions are stable, and depend on the type as well as the value
+/// assert_eq!(42u32.get_inscription()?, 42u32.get_inscription()?);
+/// assert_ne!(42u32.get_inscription()?, 42u64.get_inscription()?);
+/// assert_ne!(42u32.get_inscription()?, 42i32.get_inscription()?);
+/// assert_ne!(42u32.get_inscription()?, 43u32.get_inscription()?);
+/// assert_eq!(42usize.get_inscription()?.len(), 64);
+///
+/// // Primitive members no longer need to be annotated
+/// #[derive(Inscribe)]
+/// pub struct Header {
+///     version: u16,
+///     length: u64,
+///     final_block: bool,
+///     name: String,
+/// }
+/// let header = Header { version: 1, length: 1024, final_block: true, name: "block".to_string() };
+/// header.get_inscription()?;
+/// # Ok(())
+/// # }
+/// ```
+macro_rules! inscribe_integer {
+    ($($int:ty => $wide:ty),*) => {
+        $(
+            impl Inscribe for $int {
+                fn get_mark(&self) -> &'static str {
+                    stringify!($int)
+                }
+
+                fn get_inscription(&self) -> DecreeResult<FSInput> {
+                    let encoded = (*self as $wide).to_le_bytes();
+                    Ok(inscribe_items(self.get_mark(), &[&encoded]))
+                }
+            }
+        )*
+    };
+}
+
+inscribe_integer!(
+    u16 => u16, u32 => u32, u64 => u64, u128 => u128, usize => u64,
+    i8 => i8, i16 => i16, i32 => i32, i64 => i64, i128 => i128, isize => i64
+);
+
+/// Booleans are inscribed as a single byte, `0` or `1`.
+impl Inscribe for bool {
+    fn get_mark(&self) -> &'static str {
+        BOOL_MARK
+    }
+
+    fn get_inscription(&self) -> DecreeResult<FSInput> {
+        Ok(inscribe_items(self.get_mark(), &[&[*self as u8]]))
+    }
+}
+
+/// `PhantomData` markers carry no data, so they contribute nothing: the inscription is empty, and
+/// a derived type or a container absorbs nothing for a `PhantomData` member or element. Marker
+/// members can therefore be left unannotated, and a type inscribes the same with or without them.
+impl<T: ?Sized> Inscribe for PhantomData<T> {
+    fn get_mark(&self) -> &'static str {
+        PHANTOM_DATA_MARK
+    }
+
+    fn get_inscription(&self) -> DecreeResult<FSInput> {
+        Ok(Vec::new())
+    }
+
+    fn get_inscription_into(&self, _hasher: &mut dyn HashBackend) -> DecreeResult<()> {
+        Ok(())
+    }
+
+    fn inscription_length(&self) -> usize {
+        0
+    }
+}
+
+/// Strings are inscribed as their UTF-8 bytes. `String` and `&str` inscribe identically.
+impl Inscribe for String {
+    fn get_mark(&self) -> &'static str {
+        STRING_MARK
+    }
+
+    fn get_inscription(&self) -> DecreeResult<FSInput> {
+        Ok(inscribe_items(self.get_mark(), &[self.as_bytes()]))
+    }
+}
+
+impl Inscribe for str {
+    fn get_mark(&self) -> &'static str {
+        STRING_MARK
+    }
+
+    fn get_inscription(&self) -> DecreeResult<FSInput> {
+        Ok(inscribe_items(self.get_mark(), &[self.as_bytes()]))
+    }
+}
+
+/// Byte slices, vectors, and arrays are inscribed as the raw bytes, with the mark
+/// `INSCRIBE_MARK_U8_SLICE`. `[u8]`, `Vec<u8>`, and `[u8; N]` with the same contents inscribe
+/// identically; since TupleHash encodes the length of each item, byte strings of different
+/// lengths never collide.
+///
+/// # Tests
+///
+/// ```
+/// # use decree::Inscribe;
+/// # use decree::error::DecreeResult;
+/// # fn main() -> DecreeResult<()> {
+/// assert_eq!(vec![1u8, 2u8, 3u8].get_inscription()?, [1u8, 2u8, 3u8].get_inscription()?);
+/// assert_ne!(vec![1u8, 2u8, 3u8].get_inscription()?, [1u8, 2u8].get_inscription()?);
+/// assert_ne!("abc".get_inscription()?, b"abc".get_inscription()?);
+/// assert_eq!([1u8, 2u8, 3u8][..].get_inscription()?, [1u8, 2u8, 3u8].get_inscription()?);
+/// # Ok(())
+/// # }
+/// ```
+impl Inscribe for [u8] {
+    fn get_mark(&self) -> &'static str {
+        INSCRIBE_MARK_U8_SLICE
+    }
+
+    fn get_inscription(&self) -> DecreeResult<FSInput> {
+        Ok(inscribe_items(self.get_mark(), &[self]))
+    }
+}
+
+impl Inscribe for Vec<u8> {
+    fn get_mark(&self) -> &'static str {
+        INSCRIBE_MARK_U8_SLICE
+    }
+
+    fn get_inscription(&self) -> DecreeResult<FSInput> {
+        Ok(inscribe_items(self.get_mark(), &[self.as_slice()]))
+    }
+}
+
+impl<const N: usize> Inscribe for [u8; N] {
+    fn get_mark(&self) -> &'static str {
+        INSCRIBE_MARK_U8_SLICE
+    }
+
+    fn get_inscription(&self) -> DecreeResult<FSInput> {
+        Ok(inscribe_items(self.get_mark(), &[self.as_slice()]))
+    }
+}
+
+
+/// Arbitrary-precision integers are inscribed in a fixed sign-magnitude form that doesn't depend
+/// on `num-bigint`'s internal digit size, which varies across versions and targets (unlike their
+/// `bcs` serialization). A `BigUint` is inscribed as its minimal big-endian bytes, with zero as a
+/// single zero byte. A `BigInt` adds a sign byte first, `1` for negative values and `0`
+/// otherwise, followed by the bytes of its magnitude.
+///
+/// # Tests
+///
+/// ```
+/// # use decree::Inscribe;
+/// # use decree::error::DecreeResult;
+/// # use num_bigint::{BigInt, BigUint, Sign};
+/// # fn main() -> DecreeResult<()> {
+/// let from_int = BigInt::from(-8675309i64);
+/// let from_digits = BigInt::from_slice(Sign::Minus, &[8675309u32]);
+/// assert_eq!(from_int.get_inscription()?, from_digits.get_inscription()?);
+/// assert_ne!(from_int.get_inscription()?, (-from_int.clone()).get_inscription()?);
+///
+/// // Signed and unsigned values are distinct types, and inscribe differently
+/// assert_ne!(BigInt::from(5u32).get_inscription()?, BigUint::from(5u32).get_inscription()?);
+/// # Ok(())
+/// # }
+/// ```
+impl Inscribe for BigUint {
+    fn get_mark(&self) -> &'static str {
+        BIG_UINT_MARK
+    }
+
+    fn get_inscription(&self) -> DecreeResult<FSInput> {
+        Ok(inscribe_items(self.get_mark(), &[&self.to_bytes_be()]))
+    }
+}
+
+impl Inscribe for BigInt {
+    fn get_mark(&self) -> &'static str {
+        BIG_INT_MARK
+    }
+
+    fn get_inscription(&self) -> DecreeResult<FSInput> {
+        let (sign, magnitude) = self.to_bytes_be();
+        let sign_byte = (sign == Sign::Minus) as u8;
+        Ok(inscribe_items(self.get_mark(), &[&[sign_byte], &magnitude]))
+    }
+}
+
+
+// Computes a TupleHash, customized with `mark`, over each of `items` in order. This is the common
+// core of the hand-written `Inscribe` implementations.
+fn inscribe_items(mark: &str, items: &[&[u8]]) -> FSInput {
+    let mut hasher = TupleHash::v256(mark.as_bytes());
+    for item in items {
+        hasher.update(item);
+    }
+
+    let mut hash_buf: InscribeBuffer = [0u8; INSCRIBE_LENGTH];
+    hasher.finalize(&mut hash_buf);
+    hash_buf.to_vec()
+}
+
+// Computes a TupleHash, customized with `mark`, over the items that `absorb` adds. Containers
+// use this to absorb their elements with `get_inscription_into`, as derived types absorb their
+// members, so that an element inscribes the same wherever it appears.
+fn inscribe_streamed(
+        mark: &str,
+        absorb: impl FnOnce(&mut dyn HashBackend) -> DecreeResult<()>) -> DecreeResult<FSInput> {
+    let mut hasher = crate::backend::TupleHash::new_with_domain(mark.as_bytes());
+    absorb(&mut hasher)?;
+
+    let mut hash_buf: InscribeBuffer = [0u8; INSCRIBE_LENGTH];
+    hasher.finalize_into(&mut hash_buf);
+    Ok(hash_buf.to_vec())
+}
+
+// Inscribes a sequence exactly as a `Vec` of the same elements would be, without collecting it.
+// The count comes first, so the iterator has to know its length up front; an iterator that
+// reports the wrong length is an error.
+pub(crate) fn inscribe_sequence<T: Inscribe>(elements: impl ExactSizeIterator<Item = T>) -> DecreeResult<FSInput> {
+    let count = elements.len();
+    inscribe_streamed(VEC_MARK, |hasher| {
+        hasher.update(&(count as u64).to_le_bytes());
+
+        let mut absorbed = 0usize;
+        for element in elements {
+            element.get_inscription_into(hasher)?;
+            absorbed += 1;
+        }
+        if absorbed != count {
+            return Err(Error::new_general("Iterator length mismatch"));
+        }
+        Ok(())
+    })
+}
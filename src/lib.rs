@@ -2,6 +2,11 @@
 pub mod inscribe;
 pub use inscribe_derive::Inscribe;
 pub use inscribe::Inscribe;
+pub mod backend;
 pub mod decree;
 pub use decree::Decree;
 pub mod error;
+pub mod challenge;
+pub mod message_log;
+pub mod proof;
+mod transcript;
@@ -0,0 +1,188 @@
+use tiny_keccak::{Hasher, TupleHash};
+use crate::decree::FSInput;
+use crate::error::{Error, DecreeResult};
+use crate::inscribe::{Inscribe, InscribeBuffer, INSCRIBE_LENGTH};
+
+const MESSAGE_LOG_MARK: &str = "decree_message_log";
+
+/// The sender of a message in a two-party channel. Directions are named from the point of view
+/// of the protocol rather than either party, so that both ends of the channel log the same
+/// message in the same way.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Direction {
+    FromInitiator,
+    FromResponder,
+}
+
+impl Direction {
+    fn tag(&self) -> u8 {
+        match self {
+            Direction::FromInitiator => 0u8,
+            Direction::FromResponder => 1u8,
+        }
+    }
+}
+
+/// A `MessageLog` binds an ordered sequence of channel messages into a single digest that can be
+/// added to a `Decree` transcript via `add`. This is useful for channel binding in authenticated
+/// key exchange and similar protocols, where a proof must be tied to everything that was said on
+/// the channel up to that point.
+///
+/// Each message is absorbed into a TupleHash as three separate items: a single direction byte
+/// (`0` for the initiator, `1` for the responder), the sequence number as an 8-byte little-endian
+/// integer, and the message bytes. Since TupleHash frames each item, reordering messages,
+/// swapping their directions, or moving bytes between adjacent messages all change the digest.
+/// When the digest is computed, the total number of messages is absorbed as a final 8-byte
+/// little-endian item.
+///
+/// Sequence numbers must be strictly increasing for each direction; they need not be contiguous.
+///
+/// # Examples
+/// ```
+/// # use decree::decree::{Decree, InputLabel, ChallengeLabel};
+/// # use decree::error::DecreeResult;
+/// # use decree::message_log::{Direction, MessageLog};
+/// # fn main() -> DecreeResult<()> {
+/// let mut log = MessageLog::new();
+/// log.append(Direction::FromInitiator, 0, b"client hello")?;
+/// log.append(Direction::FromResponder, 0, b"server hello")?;
+/// log.append(Direction::FromInitiator, 1, b"client finished")?;
+///
+/// let inputs: [InputLabel; 1] = ["channel"];
+/// let challenges: [ChallengeLabel; 1] = ["binding"];
+/// let mut transcript = Decree::new("channel binding", &inputs, &challenges)?;
+/// transcript.add("channel", &log)?;
+/// let mut challenge_out: [u8; 32] = [0u8; 32];
+/// transcript.get_challenge("binding", &mut challenge_out)?;
+/// # Ok(())
+/// # }
+/// ```
+///
+/// # Tests
+///
+/// Swapping the order of two messages changes the digest
+/// ```
+/// # use decree::Inscribe;
+/// # use decree::error::DecreeResult;
+/// # use decree::message_log::{Direction, MessageLog};
+/// # fn main() -> DecreeResult<()> {
+/// let mut log_a = MessageLog::new();
+/// log_a.append(Direction::FromInitiator, 0, b"first")?;
+/// log_a.append(Direction::FromResponder, 0, b"second")?;
+///
+/// let mut log_b = MessageLog::new();
+/// log_b.append(Direction::FromResponder, 0, b"second")?;
+/// log_b.append(Direction::FromInitiator, 0, b"first")?;
+///
+/// assert_ne!(log_a.get_inscription()?, log_b.get_inscription()?);
+/// # Ok(())
+/// # }
+/// ```
+///
+/// Swapping the direction of a message changes the digest
+/// ```
+/// # use decree::Inscribe;
+/// # use decree::error::DecreeResult;
+/// # use decree::message_log::{Direction, MessageLog};
+/// # fn main() -> DecreeResult<()> {
+/// let mut log_a = MessageLog::new();
+/// log_a.append(Direction::FromInitiator, 0, b"hello")?;
+///
+/// let mut log_b = MessageLog::new();
+/// log_b.append(Direction::FromResponder, 0, b"hello")?;
+///
+/// assert_ne!(log_a.get_inscription()?, log_b.get_inscription()?);
+/// # Ok(())
+/// # }
+/// ```
+///
+/// Sequence numbers may not be repeated in the same direction
+/// ```should_panic
+/// # use decree::error::DecreeResult;
+/// # use decree::message_log::{Direction, MessageLog};
+/// # fn main() -> DecreeResult<()> {
+/// let mut log = MessageLog::new();
+/// log.append(Direction::FromInitiator, 3, b"hello")?;
+/// log.append(Direction::FromInitiator, 3, b"hello again")?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct MessageLog {
+    hasher: TupleHash,
+    last_initiator_seq: Option<u64>,
+    last_responder_seq: Option<u64>,
+    message_count: usize,
+}
+
+impl MessageLog {
+    /// Creates a new, empty `MessageLog`.
+    pub fn new() -> MessageLog {
+        MessageLog {
+            hasher: TupleHash::v256(MESSAGE_LOG_MARK.as_bytes()),
+            last_initiator_seq: None,
+            last_responder_seq: None,
+            message_count: 0,
+        }
+    }
+
+    /// Absorbs a message into the log. This will fail if `seq` is not greater than the last
+    /// sequence number logged for the same direction.
+    pub fn append(
+            &mut self,
+            direction: Direction,
+            seq: u64,
+            message: &[u8]) -> DecreeResult<()> {
+        let last_seq = match direction {
+            Direction::FromInitiator => &mut self.last_initiator_seq,
+            Direction::FromResponder => &mut self.last_responder_seq,
+        };
+
+        // Messages must be logged in the order they were sent
+        if let Some(last) = *last_seq {
+            if seq <= last {
+                return Err(Error::new_general("Message sequence numbers must increase"));
+            }
+        }
+        *last_seq = Some(seq);
+
+        self.hasher.update(&[direction.tag()]);
+        self.hasher.update(&seq.to_le_bytes());
+        self.hasher.update(message);
+        self.message_count += 1;
+
+        Ok(())
+    }
+
+    /// Returns the number of messages absorbed so far.
+    pub fn len(&self) -> usize {
+        self.message_count
+    }
+
+    /// Returns `true` if no messages have been absorbed.
+    pub fn is_empty(&self) -> bool {
+        self.message_count == 0
+    }
+}
+
+impl Default for MessageLog {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Inscribe for MessageLog {
+    fn get_mark(&self) -> &'static str {
+        MESSAGE_LOG_MARK
+    }
+
+    fn get_inscription(&self) -> DecreeResult<FSInput> {
+        // Finalize a copy, so that the log can keep accepting messages
+        let mut hasher = self.hasher.clone();
+        hasher.update(&(self.message_count as u64).to_le_bytes());
+
+        let mut hash_buf: InscribeBuffer = [0u8; INSCRIBE_LENGTH];
+        hasher.finalize(&mut hash_buf);
+        Ok(hash_buf.to_vec())
+    }
+}
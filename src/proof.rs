@@ -0,0 +1,38 @@
+use serde::{Deserialize, Serialize};
+
+/// A `Proof` bundles the challenges generated by a prover's `Decree` with their labels, in the
+/// order they were generated, so that they can be shipped to a verifier. It is created by
+/// `Decree::into_proof` and checked with `Decree::verify_challenges`.
+///
+/// Labels are stored as owned strings so that a `Proof` can be deserialized.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Proof {
+    name: String,
+    challenges: Vec<(String, Vec<u8>)>,
+}
+
+impl Proof {
+    pub(crate) fn new(name: &str, challenges: Vec<(String, Vec<u8>)>) -> Proof {
+        Proof {
+            name: name.to_string(),
+            challenges,
+        }
+    }
+
+    /// Returns the name of the transcript that generated the challenges.
+    pub fn name(&self) -> &str {
+        self.name.as_str()
+    }
+
+    /// Returns the labeled challenges, in the order they were generated.
+    pub fn challenges(&self) -> &[(String, Vec<u8>)] {
+        self.challenges.as_slice()
+    }
+
+    /// Returns the bytes of the first challenge with the given label, if there is one.
+    pub fn challenge(&self, label: &str) -> Option<&[u8]> {
+        self.challenges.iter()
+            .find(|(challenge_label, _)| challenge_label == label)
+            .map(|(_, bytes)| bytes.as_slice())
+    }
+}
@@ -0,0 +1,130 @@
+use merlin::{Transcript, TranscriptRngBuilder};
+use tiny_keccak::{Hasher, Sha3};
+use crate::decree::{TraceEntry, TraceKind};
+#[cfg(feature = "serde")]
+use std::collections::BTreeSet;
+#[cfg(feature = "serde")]
+use std::sync::Mutex;
+
+// A Merlin transcript, along with the bookkeeping `Decree` needs about it: the number of bytes
+// absorbed, an optional trace, and (with the `serde` feature) the lengths of the challenges
+// squeezed from it, so that they can be recorded for replay.
+#[derive(Clone)]
+pub(crate) struct LoggedTranscript {
+    transcript: Transcript,
+    // The lengths of the challenges squeezed since they were last taken
+    #[cfg(feature = "serde")]
+    squeezed: Vec<usize>,
+    // `None` unless tracing has been enabled
+    trace: Option<Vec<TraceEntry>>,
+    // The total length of the messages appended so far (not counting labels)
+    absorbed: usize,
+}
+
+impl LoggedTranscript {
+    pub(crate) fn new(name: &'static [u8]) -> LoggedTranscript {
+        LoggedTranscript {
+            transcript: Transcript::new(name),
+            #[cfg(feature = "serde")]
+            squeezed: Vec::new(),
+            trace: None,
+            absorbed: 0,
+        }
+    }
+
+    pub(crate) fn from_transcript(transcript: Transcript) -> LoggedTranscript {
+        LoggedTranscript {
+            transcript,
+            #[cfg(feature = "serde")]
+            squeezed: Vec::new(),
+            trace: None,
+            absorbed: 0,
+        }
+    }
+
+    pub(crate) fn append_message(&mut self, label: &'static [u8], message: &[u8]) {
+        self.transcript.append_message(label, message);
+        self.absorbed += message.len();
+        self.record(TraceKind::Append, label, message);
+    }
+
+    pub(crate) fn challenge_bytes(&mut self, label: &'static [u8], dest: &mut [u8]) {
+        self.transcript.challenge_bytes(label, dest);
+        self.record(TraceKind::Challenge, label, dest);
+        #[cfg(feature = "serde")]
+        self.squeezed.push(dest.len());
+    }
+
+    pub(crate) fn build_rng(&self) -> TranscriptRngBuilder {
+        self.transcript.build_rng()
+    }
+
+    pub(crate) fn absorbed(&self) -> usize {
+        self.absorbed
+    }
+
+    pub(crate) fn enable_trace(&mut self) {
+        if self.trace.is_none() {
+            self.trace = Some(Vec::new());
+        }
+    }
+
+    pub(crate) fn trace(&self) -> &[TraceEntry] {
+        self.trace.as_deref().unwrap_or(&[])
+    }
+
+    fn record(&mut self, kind: TraceKind, label: &[u8], value: &[u8]) {
+        if let Some(trace) = self.trace.as_mut() {
+            let mut digest = [0u8; 32];
+            let mut hasher = Sha3::v256();
+            hasher.update(value);
+            hasher.finalize(&mut digest);
+            trace.push(TraceEntry {
+                kind,
+                label: String::from_utf8_lossy(label).into_owned(),
+                length: value.len(),
+                digest,
+            });
+        }
+    }
+
+    // Returns the lengths of the challenges squeezed since the last call.
+    #[cfg(feature = "serde")]
+    pub(crate) fn take_squeezed(&mut self) -> Vec<usize> {
+        std::mem::take(&mut self.squeezed)
+    }
+}
+
+// The most label bytes that `intern` will allocate over the life of the process. Each distinct
+// label is only allocated once, so this is never reached by a fixed protocol, but it stops
+// untrusted serialized state from leaking memory without bound.
+#[cfg(feature = "serde")]
+const MAX_INTERNED_BYTES: usize = 1 << 16;
+
+// Merlin labels must be `&'static`, so labels read back from a serialized `Decree` are interned.
+// Each distinct label is allocated once for the life of the process; `None` is returned once
+// `MAX_INTERNED_BYTES` would be exceeded.
+#[cfg(feature = "serde")]
+pub(crate) fn intern(label: &[u8]) -> Option<&'static [u8]> {
+    static INTERNED: Mutex<(BTreeSet<&'static [u8]>, usize)> = Mutex::new((BTreeSet::new(), 0));
+
+    let mut guard = INTERNED.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    let (interned, total) = &mut *guard;
+    if let Some(existing) = interned.get(label) {
+        return Some(existing);
+    }
+    if total.checked_add(label.len()).is_none_or(|new_total| new_total > MAX_INTERNED_BYTES) {
+        return None;
+    }
+    *total += label.len();
+    let leaked: &'static [u8] = Box::leak(label.to_vec().into_boxed_slice());
+    interned.insert(leaked);
+    Some(leaked)
+}
+
+// Interns a label that was originally a `&'static str`.
+#[cfg(feature = "serde")]
+pub(crate) fn intern_str(label: &str) -> Option<&'static str> {
+    intern(label.as_bytes())
+        .map(|interned| std::str::from_utf8(interned).expect("Interned bytes come from a str"))
+}
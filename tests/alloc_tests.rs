@@ -0,0 +1,147 @@
+//! Allocation benchmarks. These live in their own test binary, since they install a counting
+//! global allocator.
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use decree::Inscribe;
+use decree::decree::FSInput;
+use decree::error::Error;
+
+struct CountingAllocator;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::SeqCst);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+// Counts the allocations made by a closure. The count is global, so counting is serialized
+// across tests.
+fn count_allocations<F: FnOnce() -> R, R>(f: F) -> (usize, R) {
+    static COUNTING: Mutex<()> = Mutex::new(());
+    let _guard = COUNTING.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    let before = ALLOCATIONS.load(Ordering::SeqCst);
+    let result = f();
+    let after = ALLOCATIONS.load(Ordering::SeqCst);
+    (after - before, result)
+}
+
+const TAG_LENGTH: usize = 4096;
+
+#[derive(Inscribe)]
+#[inscribe_mark(tagged_mark)]
+#[inscribe_addl(owned_tag)]
+struct OwnedTag {
+    #[inscribe(skip)]
+    tag: Vec<u8>,
+}
+
+impl OwnedTag {
+    fn tagged_mark(&self) -> &'static str {
+        "Tagged"
+    }
+
+    fn owned_tag(&self) -> Result<FSInput, Error> {
+        Ok(self.tag.clone())
+    }
+}
+
+#[derive(Inscribe)]
+#[inscribe_mark(tagged_mark)]
+#[inscribe_addl_bytes(borrowed_tag)]
+struct BorrowedTag {
+    #[inscribe(skip)]
+    tag: Vec<u8>,
+}
+
+impl BorrowedTag {
+    fn tagged_mark(&self) -> &'static str {
+        "Tagged"
+    }
+
+    fn borrowed_tag(&self) -> Result<&[u8], Error> {
+        Ok(self.tag.as_slice())
+    }
+}
+
+#[test]
+/// Borrowed additional data should save exactly the copy of the additional data
+fn bench_addl_bytes_allocations() {
+    let owned = OwnedTag { tag: vec![0x5au8; TAG_LENGTH] };
+    let borrowed = BorrowedTag { tag: vec![0x5au8; TAG_LENGTH] };
+
+    let (owned_allocs, owned_inscription) = count_allocations(|| owned.get_inscription().unwrap());
+    let (borrowed_allocs, borrowed_inscription) =
+        count_allocations(|| borrowed.get_inscription().unwrap());
+
+    assert_eq!(owned_inscription, borrowed_inscription);
+    assert_eq!(owned_allocs, borrowed_allocs + 1);
+}
+
+#[derive(Inscribe)]
+struct Level0 {
+    #[inscribe(bytes)]
+    value: [u8; 8],
+}
+
+#[derive(Inscribe)]
+struct Level1 {
+    inner: Level0,
+}
+
+#[derive(Inscribe)]
+struct Level2 {
+    inner: Level1,
+}
+
+#[derive(Inscribe)]
+struct Level3 {
+    inner: Level2,
+}
+
+#[derive(Inscribe)]
+struct Level4 {
+    inner: Level3,
+}
+
+// Inscribes a level the way derived implementations did before streaming: each child's
+// inscription is returned as a `Vec` and then absorbed.
+fn inscribe_allocating(mark: &str, child: &[u8]) -> FSInput {
+    use tiny_keccak::{Hasher, TupleHash};
+    let mut hasher = TupleHash::v256(mark.as_bytes());
+    hasher.update(child);
+    hasher.update(&[]);
+    let mut buffer = [0u8; 64];
+    hasher.finalize(&mut buffer);
+    buffer.to_vec()
+}
+
+#[test]
+/// Nested derived structs should stream their members' inscriptions, so inscribing a five-level
+/// struct allocates only the returned inscription, with the same result as allocating each level
+fn bench_nested_inscription_allocations() {
+    let nested = Level4 { inner: Level3 { inner: Level2 { inner: Level1 { inner: Level0 { value: [7u8; 8] } } } } };
+
+    let (streamed_allocs, streamed) = count_allocations(|| nested.get_inscription().unwrap());
+    let (allocating_allocs, allocating) = count_allocations(|| {
+        let mut inscription = nested.inner.inner.inner.inner.get_inscription().unwrap();
+        for mark in ["Level1", "Level2", "Level3", "Level4"] {
+            inscription = inscribe_allocating(mark, &inscription);
+        }
+        inscription
+    });
+
+    assert_eq!(streamed, allocating);
+    assert_eq!(streamed_allocs, 1);
+    assert_eq!(allocating_allocs, 5);
+}
@@ -0,0 +1,961 @@
+#[cfg(test)]
+mod tests {
+    use decree::decree::{Decree, DecreeLimits, InputLabel, ChallengeLabel, Serial};
+    use decree::error::{DecreeErrType, DecreeResult, Error};
+
+    #[test]
+    fn test_add_bytes_round_trip() -> DecreeResult<()> {
+        let inputs: [InputLabel; 2] = ["input1", "input2"];
+        let challenges: [ChallengeLabel; 1] = ["challenge1"];
+        let buf: [u8; 16] = [0xa5u8; 16];
+
+        let mut prover = Decree::new("testname", &inputs, &challenges)?;
+        prover.add_bytes("input1", &buf)?;
+        prover.add_serial("input2", 42u32)?;
+        let mut prover_challenge: [u8; 32] = [0u8; 32];
+        prover.get_challenge("challenge1", &mut prover_challenge)?;
+
+        let mut verifier = Decree::new("testname", &inputs, &challenges)?;
+        verifier.add_bytes("input1", &buf)?;
+        verifier.add_serial("input2", 42u32)?;
+        let mut verifier_challenge: [u8; 32] = [0u8; 32];
+        verifier.get_challenge("challenge1", &mut verifier_challenge)?;
+
+        assert_eq!(prover_challenge, verifier_challenge);
+        Ok(())
+    }
+
+    #[test]
+    fn test_add_bytes_is_not_serialized() -> DecreeResult<()> {
+        let inputs: [InputLabel; 1] = ["input1"];
+        let challenges: [ChallengeLabel; 1] = ["challenge1"];
+        let buf: Vec<u8> = vec![1u8, 2u8, 3u8, 4u8];
+
+        let mut raw = Decree::new("testname", &inputs, &challenges)?;
+        raw.add_bytes("input1", &buf)?;
+        let mut raw_challenge: [u8; 32] = [0u8; 32];
+        raw.get_challenge("challenge1", &mut raw_challenge)?;
+
+        let mut serial = Decree::new("testname", &inputs, &challenges)?;
+        serial.add_serial("input1", &buf)?;
+        let mut serial_challenge: [u8; 32] = [0u8; 32];
+        serial.get_challenge("challenge1", &mut serial_challenge)?;
+
+        assert_ne!(raw_challenge, serial_challenge);
+        Ok(())
+    }
+
+    #[test]
+    fn test_get_challenge_vec() -> DecreeResult<()> {
+        let inputs: [InputLabel; 1] = ["input1"];
+        let challenges: [ChallengeLabel; 1] = ["challenge1"];
+
+        let mut vec_decree = Decree::new("testname", &inputs, &challenges)?;
+        vec_decree.add_serial("input1", 10u32)?;
+        let challenge_vec = vec_decree.get_challenge_vec("challenge1", 200)?;
+        assert_eq!(challenge_vec.len(), 200);
+
+        // Same length through the existing method gives the same bytes
+        let mut buf_decree = Decree::new("testname", &inputs, &challenges)?;
+        buf_decree.add_serial("input1", 10u32)?;
+        let mut challenge_buf: [u8; 200] = [0u8; 200];
+        buf_decree.get_challenge("challenge1", &mut challenge_buf)?;
+        assert_eq!(challenge_vec, challenge_buf.to_vec());
+
+        // Merlin binds the output length, so a longer buffer is not an extension
+        let mut long_decree = Decree::new("testname", &inputs, &challenges)?;
+        long_decree.add_serial("input1", 10u32)?;
+        let mut long_buf: [u8; 256] = [0u8; 256];
+        long_decree.get_challenge("challenge1", &mut long_buf)?;
+        assert_ne!(challenge_vec, long_buf[..200].to_vec());
+        Ok(())
+    }
+
+    #[test]
+    fn test_boxed_error() {
+        fn make_decree() -> Result<Decree, Box<dyn std::error::Error>> {
+            let inputs: [InputLabel; 2] = ["input1", "input1"];
+            let challenges: [ChallengeLabel; 1] = ["challenge1"];
+            Ok(Decree::new("testname", &inputs, &challenges)?)
+        }
+
+        let err = make_decree().err().unwrap();
+        assert!(format!("{}", err).starts_with("Initialization failure: "));
+    }
+
+    #[test]
+    /// Verifying many proofs with one reset `Decree` matches building a fresh one for each
+    fn test_reset_many_proofs() -> DecreeResult<()> {
+        use std::time::Instant;
+        let inputs: [InputLabel; 2] = ["input1", "input2"];
+        let challenges: [ChallengeLabel; 2] = ["challenge1", "challenge2"];
+        let proofs = 1000u32;
+
+        let mut fresh_outputs = Vec::new();
+        let start = Instant::now();
+        for i in 0..proofs {
+            let mut decree = Decree::new("testname", &inputs, &challenges)?;
+            decree.add_serial("input1", i)?;
+            decree.add_serial("input2", i + 1)?;
+            let first = decree.get_challenge_vec("challenge1", 32)?;
+            let second = decree.get_challenge_vec("challenge2", 16)?;
+            fresh_outputs.push((first, second));
+        }
+        let fresh_time = start.elapsed();
+
+        let mut reused_outputs = Vec::new();
+        let mut decree = Decree::new("testname", &inputs, &challenges)?;
+        let start = Instant::now();
+        for i in 0..proofs {
+            decree.reset();
+            decree.add_serial("input2", i + 1)?;
+            decree.add_serial("input1", i)?;
+            let first = decree.get_challenge_vec("challenge1", 32)?;
+            let second = decree.get_challenge_vec("challenge2", 16)?;
+            reused_outputs.push((first, second));
+        }
+        let reused_time = start.elapsed();
+
+        assert_eq!(fresh_outputs, reused_outputs);
+        println!("{} proofs: fresh {:?}, reset {:?}", proofs, fresh_time, reused_time);
+        Ok(())
+    }
+
+    #[test]
+    /// `extend` reports the challenges that would be dropped, and `is_extendable` predicts it
+    fn test_extend_with_unread_challenges() -> DecreeResult<()> {
+        let inputs: [InputLabel; 1] = ["input1"];
+        let challenges: [ChallengeLabel; 3] = ["challenge1", "challenge2", "challenge3"];
+        let mut my_decree = Decree::new("testname", &inputs, &challenges)?;
+        my_decree.add_serial("input1", 10u32)?;
+        let _: [u8; 32] = my_decree.challenge("challenge1")?;
+
+        assert!(!my_decree.is_extendable());
+        let e = my_decree.extend(&inputs, &challenges).unwrap_err();
+        assert_eq!(e.get_type(), DecreeErrType::ExtendFail);
+        assert_eq!(e, "Cannot extend Decree with 2 unread challenge(s): challenge2, challenge3");
+
+        let _: [u8; 32] = my_decree.challenge("challenge2")?;
+        assert!(!my_decree.is_extendable());
+        let _: [u8; 32] = my_decree.challenge("challenge3")?;
+        assert!(my_decree.is_extendable());
+        my_decree.extend(&inputs, &challenges)?;
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    /// A serialized `Decree` round-trips across stages, strict modes, and owned labels
+    fn test_serde_round_trip() -> DecreeResult<()> {
+        let inputs: [InputLabel; 2] = ["input1", "input2"];
+        let challenges: [ChallengeLabel; 2] = ["challenge1", "challenge2"];
+        let round_inputs: Vec<String> = (0..2).map(|i| format!("round_{i}")).collect();
+        let round_challenges = vec![String::from("final")];
+
+        let mut original = Decree::with_strict_challenges("testname", &inputs, &challenges)?;
+        original.set_challenge_caching(true);
+        original.add_serial("input1", 10u32)?;
+        original.add_serial("input2", 14u32)?;
+        let _: [u8; 32] = original.challenge("challenge1")?;
+
+        // Save mid-stage, with one challenge outstanding
+        let mut restored: Decree = bcs::from_bytes(&bcs::to_bytes(&original).unwrap()).unwrap();
+        for decree in [&mut original, &mut restored] {
+            let _: [u8; 16] = decree.challenge("challenge2")?;
+            decree.extend_owned(&round_inputs, &round_challenges)?;
+            decree.add_serial("round_0", 1u32)?;
+        }
+
+        // Save again, with an input outstanding in an owned-label stage
+        let mut restored: Decree = bcs::from_bytes(&bcs::to_bytes(&restored).unwrap()).unwrap();
+        original.add_serial("round_1", 2u32)?;
+        restored.add_serial("round_1", 2u32)?;
+        let original_out: [u8; 32] = original.challenge("final")?;
+        let restored_out: [u8; 32] = restored.challenge("final")?;
+        assert_eq!(original_out, restored_out);
+
+        // Cached challenges and the verification starting point survive too
+        let cached: [u8; 32] = restored.challenge("final")?;
+        assert_eq!(cached, original_out);
+        let mut original_verifier = original.clone_for_verification();
+        let mut restored_verifier = restored.clone_for_verification();
+        original_verifier.add_serial("input1", 10u32)?;
+        original_verifier.add_serial("input2", 14u32)?;
+        restored_verifier.add_serial("input1", 10u32)?;
+        restored_verifier.add_serial("input2", 14u32)?;
+        assert_eq!(original_verifier.challenge::<32>("challenge1")?,
+                   restored_verifier.challenge::<32>("challenge1")?);
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    /// Every way of building a transcript is replayed on deserialization: versions, optional and
+    /// kept inputs, raw messages, domain-separated and rejection-sampled challenges
+    fn test_serde_replays_every_path() -> DecreeResult<()> {
+        use num_bigint::BigUint;
+
+        let inputs: [InputLabel; 1] = ["input1"];
+        let optional: [InputLabel; 1] = ["extra"];
+        let challenges: [ChallengeLabel; 2] = ["challenge1", "challenge2"];
+        let round_inputs: [InputLabel; 1] = ["round"];
+        let round_challenges: [ChallengeLabel; 1] = ["final"];
+        let modulus = BigUint::from(1_000_003u32);
+
+        let mut original = Decree::with_version("testname", 3, &inputs, &challenges)?;
+        original.append_raw_message(b"raw", b"message")?;
+        original.add_serial("input1", 10u32)?;
+        original.get_challenge_domained("challenge1", b"domain", &mut [0u8; 16])?;
+        original.get_challenge_mod("challenge2", &modulus)?;
+        original.extend_keeping(&round_inputs, &round_challenges, &inputs)?;
+
+        let mut restored: Decree = bcs::from_bytes(&bcs::to_bytes(&original).unwrap()).unwrap();
+        restored.check_invariants()?;
+        for decree in [&mut original, &mut restored] {
+            decree.add_serial("round", 2u32)?;
+        }
+        assert_eq!(original.challenge::<32>("final")?, restored.challenge::<32>("final")?);
+
+        let mut original = Decree::with_optional_inputs("testname", &inputs, &optional, &challenges)?;
+        original.add_serial("input1", 10u32)?;
+        let _: [u8; 32] = original.challenge("challenge1")?;
+        let mut restored: Decree = bcs::from_bytes(&bcs::to_bytes(&original).unwrap()).unwrap();
+        assert_eq!(original.challenge::<32>("challenge2")?, restored.challenge::<32>("challenge2")?);
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    /// The challenge cache is rebuilt from the replayed transcript, and a cache entry for a
+    /// challenge that wasn't drawn is rejected
+    fn test_serde_challenge_cache() -> DecreeResult<()> {
+        let inputs: [InputLabel; 1] = ["input1"];
+        let challenges: [ChallengeLabel; 2] = ["challenge1", "challenge2"];
+        let mut original = Decree::new("testname", &inputs, &challenges)?;
+        original.set_challenge_caching(true);
+        original.add_serial("input1", 10u32)?;
+        let challenge1: [u8; 32] = original.challenge("challenge1")?;
+        let saved = bcs::to_bytes(&original).unwrap();
+
+        let mut restored: Decree = bcs::from_bytes(&saved).unwrap();
+        assert_eq!(restored.challenge::<32>("challenge1")?, challenge1);
+
+        // The cache is serialized last, so the last occurrence of the label is its entry
+        let label = b"challenge1";
+        let start = saved.windows(label.len()).rposition(|window| window == label).unwrap();
+        let mut tampered = saved.clone();
+        tampered[start..start + label.len()].copy_from_slice(b"challenge2");
+        let err = bcs::from_bytes::<Decree>(&tampered).unwrap_err();
+        assert!(err.to_string().contains("Cached challenge was not generated"), "{err}");
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    /// Restoring a checkpoint of an unrelated `Decree` makes a `Decree` unserializable, since its
+    /// transcript no longer follows from its own history; checkpoints of clones are fine
+    fn test_serde_foreign_checkpoint() -> DecreeResult<()> {
+        let inputs: [InputLabel; 1] = ["input1"];
+        let challenges: [ChallengeLabel; 1] = ["challenge1"];
+        let mut original = Decree::new("testname", &inputs, &challenges)?;
+        let mut fork = original.clone();
+        fork.add_serial("input1", 10u32)?;
+        original.restore(fork.checkpoint());
+        assert!(bcs::to_bytes(&original).is_ok());
+
+        let mut other = Decree::new("othername", &inputs, &challenges)?;
+        other.add_serial("input1", 10u32)?;
+        original.restore(other.checkpoint());
+        assert!(bcs::to_bytes(&original).is_err());
+        Ok(())
+    }
+
+    #[test]
+    /// Errors can be compared directly against their message text
+    fn test_error_message_comparison() -> DecreeResult<()> {
+        let inputs: [InputLabel; 1] = ["input1"];
+        let challenges: [ChallengeLabel; 1] = ["challenge1"];
+        let mut my_decree = Decree::new("testname", &inputs, &challenges)?;
+
+        let e = my_decree.add_serial("input2", 1u32).unwrap_err();
+        assert_eq!(e, "Invalid label");
+        assert_ne!(e, "Label already used");
+        assert!(e == *"Invalid label");
+        assert!(e != *"Invalid");
+
+        my_decree.add_serial("input1", 1u32)?;
+        let e = my_decree.add_serial("input1", 1u32).unwrap_err();
+        assert_eq!(e, "Cannot add values after commitment");
+        Ok(())
+    }
+
+    #[test]
+    fn test_serialize_error_reports_label() -> DecreeResult<()> {
+        let inputs: [InputLabel; 2] = ["input1", "input2"];
+        let challenges: [ChallengeLabel; 1] = ["challenge1"];
+        let mut my_decree = Decree::new("testname", &inputs, &challenges)?;
+
+        // BCS does not support floating point values
+        let err = my_decree.add_serial("input2", 1.5f64).err().unwrap();
+        assert_eq!(err.get_type(), DecreeErrType::Serialization);
+        assert!(err.get_str().contains("input2"));
+        Ok(())
+    }
+
+    #[test]
+    /// Rejection sampling over a small modulus should give a near-uniform distribution. With
+    /// 7000 draws, each residue is expected 1000 times, with a standard deviation of about 29.
+    fn test_get_challenge_mod_distribution() -> DecreeResult<()> {
+        use num_bigint::BigUint;
+        let inputs: [InputLabel; 1] = ["input1"];
+        let challenges: [ChallengeLabel; 1] = ["challenge1"];
+        let modulus = BigUint::from(7u32);
+        let mut counts: [usize; 7] = [0usize; 7];
+
+        for i in 0..7000u32 {
+            let mut my_decree = Decree::new("testname", &inputs, &challenges)?;
+            my_decree.add_serial("input1", i)?;
+            let residue = my_decree.get_challenge_mod("challenge1", &modulus)?;
+            assert!(residue < modulus);
+            counts[residue.to_u32_digits().first().copied().unwrap_or(0) as usize] += 1;
+        }
+
+        for count in counts.iter() {
+            assert!(*count > 850 && *count < 1150, "Skewed distribution: {:?}", counts);
+        }
+        Ok(())
+    }
+
+    #[test]
+    /// Forked transcripts share their pre-fork challenges, then diverge
+    fn test_clone_fork() -> DecreeResult<()> {
+        let inputs: [InputLabel; 1] = ["shared"];
+        let challenges: [ChallengeLabel; 1] = ["prefix"];
+        let branch_inputs: [InputLabel; 1] = ["branch"];
+        let branch_challenges: [ChallengeLabel; 1] = ["suffix"];
+
+        let mut trunk = Decree::new("testname", &inputs, &challenges)?;
+        trunk.add_serial("shared", 10u32)?;
+        let mut fork = trunk.clone();
+
+        let mut trunk_prefix: [u8; 32] = [0u8; 32];
+        let mut fork_prefix: [u8; 32] = [0u8; 32];
+        trunk.get_challenge("prefix", &mut trunk_prefix)?;
+        fork.get_challenge("prefix", &mut fork_prefix)?;
+        assert_eq!(trunk_prefix, fork_prefix);
+
+        trunk.extend(&branch_inputs, &branch_challenges)?;
+        fork.extend(&branch_inputs, &branch_challenges)?;
+        trunk.add_serial("branch", 1u32)?;
+        fork.add_serial("branch", 2u32)?;
+
+        let mut trunk_suffix: [u8; 32] = [0u8; 32];
+        let mut fork_suffix: [u8; 32] = [0u8; 32];
+        trunk.get_challenge("suffix", &mut trunk_suffix)?;
+        fork.get_challenge("suffix", &mut fork_suffix)?;
+        assert_ne!(trunk_suffix, fork_suffix);
+        Ok(())
+    }
+
+    #[test]
+    /// Batch coefficients can't be anticipated by a prover choosing proofs adaptively, unlike
+    /// coefficients derived one proof at a time
+    fn test_batch_challenges_resist_adaptive_proofs() -> DecreeResult<()> {
+        // A toy batch check: each "proof" is just its error term, and a batch passes if the
+        // coefficient-weighted sum of the errors vanishes modulo a prime.
+        const P: u128 = (1 << 61) - 1;
+        let reduce = |bytes: &[u8; 32]| -> u128 {
+            u64::from_le_bytes(bytes[..8].try_into().unwrap()) as u128 % P
+        };
+        let inverse = |mut base: u128| -> u128 {
+            let mut result = 1u128;
+            let mut exp = P - 2;
+            while exp > 0 {
+                if exp & 1 == 1 {
+                    result = result * base % P;
+                }
+                base = base * base % P;
+                exp >>= 1;
+            }
+            result
+        };
+        let bad_error: u64 = 1;
+
+        // Improper batching: the coefficient for the first proof is derived before the second
+        // proof is absorbed, and the last coefficient is fixed at 1. Knowing the first
+        // coefficient, the prover picks a second proof that cancels out the bad first proof.
+        let mut sequential = Decree::new("batch", &["proof1"], &["r1"])?;
+        sequential.add_serial("proof1", bad_error)?;
+        let r1 = reduce(&sequential.challenge("r1")?);
+        let cancelling_error = ((P - r1 * bad_error as u128 % P) % P) as u64;
+        sequential.extend(&["proof2"], &["r2"])?;
+        sequential.add_serial("proof2", cancelling_error)?;
+        assert_eq!((r1 * bad_error as u128 + cancelling_error as u128) % P, 0);
+
+        // Proper batching: no coefficient exists until both proofs are fixed. The best the prover
+        // can do is guess a second proof and cancel against the coefficients the guess produces,
+        // but changing the second proof changes every coefficient.
+        let inputs: [InputLabel; 2] = ["proof1", "proof2"];
+        let challenges: [ChallengeLabel; 1] = ["batch"];
+        let mut guess = Decree::new("batch", &inputs, &challenges)?;
+        guess.add_serial("proof1", bad_error)?;
+        guess.add_serial("proof2", 0u64)?;
+        let guessed = guess.batch_challenges(&challenges, 2)?;
+        let (c1, c2) = (reduce(&guessed[0]), reduce(&guessed[1]));
+        let cancelling_error = ((P - c1 * bad_error as u128 % P) % P * inverse(c2) % P) as u64;
+        assert_eq!((c1 * bad_error as u128 + c2 * cancelling_error as u128) % P, 0);
+
+        let mut batch = Decree::new("batch", &inputs, &challenges)?;
+        batch.add_serial("proof1", bad_error)?;
+        batch.add_serial("proof2", cancelling_error)?;
+        let actual = batch.batch_challenges(&challenges, 2)?;
+        let (c1, c2) = (reduce(&actual[0]), reduce(&actual[1]));
+        assert_ne!((c1 * bad_error as u128 + c2 * cancelling_error as u128) % P, 0);
+
+        // The labels must still follow the schedule
+        let err = batch.batch_challenges(&["other"], 2).unwrap_err();
+        assert_eq!(err.get_type(), DecreeErrType::InvalidChallenge);
+        Ok(())
+    }
+
+    #[test]
+    /// Swapping equal-length values between two inputs changes the challenge
+    fn test_swapped_inputs_diverge() -> DecreeResult<()> {
+        let inputs: [InputLabel; 2] = ["input_a", "input_b"];
+        let challenges: [ChallengeLabel; 1] = ["challenge1"];
+
+        let mut original = Decree::new("testname", &inputs, &challenges)?;
+        original.add_bytes("input_a", b"left")?;
+        original.add_bytes("input_b", b"rght")?;
+
+        let mut swapped = Decree::new("testname", &inputs, &challenges)?;
+        swapped.add_bytes("input_a", b"rght")?;
+        swapped.add_bytes("input_b", b"left")?;
+
+        let original_out: [u8; 32] = original.challenge("challenge1")?;
+        let swapped_out: [u8; 32] = swapped.challenge("challenge1")?;
+        assert_ne!(original_out, swapped_out);
+        Ok(())
+    }
+
+    #[test]
+    /// Prover and verifier traces locate the first input on which they disagree
+    fn test_trace_divergence() -> DecreeResult<()> {
+        let inputs: [InputLabel; 3] = ["input1", "input2", "input3"];
+        let challenges: [ChallengeLabel; 1] = ["challenge1"];
+
+        let run = |second: u32| -> DecreeResult<Decree> {
+            let mut decree = Decree::new("testname", &inputs, &challenges)?;
+            decree.enable_trace();
+            decree.add_serial("input1", 1u32)?;
+            decree.add_serial("input2", second)?;
+            decree.add_serial("input3", 3u32)?;
+            decree.get_challenge_vec("challenge1", 32)?;
+            Ok(decree)
+        };
+        let prover = run(2u32)?;
+        let verifier = run(20u32)?;
+
+        assert_eq!(prover.trace().len(), verifier.trace().len());
+        let divergence = prover.trace()
+            .iter()
+            .zip(verifier.trace().iter())
+            .position(|(ours, theirs)| ours != theirs)
+            .unwrap();
+        assert_eq!(prover.trace()[divergence].label, "input2");
+        assert_eq!(prover.trace()[divergence].length, verifier.trace()[divergence].length);
+        Ok(())
+    }
+
+    #[test]
+    /// Repeated challenge and input labels are rejected by `new` and `extend`
+    fn test_repeated_challenge_labels() -> DecreeResult<()> {
+        let inputs: [InputLabel; 1] = ["input1"];
+        let repeated: [ChallengeLabel; 3] = ["challenge1", "challenge2", "challenge1"];
+
+        let err = Decree::new("testname", &inputs, &repeated).err().unwrap();
+        assert_eq!(err.get_type(), DecreeErrType::InitFail);
+
+        let mut decree = Decree::new("testname", &inputs, &["challenge1"])?;
+        decree.add_serial("input1", 1u32)?;
+        decree.get_challenge_vec("challenge1", 32)?;
+        let err = decree.extend(&["input2"], &repeated).unwrap_err();
+        assert_eq!(err.get_type(), DecreeErrType::ExtendFail);
+        let err = decree.extend(&["input2", "input2"], &["challenge2"]).unwrap_err();
+        assert_eq!(err.get_type(), DecreeErrType::ExtendFail);
+        assert_eq!(err, "Inputs must be distinct");
+
+        // The failed extension leaves the stage in place
+        assert!(decree.is_extendable());
+        decree.extend(&["input2"], &["challenge2"])?;
+        Ok(())
+    }
+
+    #[test]
+    /// Runtime-generated labels drive a multi-round protocol, and prover and verifier agree
+    fn test_owned_labels() -> DecreeResult<()> {
+        let commitments: Vec<String> = (0..3).map(|i| format!("commit_{i}")).collect();
+        let challenges: Vec<String> = (0..3).map(|i| format!("challenge_{i}")).collect();
+        let rounds: Vec<String> = (0..3).map(|i| format!("response_{i}")).collect();
+        let round_challenges = vec![String::from("final")];
+
+        let run = |order: &[usize]| -> DecreeResult<Vec<Vec<u8>>> {
+            let mut decree = Decree::new_owned("testname", &commitments, &challenges)?;
+            for i in order {
+                decree.add_serial(&commitments[*i], *i as u32)?;
+            }
+            assert!(decree.missing_inputs().is_empty());
+            assert_eq!(decree.remaining_challenges(), vec!["challenge_0", "challenge_1", "challenge_2"]);
+
+            let mut outputs = Vec::new();
+            for challenge in challenges.iter() {
+                outputs.push(decree.get_challenge_vec(challenge, 32)?);
+            }
+
+            decree.extend_owned(&rounds, &round_challenges)?;
+            for (i, round) in rounds.iter().enumerate() {
+                decree.add_serial(round, i as u32)?;
+            }
+            outputs.push(decree.get_challenge_vec("final", 32)?);
+            Ok(outputs)
+        };
+
+        let prover = run(&[0, 1, 2])?;
+        let verifier = run(&[2, 0, 1])?;
+        assert_eq!(prover, verifier);
+        assert_ne!(prover[0], prover[1]);
+        assert_ne!(prover[1], prover[2]);
+
+        // Labels outside the generated set are still rejected
+        let mut decree = Decree::new_owned("testname", &commitments, &challenges)?;
+        let err = decree.add_serial("commit_3", 3u32).unwrap_err();
+        assert_eq!(err.get_type(), DecreeErrType::InvalidLabel);
+        Ok(())
+    }
+
+    #[test]
+    /// Errors can be cloned out of a failed call and compared with the original
+    fn test_error_clone_eq() -> DecreeResult<()> {
+        let inputs: [InputLabel; 1] = ["input1"];
+        let challenges: [ChallengeLabel; 1] = ["challenge1"];
+        let mut decree = Decree::new("testname", &inputs, &challenges)?;
+        let err = decree.add_serial("input2", 1u32).unwrap_err();
+
+        let cloned = err.clone();
+        assert_eq!(cloned, err);
+        assert_eq!(cloned.get_type(), DecreeErrType::InvalidLabel);
+
+        // Errors with the same message but a different type are distinct
+        assert_ne!(Error::new_general(err.get_str().to_string()), err);
+        Ok(())
+    }
+
+    #[test]
+    /// `absorb` picks the inscription for `Inscribe` types and BCS for `Serial` values
+    fn test_absorb_dispatch() -> DecreeResult<()> {
+        let inputs: [InputLabel; 2] = ["count", "symbol"];
+        let challenges: [ChallengeLabel; 1] = ["challenge1"];
+        let run = |count: &dyn Fn(&mut Decree) -> DecreeResult<()>,
+                   symbol: &dyn Fn(&mut Decree) -> DecreeResult<()>| -> DecreeResult<Vec<u8>> {
+            let mut decree = Decree::new("testname", &inputs, &challenges)?;
+            count(&mut decree)?;
+            symbol(&mut decree)?;
+            decree.get_challenge_vec("challenge1", 32)
+        };
+
+        // `u64` is both `Inscribe` and `Serialize`; `u8` is only `Serialize`
+        let absorbed = run(&|d| d.absorb("count", &7u64), &|d| d.absorb("symbol", &Serial(5u8)))?;
+        let added = run(&|d| d.add("count", &7u64), &|d| d.add_serial("symbol", 5u8))?;
+        let serialized = run(&|d| d.add_serial("count", 7u64), &|d| d.add_serial("symbol", 5u8))?;
+
+        assert_eq!(absorbed, added);
+        assert_ne!(absorbed, serialized);
+        Ok(())
+    }
+
+    #[test]
+    /// A kept input carries over to the next round without being absorbed again
+    fn test_extend_keeping() -> DecreeResult<()> {
+        let inputs: [InputLabel; 2] = ["modulus", "commitment"];
+        let challenges: [ChallengeLabel; 1] = ["challenge1"];
+        let first_round = || -> DecreeResult<Decree> {
+            let mut decree = Decree::new("testname", &inputs, &challenges)?;
+            decree.add_serial("modulus", 23u32)?;
+            decree.add_serial("commitment", 5u32)?;
+            decree.get_challenge_vec("challenge1", 32)?;
+            Ok(decree)
+        };
+
+        let mut kept = first_round()?;
+        kept.extend_keeping(&["response"], &["challenge2"], &["modulus"])?;
+        assert!(kept.add_serial("modulus", 23u32).is_err());
+        kept.add_serial("response", 7u32)?;
+        let kept_out = kept.get_challenge_vec("challenge2", 32)?;
+
+        // The transcript is the same as for a round that never listed the kept input
+        let mut plain = first_round()?;
+        plain.extend(&["response"], &["challenge2"])?;
+        plain.add_serial("response", 7u32)?;
+        assert_eq!(kept_out, plain.get_challenge_vec("challenge2", 32)?);
+
+        // Re-adding the input instead absorbs it a second time
+        let mut readded = first_round()?;
+        readded.extend(&["response", "modulus"], &["challenge2"])?;
+        readded.add_serial("modulus", 23u32)?;
+        readded.add_serial("response", 7u32)?;
+        assert_ne!(kept_out, readded.get_challenge_vec("challenge2", 32)?);
+        Ok(())
+    }
+
+    #[test]
+    /// The absorbed byte count covers each input's value and its frame, and survives a reset
+    fn test_bytes_absorbed() -> DecreeResult<()> {
+        let inputs: [InputLabel; 2] = ["input1", "input2"];
+        let challenges: [ChallengeLabel; 1] = ["challenge1"];
+        let mut decree = Decree::new("testname", &inputs, &challenges)?;
+        decree.add_bytes("input1", &[1u8; 100])?;
+        assert_eq!(decree.bytes_absorbed(), 0);
+
+        // BCS encodes a `u64` in 8 bytes; each input is preceded by a 16-byte frame
+        decree.add_serial("input2", 7u64)?;
+        let framed = (16 + 100) + (16 + 8);
+        assert_eq!(decree.bytes_absorbed(), framed);
+
+        // Drawing challenges doesn't absorb anything
+        decree.get_challenge_vec("challenge1", 32)?;
+        assert_eq!(decree.bytes_absorbed(), framed);
+
+        decree.reset();
+        assert_eq!(decree.bytes_absorbed(), 0);
+        Ok(())
+    }
+
+    #[test]
+    /// An empty challenge buffer is rejected without consuming the challenge
+    fn test_zero_length_challenge() -> DecreeResult<()> {
+        let inputs: [InputLabel; 1] = ["input1"];
+        let challenges: [ChallengeLabel; 1] = ["challenge1"];
+        let mut decree = Decree::new("testname", &inputs, &challenges)?;
+        decree.add_serial("input1", 1u32)?;
+
+        let err = decree.get_challenge("challenge1", &mut []).unwrap_err();
+        assert_eq!(err.get_type(), DecreeErrType::InvalidChallenge);
+        assert_eq!(err, "Zero-length challenge buffer");
+        assert!(decree.get_challenge_vec("challenge1", 0).is_err());
+        let err = decree.peek_challenge("challenge1", &mut []).unwrap_err();
+        assert_eq!(err.get_type(), DecreeErrType::InvalidChallenge);
+        assert_eq!(err, "Zero-length challenge buffer");
+
+        assert_eq!(decree.remaining_challenges(), vec!["challenge1"]);
+        decree.get_challenge_vec("challenge1", 32)?;
+        Ok(())
+    }
+
+    #[test]
+    /// With challenge caching enabled, peeking an already-generated challenge returns the cached
+    /// bytes, as `get_challenge` does
+    fn test_peek_cached_challenge() -> DecreeResult<()> {
+        let inputs: [InputLabel; 1] = ["input1"];
+        let challenges: [ChallengeLabel; 2] = ["challenge1", "challenge2"];
+        let mut uncached = Decree::new("testname", &inputs, &challenges)?;
+        uncached.add_serial("input1", 1u32)?;
+        let mut cached = uncached.clone();
+        cached.set_challenge_caching(true);
+
+        // Without the cache, only the next challenge can be peeked
+        let first = uncached.get_challenge_vec("challenge1", 32)?;
+        let mut peeked = [0u8; 32];
+        assert!(uncached.peek_challenge("challenge1", &mut peeked).is_err());
+
+        assert_eq!(cached.get_challenge_vec("challenge1", 32)?, first);
+        cached.peek_challenge("challenge1", &mut peeked)?;
+        assert_eq!(peeked.to_vec(), first);
+        let err = cached.peek_challenge("challenge1", &mut [0u8; 16]).unwrap_err();
+        assert_eq!(err, "Repeated challenge length mismatch");
+
+        // Peeking the cache leaves the next challenge where it was
+        assert_eq!(cached.remaining_challenges(), vec!["challenge2"]);
+        assert_eq!(cached.get_challenge_vec("challenge2", 32)?, uncached.get_challenge_vec("challenge2", 32)?);
+        Ok(())
+    }
+
+    #[test]
+    /// Little- and big-endian readings of the same challenge differ, but the prover and verifier
+    /// agree as long as they use the same order
+    fn test_challenge_byte_order() -> DecreeResult<()> {
+        use decree::challenge::ByteOrder;
+        use num_bigint::BigUint;
+
+        let modulus = BigUint::parse_bytes(
+            b"7237005577332262213973186563042994240857116359379907606001950938285454250989", 10).unwrap();
+        let run = |order: ByteOrder| -> DecreeResult<(BigUint, BigUint)> {
+            let mut decree = Decree::new("testname", &["input1"], &["typed", "reduced"])?;
+            decree.add_serial("input1", 10u32)?;
+            let typed: BigUint = decree.get_challenge_as_ordered("typed", order)?;
+            let reduced = decree.get_challenge_mod_ordered("reduced", &modulus, order)?;
+            Ok((typed, reduced))
+        };
+
+        let (prover_le, prover_le_mod) = run(ByteOrder::LittleEndian)?;
+        let (verifier_le, verifier_le_mod) = run(ByteOrder::LittleEndian)?;
+        let (prover_be, prover_be_mod) = run(ByteOrder::BigEndian)?;
+        let (verifier_be, verifier_be_mod) = run(ByteOrder::BigEndian)?;
+        assert_eq!((&prover_le, &prover_le_mod), (&verifier_le, &verifier_le_mod));
+        assert_eq!((&prover_be, &prover_be_mod), (&verifier_be, &verifier_be_mod));
+        assert_ne!(prover_le, prover_be);
+        assert_ne!(prover_le_mod, prover_be_mod);
+
+        // The byte orders are mirror images of each other
+        let mut reversed = prover_le.to_bytes_le();
+        reversed.resize(32, 0);
+        assert_eq!(BigUint::from_bytes_be(&reversed), prover_be);
+
+        // The default order is little-endian
+        let mut decree = Decree::new("testname", &["input1"], &["typed", "reduced"])?;
+        decree.add_serial("input1", 10u32)?;
+        assert_eq!(decree.get_challenge_as::<BigUint>("typed")?, prover_le);
+        assert_eq!(decree.get_challenge_mod("reduced", &modulus)?, prover_le_mod);
+        Ok(())
+    }
+
+    #[test]
+    /// Merging two parallel sub-proofs binds the parent challenge to both of them
+    fn test_absorb_subtranscripts() -> DecreeResult<()> {
+        let sub_proof = |name: &'static str, commitment: u32| -> DecreeResult<Decree> {
+            let mut sub = Decree::new(name, &["commitment"], &["challenge"])?;
+            sub.add_serial("commitment", commitment)?;
+            sub.get_challenge_vec("challenge", 32)?;
+            Ok(sub)
+        };
+        let merge = |left: &Decree, right: &Decree| -> DecreeResult<Vec<u8>> {
+            let mut parent = Decree::new("parent", &["left", "right"], &["challenge1"])?;
+            parent.absorb_subtranscript("right", right)?;
+            parent.absorb_subtranscript("left", left)?;
+            parent.get_challenge_vec("challenge1", 32)
+        };
+
+        let left = sub_proof("left proof", 1)?;
+        let right = sub_proof("right proof", 2)?;
+        let merged = merge(&left, &right)?;
+        assert_eq!(merged, merge(&sub_proof("left proof", 1)?, &sub_proof("right proof", 2)?)?);
+        assert_ne!(merged, merge(&sub_proof("left proof", 3)?, &right)?);
+        assert_ne!(merged, merge(&left, &sub_proof("right proof", 3)?)?);
+        assert_ne!(merged, merge(&right, &left)?);
+
+        // Absorbing doesn't disturb the sub-transcript
+        let mut extended = left.clone();
+        let mut untouched = sub_proof("left proof", 1)?;
+        extended.extend(&["response"], &["final"])?;
+        untouched.extend(&["response"], &["final"])?;
+        extended.add_serial("response", 4u32)?;
+        untouched.add_serial("response", 4u32)?;
+        assert_eq!(extended.get_challenge_vec("final", 32)?, untouched.get_challenge_vec("final", 32)?);
+        Ok(())
+    }
+
+    #[test]
+    /// Test that `Debug` output shows labels and commitment status, but never input values
+    fn test_debug_redacts_values() -> DecreeResult<()> {
+        let secret = [0xdeu8, 0xad, 0xbe, 0xef];
+        let mut transcript = Decree::new("debug test", &["witness", "public"], &["challenge1"])?;
+        transcript.add_bytes("witness", &secret)?;
+
+        let pending = format!("{:?}", transcript);
+        assert!(pending.contains("debug test"));
+        assert!(pending.contains("\"public\": <missing>"));
+        assert!(pending.contains("\"witness\": <pending>"));
+        assert!(pending.contains("challenge1"));
+
+        transcript.add_serial("public", 7u32)?;
+        let committed = format!("{:?}", transcript);
+        assert!(committed.contains("\"public\": <committed>"));
+        assert!(committed.contains("\"witness\": <committed>"));
+
+        for output in [pending, committed, format!("{:#?}", transcript)] {
+            assert!(!output.contains("222"));
+            assert!(!output.to_lowercase().contains("deadbeef"));
+        }
+        Ok(())
+    }
+
+    #[test]
+    /// Test that a transcript can't be created with an empty name
+    fn test_empty_name() {
+        let err = Decree::new("", &["input1"], &["challenge1"]).unwrap_err();
+        assert_eq!(err, Error::new_init_fail("Transcript name must be non-empty"));
+        assert_eq!(err.get_type(), DecreeErrType::InitFail);
+
+        let owned_err = Decree::new_owned("", &[String::from("input1")], &[String::from("challenge1")]).unwrap_err();
+        assert_eq!(owned_err, err);
+    }
+
+    #[test]
+    /// Test that `inputs_remaining` counts down as inputs are added, and resets on `extend`
+    fn test_inputs_remaining() -> DecreeResult<()> {
+        let mut transcript = Decree::new("remaining test", &["input1", "input2"], &["challenge1"])?;
+        assert_eq!(transcript.inputs_remaining(), 2);
+        transcript.add_serial("input2", 1u32)?;
+        assert_eq!(transcript.inputs_remaining(), 1);
+        transcript.add_serial("input1", 2u32)?;
+        assert_eq!(transcript.inputs_remaining(), 0);
+        assert_eq!(transcript.inputs_remaining(), transcript.missing_inputs().len());
+
+        transcript.get_challenge_vec("challenge1", 32)?;
+        transcript.extend(&["input3", "input4", "input5"], &["challenge2"])?;
+        assert_eq!(transcript.inputs_remaining(), 3);
+        transcript.add_serial("input4", 3u32)?;
+        assert_eq!(transcript.inputs_remaining(), 2);
+        assert_eq!(transcript.inputs_remaining(), transcript.missing_inputs().len());
+        Ok(())
+    }
+
+    #[test]
+    /// Test that extending with the same labels, and adding the same inputs, gives fresh challenges
+    /// every round
+    fn test_repeated_rounds_diverge() -> DecreeResult<()> {
+        let inputs: [InputLabel; 1] = ["commitment"];
+        let challenges: [ChallengeLabel; 1] = ["round challenge"];
+        let plain = Decree::new("recursion test", &inputs, &challenges)?;
+        let mut cached = plain.clone();
+        cached.set_challenge_caching(true);
+        let strict = Decree::with_strict_challenges("recursion test", &inputs, &challenges)?;
+
+        for mut transcript in [plain, cached, strict] {
+            let mut seen: Vec<Vec<u8>> = Vec::new();
+            for round in 0..5 {
+                if round > 0 {
+                    transcript.extend(&inputs, &challenges)?;
+                }
+                transcript.add_serial("commitment", 42u32)?;
+                let challenge = transcript.get_challenge_vec("round challenge", 32)?;
+                assert!(!seen.contains(&challenge), "Challenge repeated in round {}", round);
+                seen.push(challenge);
+            }
+            assert_eq!(seen.len(), 5);
+        }
+        Ok(())
+    }
+
+    #[test]
+    /// Test that errors about a specific input or challenge name the offending label
+    fn test_error_label_context() -> DecreeResult<()> {
+        let mut transcript = Decree::new("label test", &["input1"], &["challenge1", "challenge2"])?;
+        let err = transcript.add_bytes("bogus", &[1u8, 2u8]).unwrap_err();
+        assert_eq!(err, "Invalid label");
+        assert_eq!(err.get_label(), Some("bogus"));
+        assert!(format!("{}", err).contains("bogus"));
+
+        transcript.add_serial("input1", 1u32)?;
+        let err = transcript.add_serial("input1", 2u32).unwrap_err();
+        assert_eq!(err.get_label(), Some("input1"));
+
+        let err = transcript.get_challenge_vec("challenge2", 32).unwrap_err();
+        assert_eq!(err, "Challenge order incorrect");
+        assert!(format!("{}", err).contains("challenge2"));
+        Ok(())
+    }
+
+    #[test]
+    /// Test that `commit_now` commits complete stages, tolerates earlier commits, and lists any
+    /// missing inputs
+    fn test_commit_now() -> DecreeResult<()> {
+        let mut transcript = Decree::new("commit test", &["input1", "input2", "input3"], &["challenge1"])?;
+        transcript.add_serial("input2", 2u32)?;
+        let err = transcript.commit_now().unwrap_err();
+        assert_eq!(err, "Cannot commit with missing input(s): input1, input3");
+        assert!(!transcript.is_committed());
+
+        transcript.add_serial("input1", 1u32)?;
+        transcript.add_serial("input3", 3u32)?;
+        assert!(transcript.is_committed());
+        transcript.commit_now()?;
+
+        let mut manual = Decree::new_manual("commit test", &["input1", "input2", "input3"], &["challenge1"])?;
+        manual.add_serial("input3", 3u32)?;
+        manual.add_serial("input1", 1u32)?;
+        manual.add_serial("input2", 2u32)?;
+        assert!(!manual.is_committed());
+        manual.commit_now()?;
+        assert!(manual.is_committed());
+        manual.commit_now()?;
+
+        assert_eq!(transcript.get_challenge_vec("challenge1", 32)?, manual.get_challenge_vec("challenge1", 32)?);
+        Ok(())
+    }
+
+    #[test]
+    /// Test that `get_challenge_bits` is deterministic, and expands the bytes of a same-length draw
+    /// least significant bit first
+    fn test_challenge_bits() -> DecreeResult<()> {
+        let bits_for = |input: u32| -> DecreeResult<Vec<bool>> {
+            let mut transcript = Decree::new("bits test", &["input1"], &["challenge1"])?;
+            transcript.add_serial("input1", input)?;
+            transcript.get_challenge_bits("challenge1", 10)
+        };
+        let bits = bits_for(5u32)?;
+        assert_eq!(bits.len(), 10);
+        assert_eq!(bits, bits_for(5u32)?);
+
+        let mut transcript = Decree::new("bits test", &["input1"], &["challenge1"])?;
+        transcript.add_serial("input1", 5u32)?;
+        let bytes = transcript.get_challenge_vec("challenge1", 2)?;
+        let expected: Vec<bool> = (0..10).map(|bit| (bytes[bit / 8] >> (bit % 8)) & 1 == 1).collect();
+        assert_eq!(bits, expected);
+
+        let mut transcript = Decree::new("bits test", &["input1"], &["challenge1"])?;
+        transcript.add_serial("input1", 5u32)?;
+        let err = transcript.get_challenge_bits("challenge1", 0).unwrap_err();
+        assert_eq!(err, "Zero-length challenge buffer");
+        Ok(())
+    }
+
+    #[test]
+    /// Test that the byte limit is enforced, without overflow, when the inputs added so far already
+    /// exceed it
+    fn test_input_bytes_over_limit() -> DecreeResult<()> {
+        let inputs: [InputLabel; 2] = ["input1", "input2"];
+        let challenges: [ChallengeLabel; 1] = ["challenge1"];
+        let mut loose = Decree::new("limit test", &inputs, &challenges)?;
+        loose.add_bytes("input1", &[0u8; 16])?;
+
+        let limits = DecreeLimits { max_input_bytes: 8, ..DecreeLimits::default() };
+        let mut strict = Decree::new_with_limits("limit test", &inputs, &challenges, limits)?;
+        strict.restore(loose.checkpoint());
+        let err = strict.add_bytes("input2", &[]).unwrap_err();
+        assert_eq!(err, "Input size limit exceeded");
+        Ok(())
+    }
+
+    #[test]
+    /// A proof must be non-empty and cover every pending challenge of the stage
+    fn test_verify_challenges_full_stage() -> DecreeResult<()> {
+        let inputs: [InputLabel; 1] = ["input1"];
+        let challenges: [ChallengeLabel; 3] = ["challenge1", "challenge2", "challenge3"];
+        let mut prover = Decree::new("proof test", &inputs, &challenges)?;
+        let verifier = prover.clone_for_verification();
+        prover.add_serial("input1", 10u32)?;
+        let challenge1: [u8; 32] = prover.challenge("challenge1")?;
+        let challenge2: [u8; 32] = prover.challenge("challenge2")?;
+        let challenge3: [u8; 32] = prover.challenge("challenge3")?;
+
+        let mut empty = verifier.clone();
+        empty.add_serial("input1", 10u32)?;
+        let err = empty.verify_challenges(&prover.clone().into_proof(vec![])).unwrap_err();
+        assert_eq!(err, "Proof contains no challenges");
+
+        let mut partial = verifier.clone();
+        partial.add_serial("input1", 10u32)?;
+        let proof = prover.clone().into_proof(vec![("challenge1", challenge1.to_vec())]);
+        let err = partial.verify_challenges(&proof).unwrap_err();
+        assert_eq!(err, "Proof leaves challenge(s) unchecked: challenge2, challenge3");
+
+        let mut full = verifier;
+        full.add_serial("input1", 10u32)?;
+        let proof = prover.into_proof(vec![
+            ("challenge1", challenge1.to_vec()),
+            ("challenge2", challenge2.to_vec()),
+            ("challenge3", challenge3.to_vec())]);
+        full.verify_challenges(&proof)?;
+        Ok(())
+    }
+}
@@ -1,7 +1,6 @@
 #[cfg(test)]
 mod tests {
-    use num_bigint::{BigInt, BigUint, RandBigInt, Sign};
-    use bcs;
+    use num_bigint::{BigInt, RandBigInt, Sign};
     use decree::error::Error;
     use decree::Inscribe;
     use decree::decree::FSInput;
@@ -89,6 +88,1282 @@ mod tests {
         assert_eq!(inscript_auto, buffer_total.to_vec());
     }
 
+    #[test]
+    #[cfg(feature = "describe")]
+    /// Test the structural descriptions of the derived example structs
+    fn test_derive_describe() {
+        let point = Point { x: 1i32, y: 2i32 };
+        assert_eq!(point.describe(),
+            "Atypical mark!\n  input_1 (y): serialize\n  input_2 (x): serialize\n  additional: none\n");
+
+        let inscriber = InscribeTest { a: Point { x: 1i32, y: 2i32 }, b: Point { x: 3i32, y: 4i32 } };
+        assert_eq!(inscriber.describe(),
+            "InscribeTest\n  a (a): recurse\n  b (b): recurse\n  additional: additional_data_method\n");
+
+        let computed = ComputedTest {
+            a: Point { x: 1i32, y: 2i32 },
+            b: Point { x: 3i32, y: 4i32 },
+            sum: (),
+        };
+        assert_eq!(computed.describe(),
+            "ComputedTest\n  a (a): recurse\n  b (b): recurse\n  sum (sum): compute_with(compute_sum)\n  additional: none\n");
+    }
+
+    /// The same data with owned and borrowed additional data
+    #[derive(Inscribe)]
+    #[inscribe_mark(tagged_mark)]
+    #[inscribe_addl(owned_tag)]
+    struct OwnedTag {
+        #[inscribe(serialize)]
+        value: u64,
+        #[inscribe(skip)]
+        tag: Vec<u8>,
+    }
+
+    impl OwnedTag {
+        fn tagged_mark(&self) -> &'static str {
+            "Tagged"
+        }
+
+        fn owned_tag(&self) -> Result<FSInput, Error> {
+            Ok(self.tag.clone())
+        }
+    }
+
+    #[derive(Inscribe)]
+    #[inscribe_mark(tagged_mark)]
+    #[inscribe_addl_bytes(borrowed_tag)]
+    struct BorrowedTag {
+        #[inscribe(serialize)]
+        value: u64,
+        #[inscribe(skip)]
+        tag: Vec<u8>,
+    }
+
+    impl BorrowedTag {
+        fn tagged_mark(&self) -> &'static str {
+            "Tagged"
+        }
+
+        fn borrowed_tag(&self) -> Result<&[u8], Error> {
+            Ok(self.tag.as_slice())
+        }
+    }
+
+    #[test]
+    /// Test that borrowed additional data produces the same inscription as owned additional data
+    fn test_derive_addl_bytes() {
+        let owned = OwnedTag { value: 8675309u64, tag: ADDL_TEST_DATA.as_bytes().to_vec() };
+        let borrowed = BorrowedTag { value: 8675309u64, tag: ADDL_TEST_DATA.as_bytes().to_vec() };
+        assert_eq!(owned.get_inscription().unwrap(), borrowed.get_inscription().unwrap());
+        assert_eq!(borrowed.get_additional().unwrap(), ADDL_TEST_DATA.as_bytes().to_vec());
+    }
+
+    #[derive(Inscribe)]
+    #[inscribe(deny_unjustified_skip)]
+    struct JustifiedSkip {
+        #[inscribe(serialize)]
+        value: u64,
+        #[inscribe(skip, reason = "derived from value")]
+        cache: Vec<u8>,
+    }
+
+    #[derive(Inscribe)]
+    #[inscribe_mark("JustifiedSkip")]
+    struct PlainSkip {
+        #[inscribe(serialize)]
+        value: u64,
+        #[inscribe(skip)]
+        cache: Vec<u8>,
+    }
+
+    #[test]
+    /// A skip reason is documentation only: it doesn't change the inscription, but it shows up in
+    /// `describe`
+    fn test_skip_reason() {
+        let justified = JustifiedSkip { value: 8675309u64, cache: vec![1u8] };
+        let plain = PlainSkip { value: 8675309u64, cache: vec![2u8] };
+        assert_ne!(justified.cache, plain.cache);
+        assert_eq!(justified.get_inscription().unwrap(), plain.get_inscription().unwrap());
+
+        #[cfg(feature = "describe")]
+        {
+            assert_eq!(justified.describe(),
+                "JustifiedSkip\n  cache (cache): skip(derived from value)\n  value (value): serialize\n  additional: none\n");
+            assert_eq!(plain.describe(),
+                "JustifiedSkip\n  cache (cache): skip\n  value (value): serialize\n  additional: none\n");
+        }
+    }
+
+    #[derive(Inscribe)]
+    #[inscribe_mark("Holder")]
+    struct BoxedHolder {
+        point: Box<Point>,
+    }
+
+    #[derive(Inscribe)]
+    #[inscribe_mark("Holder")]
+    struct BorrowedHolder<'a> {
+        point: &'a Point,
+    }
+
+    #[derive(Inscribe)]
+    #[inscribe_mark("Holder")]
+    struct PlainHolder {
+        point: Point,
+    }
+
+    #[test]
+    /// Boxed and borrowed members inscribe exactly like the values they point to
+    fn test_derive_boxed_member() {
+        let point = Point { x: 8675309i32, y: 8675311i32 };
+        let boxed = BoxedHolder { point: Box::new(Point { x: 8675309i32, y: 8675311i32 }) };
+        let borrowed = BorrowedHolder { point: &point };
+        let plain = PlainHolder { point: Point { x: 8675309i32, y: 8675311i32 } };
+
+        let expected = plain.get_inscription().unwrap();
+        assert_eq!(boxed.get_inscription().unwrap(), expected);
+        assert_eq!(borrowed.get_inscription().unwrap(), expected);
+        assert_eq!(boxed.point.get_mark(), MARK_TEST_DATA);
+    }
+
+    #[test]
+    /// `Serialized` inscribes the BCS serialization of its value under a fixed mark
+    fn test_serialized_inscription() {
+        use decree::inscribe::Serialized;
+
+        let value = (8675309u64, String::from("session"), vec![1u16, 2u16]);
+        let wrapped = Serialized(value.clone());
+        assert_eq!(wrapped.get_mark(), "serde_bcs_serialized");
+
+        let mut expected = TupleHash::v256("serde_bcs_serialized".as_bytes());
+        expected.update(&bcs::to_bytes(&value).unwrap());
+        let mut expected_buf = [0u8; INSCRIBE_LENGTH];
+        expected.finalize(&mut expected_buf);
+        assert_eq!(wrapped.get_inscription().unwrap(), expected_buf.to_vec());
+    }
+
+    #[test]
+    /// The provided byte string and serialization impls use the exported marks
+    fn test_exported_marks() {
+        use decree::inscribe::{Serialized, INSCRIBE_MARK_SERIALIZE, INSCRIBE_MARK_U8_SLICE};
+
+        let bytes = vec![1u8, 2u8, 3u8];
+        assert_eq!(bytes.get_mark(), INSCRIBE_MARK_U8_SLICE);
+        assert_eq!(bytes.as_slice().get_mark(), INSCRIBE_MARK_U8_SLICE);
+        assert_eq!([1u8, 2u8, 3u8].get_mark(), INSCRIBE_MARK_U8_SLICE);
+        assert_eq!(Serialized(7u64).get_mark(), INSCRIBE_MARK_SERIALIZE);
+
+        // A hand-written impl reusing the mark inscribes like the provided one
+        let mut expected = TupleHash::v256(INSCRIBE_MARK_U8_SLICE.as_bytes());
+        expected.update(&bytes);
+        let mut expected_buf = [0u8; INSCRIBE_LENGTH];
+        expected.finalize(&mut expected_buf);
+        assert_eq!(bytes.as_slice().get_inscription().unwrap(), expected_buf.to_vec());
+    }
+
+    #[test]
+    /// A streamed sequence of derived values matches the `Vec<T>` inscription of the same elements
+    fn test_add_iter_matches_vec() {
+        use decree::decree::Decree;
+
+        let points: Vec<Point> = (0..16).map(|i| Point { x: i, y: -i }).collect();
+        let run = |add: &dyn Fn(&mut Decree)| {
+            let mut decree = Decree::new("testname", &["points"], &["challenge1"]).unwrap();
+            add(&mut decree);
+            decree.get_challenge_vec("challenge1", 32).unwrap()
+        };
+
+        let streamed = run(&|decree| decree.add_iter("points", points.iter()).unwrap());
+        let collected = run(&|decree| decree.add("points", &points).unwrap());
+        let reversed = run(&|decree| decree.add_iter("points", points.iter().rev()).unwrap());
+        assert_eq!(streamed, collected);
+        assert_ne!(streamed, reversed);
+    }
+
+    #[test]
+    /// Containers absorb their elements exactly as `add_iter` does, so elements that absorb
+    /// nothing and elements of the wrong length are treated the same either way
+    fn test_container_elements_match_add_iter() {
+        use decree::decree::Decree;
+        use std::collections::BTreeMap;
+        use std::marker::PhantomData;
+
+        let markers: Vec<PhantomData<u32>> = vec![PhantomData; 3];
+        let run = |add: &dyn Fn(&mut Decree)| {
+            let mut decree = Decree::new("testname", &["markers"], &["challenge1"]).unwrap();
+            add(&mut decree);
+            decree.get_challenge_vec("challenge1", 32).unwrap()
+        };
+        let streamed = run(&|decree| decree.add_iter("markers", markers.iter()).unwrap());
+        let collected = run(&|decree| decree.add("markers", &markers).unwrap());
+        assert_eq!(streamed, collected);
+
+        // A `PhantomData` element adds no item at all, not an empty one
+        let mut tuplehasher = TupleHash::v256("vec".as_bytes());
+        tuplehasher.update(&3u64.to_le_bytes());
+        let mut buffer: [u8; INSCRIBE_LENGTH] = [0u8; INSCRIBE_LENGTH];
+        tuplehasher.finalize(&mut buffer);
+        assert_eq!(markers.get_inscription().unwrap(), buffer.to_vec());
+        assert_eq!([PhantomData::<u32>; 2].get_inscription().unwrap(), [0u64; 0].get_inscription().unwrap());
+
+        // A short hand-written child is rejected wherever it appears
+        let shorts = vec![ShortInscription];
+        let mut decree = Decree::new("testname", &["markers"], &["challenge1"]).unwrap();
+        let err = decree.add_iter("markers", shorts.iter()).unwrap_err();
+        assert_eq!(err.get_str(), "Invalid inscription length");
+        assert_eq!(shorts.get_inscription().unwrap_err().get_str(), "Invalid inscription length");
+        assert_eq!([ShortInscription].get_inscription().unwrap_err().get_str(), "Invalid inscription length");
+        assert_eq!(Some(ShortInscription).get_inscription().unwrap_err().get_str(), "Invalid inscription length");
+        assert_eq!((ShortInscription, 1u64).get_inscription().unwrap_err().get_str(), "Invalid inscription length");
+        let map = BTreeMap::from([(1u64, ShortInscription)]);
+        assert_eq!(map.get_inscription().unwrap_err().get_str(), "Invalid inscription length");
+    }
+
+    #[test]
+    /// Equal `BigInt`s inscribe identically however they were built, and the inscription is the
+    /// documented sign-magnitude encoding rather than anything tied to the digit size
+    fn test_bigint_canonical_inscription() {
+        use num_bigint::BigUint;
+        use std::str::FromStr;
+
+        let modulus = &BigInt::from(2u32).pow(127) - BigInt::from(1u32);
+        let from_str = BigInt::from_str("170141183460469231731687303715884105727").unwrap();
+        let from_le = BigInt::from_bytes_le(Sign::Plus, &[0xffu8; 16]) >> 1;
+        let from_digits = BigInt::from_slice(Sign::Plus, &[u32::MAX, u32::MAX, u32::MAX, i32::MAX as u32]);
+        let from_uint = BigInt::from(BigUint::from(u128::MAX >> 1));
+        for other in [&from_str, &from_le, &from_digits, &from_uint] {
+            assert_eq!(other, &modulus);
+            assert_eq!(other.get_inscription().unwrap(), modulus.get_inscription().unwrap());
+        }
+
+        let mut expected = TupleHash::v256("big_int".as_bytes());
+        expected.update(&[0u8]);
+        expected.update(&[0x7fu8].iter().chain([0xffu8; 15].iter()).copied().collect::<Vec<u8>>());
+        let mut expected_buf = [0u8; INSCRIBE_LENGTH];
+        expected.finalize(&mut expected_buf);
+        assert_eq!(modulus.get_inscription().unwrap(), expected_buf.to_vec());
+
+        // Negation only flips the sign byte, and zero is never negative
+        assert_ne!((-&modulus).get_inscription().unwrap(), modulus.get_inscription().unwrap());
+        assert_eq!((-BigInt::from(0u32)).get_inscription().unwrap(), BigInt::from(0u32).get_inscription().unwrap());
+    }
+
+    #[derive(Inscribe)]
+    #[inscribe_mark("Context")]
+    #[inscribe_addl(protocol_context)]
+    #[inscribe_addl_bytes(session_context)]
+    struct ProtocolFirst {
+        #[inscribe(serialize)]
+        value: u64,
+    }
+
+    #[derive(Inscribe)]
+    #[inscribe_mark("Context")]
+    #[inscribe_addl_bytes(session_context)]
+    #[inscribe_addl(protocol_context)]
+    struct SessionFirst {
+        #[inscribe(serialize)]
+        value: u64,
+    }
+
+    impl ProtocolFirst {
+        fn protocol_context(&self) -> Result<FSInput, Error> {
+            Ok(ADDL_TEST_DATA.as_bytes().to_vec())
+        }
+
+        fn session_context(&self) -> Result<&[u8], Error> {
+            Ok(MARK_TEST_DATA.as_bytes())
+        }
+    }
+
+    impl SessionFirst {
+        fn protocol_context(&self) -> Result<FSInput, Error> {
+            Ok(ADDL_TEST_DATA.as_bytes().to_vec())
+        }
+
+        fn session_context(&self) -> Result<&[u8], Error> {
+            Ok(MARK_TEST_DATA.as_bytes())
+        }
+    }
+
+    #[test]
+    /// Test that each additional data method is absorbed separately, in attribute order
+    fn test_derive_multiple_addl() {
+        let protocol_first = ProtocolFirst { value: 8675309u64 };
+        let session_first = SessionFirst { value: 8675309u64 };
+
+        let mut hasher = TupleHash::v256("Context".as_bytes());
+        hasher.update(&bcs::to_bytes(&8675309u64).unwrap());
+        hasher.update(ADDL_TEST_DATA.as_bytes());
+        hasher.update(MARK_TEST_DATA.as_bytes());
+        let mut buffer: [u8; INSCRIBE_LENGTH] = [0u8; INSCRIBE_LENGTH];
+        hasher.finalize(&mut buffer);
+        assert_eq!(protocol_first.get_inscription().unwrap(), buffer.to_vec());
+        assert_ne!(protocol_first.get_inscription().unwrap(), session_first.get_inscription().unwrap());
+
+        // `get_additional` frames each source with its length
+        let framed = [
+            &(ADDL_TEST_DATA.len() as u64).to_le_bytes()[..], ADDL_TEST_DATA.as_bytes(),
+            &(MARK_TEST_DATA.len() as u64).to_le_bytes()[..], MARK_TEST_DATA.as_bytes()].concat();
+        assert_eq!(protocol_first.get_additional().unwrap(), framed);
+    }
+
+    /// Two sources of additional data, whose boundary is set at runtime
+    #[derive(Inscribe)]
+    #[inscribe_addl(first_part)]
+    #[inscribe_addl(second_part)]
+    struct SplitContext {
+        #[inscribe(skip)]
+        split: usize,
+    }
+
+    impl SplitContext {
+        fn first_part(&self) -> Result<FSInput, Error> {
+            Ok(b"ab"[..self.split].to_vec())
+        }
+
+        fn second_part(&self) -> Result<FSInput, Error> {
+            Ok(b"ab"[self.split..].to_vec())
+        }
+    }
+
+    #[test]
+    /// Test that different splits of the same additional data are distinguished both by the
+    /// inscription and by `get_additional`
+    fn test_multiple_addl_framing() {
+        let whole = SplitContext { split: 2 };
+        let halves = SplitContext { split: 1 };
+        assert_ne!(whole.get_inscription().unwrap(), halves.get_inscription().unwrap());
+        assert_ne!(whole.get_additional().unwrap(), halves.get_additional().unwrap());
+        assert_eq!(halves.get_additional().unwrap(), [&1u64.to_le_bytes()[..], b"a", &1u64.to_le_bytes()[..], b"b"].concat());
+    }
+
+    /// Hand-written implementations whose additional data is absorbed through the default
+    /// allocating path, and through a streaming override
+    struct AllocatedTag {
+        tag: &'static [u8],
+    }
+
+    struct StreamedTag {
+        tag: &'static [u8],
+    }
+
+    fn inscribe_tag(tag: &dyn Inscribe) -> Result<FSInput, Error> {
+        use decree::backend::{HashBackend, TupleHash as TupleHashBackend};
+        let mut hasher = TupleHashBackend::new_with_domain(tag.get_mark().as_bytes());
+        tag.get_additional_into(&mut hasher)?;
+        let mut buffer: [u8; INSCRIBE_LENGTH] = [0u8; INSCRIBE_LENGTH];
+        hasher.finalize_into(&mut buffer);
+        Ok(buffer.to_vec())
+    }
+
+    impl Inscribe for AllocatedTag {
+        fn get_mark(&self) -> &'static str {
+            "Tag"
+        }
+
+        fn get_inscription(&self) -> Result<FSInput, Error> {
+            inscribe_tag(self)
+        }
+
+        fn get_additional(&self) -> Result<FSInput, Error> {
+            Ok(self.tag.to_vec())
+        }
+    }
+
+    impl Inscribe for StreamedTag {
+        fn get_mark(&self) -> &'static str {
+            "Tag"
+        }
+
+        fn get_inscription(&self) -> Result<FSInput, Error> {
+            inscribe_tag(self)
+        }
+
+        fn get_additional_into(&self, hasher: &mut dyn decree::backend::HashBackend) -> Result<(), Error> {
+            hasher.update(self.tag);
+            Ok(())
+        }
+    }
+
+    #[test]
+    /// Test that streaming additional data gives the same inscription as allocating it
+    fn test_additional_into() {
+        let allocated = AllocatedTag { tag: ADDL_TEST_DATA.as_bytes() };
+        let streamed = StreamedTag { tag: ADDL_TEST_DATA.as_bytes() };
+        assert_eq!(allocated.get_inscription().unwrap(), streamed.get_inscription().unwrap());
+
+        let mut hasher = TupleHash::v256("Tag".as_bytes());
+        hasher.update(ADDL_TEST_DATA.as_bytes());
+        let mut buffer: [u8; INSCRIBE_LENGTH] = [0u8; INSCRIBE_LENGTH];
+        hasher.finalize(&mut buffer);
+        assert_eq!(streamed.get_inscription().unwrap(), buffer.to_vec());
+    }
+
+    /// A hand-written `Inscribe` implementation that behaves like a derived one
+    struct HandWritten {
+        value: u64,
+    }
+
+    impl Inscribe for HandWritten {
+        fn get_mark(&self) -> &'static str {
+            "HandWritten"
+        }
+
+        fn get_inscription(&self) -> Result<FSInput, Error> {
+            let mut hasher = TupleHash::v256(self.get_mark().as_bytes());
+            hasher.update(&self.value.to_le_bytes());
+            let mut buffer: [u8; INSCRIBE_LENGTH] = [0u8; INSCRIBE_LENGTH];
+            hasher.finalize(&mut buffer);
+            Ok(buffer.to_vec())
+        }
+    }
+
+    /// A hand-written `Inscribe` implementation with the wrong inscription length
+    struct ShortInscription;
+
+    impl Inscribe for ShortInscription {
+        fn get_mark(&self) -> &'static str {
+            "ShortInscription"
+        }
+
+        fn get_inscription(&self) -> Result<FSInput, Error> {
+            Ok(vec![0u8; INSCRIBE_LENGTH / 2])
+        }
+    }
+
+    /// A hand-written `Inscribe` implementation that panics
+    struct PanickingInscription;
+
+    impl Inscribe for PanickingInscription {
+        fn get_mark(&self) -> &'static str {
+            "PanickingInscription"
+        }
+
+        fn get_inscription(&self) -> Result<FSInput, Error> {
+            panic!("inscription failed");
+        }
+    }
+
+    #[derive(Inscribe)]
+    struct MixedInner {
+        hand_written: HandWritten,
+        point: Point,
+    }
+
+    /// A hand-written parent of a derived struct with a hand-written member
+    struct MixedOuter {
+        inner: MixedInner,
+    }
+
+    impl Inscribe for MixedOuter {
+        fn get_mark(&self) -> &'static str {
+            "MixedOuter"
+        }
+
+        fn get_inscription(&self) -> Result<FSInput, Error> {
+            let mut hasher = TupleHash::v256(self.get_mark().as_bytes());
+            hasher.update(&self.inner.get_inscription()?);
+            let mut buffer: [u8; INSCRIBE_LENGTH] = [0u8; INSCRIBE_LENGTH];
+            hasher.finalize(&mut buffer);
+            Ok(buffer.to_vec())
+        }
+    }
+
+    #[derive(Inscribe)]
+    struct MixedBadLength {
+        short: ShortInscription,
+        point: Point,
+    }
+
+    #[derive(Inscribe)]
+    struct MixedPanicking {
+        panicking: PanickingInscription,
+    }
+
+    #[test]
+    /// Test that hand-written and derived implementations compose in both directions
+    fn test_mixed_inscribe() {
+        let outer = MixedOuter {
+            inner: MixedInner {
+                hand_written: HandWritten { value: 8675309u64 },
+                point: Point { x: 1i32, y: 2i32 },
+            },
+        };
+
+        let mut tuplehasher_inner = TupleHash::v256("MixedInner".as_bytes());
+        tuplehasher_inner.update(&outer.inner.hand_written.get_inscription().unwrap());
+        tuplehasher_inner.update(&outer.inner.point.get_inscription().unwrap());
+        tuplehasher_inner.update(&[]);
+        let mut buffer_inner: [u8; INSCRIBE_LENGTH] = [0u8; INSCRIBE_LENGTH];
+        tuplehasher_inner.finalize(&mut buffer_inner);
+        assert_eq!(outer.inner.get_inscription().unwrap(), buffer_inner.to_vec());
+
+        let mut tuplehasher_outer = TupleHash::v256("MixedOuter".as_bytes());
+        tuplehasher_outer.update(&buffer_inner);
+        let mut buffer_outer: [u8; INSCRIBE_LENGTH] = [0u8; INSCRIBE_LENGTH];
+        tuplehasher_outer.finalize(&mut buffer_outer);
+        assert_eq!(outer.get_inscription().unwrap(), buffer_outer.to_vec());
+    }
+
+    #[test]
+    /// Test that a member inscription of the wrong length is rejected
+    fn test_mixed_bad_length() {
+        let bad = MixedBadLength { short: ShortInscription, point: Point { x: 1i32, y: 2i32 } };
+        let err = bad.get_inscription().unwrap_err();
+        assert_eq!(err.get_str(), "Invalid inscription length");
+    }
+
+    #[test]
+    #[should_panic(expected = "inscription failed")]
+    /// Test that a panic in a member inscription propagates
+    fn test_mixed_panicking() {
+        let panicking = MixedPanicking { panicking: PanickingInscription };
+        let _ = panicking.get_inscription();
+    }
+
+    #[derive(Inscribe)]
+    #[inscribe_mark(format_mark)]
+    struct LegacyFormat {
+        #[inscribe(serialize)]
+        value: u64,
+    }
+
+    #[derive(Inscribe)]
+    #[inscribe_mark(format_mark)]
+    struct ExtendedFormat {
+        #[inscribe(serialize)]
+        value: u64,
+        #[inscribe(skip_if_empty)]
+        #[inscribe_name(zz_extras)]
+        extras: Vec<u32>,
+    }
+
+    impl LegacyFormat {
+        fn format_mark(&self) -> &'static str {
+            "Format"
+        }
+    }
+
+    impl ExtendedFormat {
+        fn format_mark(&self) -> &'static str {
+            "Format"
+        }
+    }
+
+    #[test]
+    /// Test that an empty `skip_if_empty` member is left out, and a non-empty one is serialized
+    fn test_derive_skip_if_empty() {
+        let legacy = LegacyFormat { value: 8675309u64 };
+        let empty = ExtendedFormat { value: 8675309u64, extras: Vec::new() };
+        assert_eq!(empty.get_inscription().unwrap(), legacy.get_inscription().unwrap());
+
+        let extended = ExtendedFormat { value: 8675309u64, extras: vec![1u32, 2u32] };
+        let mut hasher = TupleHash::v256("Format".as_bytes());
+        hasher.update(&bcs::to_bytes(&8675309u64).unwrap());
+        hasher.update(&bcs::to_bytes(&vec![1u32, 2u32]).unwrap());
+        hasher.update(&[]);
+        let mut buffer: [u8; INSCRIBE_LENGTH] = [0u8; INSCRIBE_LENGTH];
+        hasher.finalize(&mut buffer);
+        assert_eq!(extended.get_inscription().unwrap(), buffer.to_vec());
+        assert_ne!(extended.get_inscription().unwrap(), legacy.get_inscription().unwrap());
+    }
+
+    #[derive(Inscribe)]
+    #[inscribe_mark("pedersen-commitment v1")]
+    struct LiteralNames {
+        #[inscribe(serialize)]
+        #[inscribe_name("commitment B")]
+        first: u32,
+        #[inscribe(serialize)]
+        #[inscribe_name = "commitment A"]
+        second: u32,
+    }
+
+    #[test]
+    /// Test that string literals can be used as sort names and marks
+    fn test_derive_literal_names() {
+        let literal = LiteralNames { first: 1u32, second: 2u32 };
+        assert_eq!(literal.get_mark(), "pedersen-commitment v1");
+
+        let mut hasher = TupleHash::v256("pedersen-commitment v1".as_bytes());
+        hasher.update(&bcs::to_bytes(&2u32).unwrap());
+        hasher.update(&bcs::to_bytes(&1u32).unwrap());
+        hasher.update(&[]);
+        let mut buffer: [u8; INSCRIBE_LENGTH] = [0u8; INSCRIBE_LENGTH];
+        hasher.finalize(&mut buffer);
+        assert_eq!(literal.get_inscription().unwrap(), buffer.to_vec());
+    }
+
+    #[derive(Inscribe)]
+    struct FixedArity {
+        commitments: [Point; 2],
+    }
+
+    #[derive(Inscribe)]
+    struct VariableArity {
+        commitments: Vec<Point>,
+    }
+
+    #[test]
+    /// Test that a fixed-size array member is inscribed without a length prefix, unlike a `Vec`
+    fn test_derive_array_member() {
+        let fixed = FixedArity { commitments: [Point { x: 1i32, y: 2i32 }, Point { x: 3i32, y: 4i32 }] };
+        let variable = VariableArity { commitments: vec![Point { x: 1i32, y: 2i32 }, Point { x: 3i32, y: 4i32 }] };
+
+        let a = fixed.commitments[0].get_inscription().unwrap();
+        let b = fixed.commitments[1].get_inscription().unwrap();
+        let mut hasher = TupleHash::v256("array".as_bytes());
+        hasher.update(&a);
+        hasher.update(&b);
+        let mut buffer: [u8; INSCRIBE_LENGTH] = [0u8; INSCRIBE_LENGTH];
+        hasher.finalize(&mut buffer);
+        assert_eq!(fixed.commitments.get_inscription().unwrap(), buffer.to_vec());
+
+        let mut hasher = TupleHash::v256("vec".as_bytes());
+        hasher.update(&2u64.to_le_bytes());
+        hasher.update(&a);
+        hasher.update(&b);
+        hasher.finalize(&mut buffer);
+        assert_eq!(variable.commitments.get_inscription().unwrap(), buffer.to_vec());
+
+        assert_ne!(fixed.commitments.get_inscription().unwrap(), variable.commitments.get_inscription().unwrap());
+        assert_ne!(fixed.get_inscription().unwrap(), variable.get_inscription().unwrap());
+    }
+
+    #[derive(Inscribe)]
+    #[inscribe_mark("Layout")]
+    struct SortedLayout {
+        #[inscribe(serialize)]
+        zeta: u32,
+        #[inscribe(serialize)]
+        alpha: u32,
+    }
+
+    #[derive(Inscribe)]
+    #[inscribe(ordered)]
+    #[inscribe_mark("Layout")]
+    struct DeclaredLayout {
+        #[inscribe(serialize)]
+        zeta: u32,
+        #[inscribe(serialize)]
+        alpha: u32,
+    }
+
+    #[test]
+    /// Test that `#[inscribe(ordered)]` absorbs members in declaration order rather than sorting
+    fn test_derive_ordered() {
+        let sorted = SortedLayout { zeta: 1u32, alpha: 2u32 };
+        let declared = DeclaredLayout { zeta: 1u32, alpha: 2u32 };
+
+        let mut hasher = TupleHash::v256("Layout".as_bytes());
+        hasher.update(&bcs::to_bytes(&1u32).unwrap());
+        hasher.update(&bcs::to_bytes(&2u32).unwrap());
+        hasher.update(&[]);
+        let mut buffer: [u8; INSCRIBE_LENGTH] = [0u8; INSCRIBE_LENGTH];
+        hasher.finalize(&mut buffer);
+        assert_eq!(declared.get_inscription().unwrap(), buffer.to_vec());
+        assert_ne!(sorted.get_inscription().unwrap(), declared.get_inscription().unwrap());
+    }
+
+    #[derive(Inscribe)]
+    #[inscribe(bind_names)]
+    #[inscribe_mark("Account")]
+    struct BoundBalance {
+        #[inscribe(serialize)]
+        balance: u64,
+    }
+
+    #[derive(Inscribe)]
+    #[inscribe(bind_names)]
+    #[inscribe_mark("Account")]
+    struct BoundNonce {
+        #[inscribe(serialize)]
+        #[inscribe_name(nonce)]
+        balance: u64,
+    }
+
+    #[derive(Inscribe)]
+    #[inscribe_mark("Account")]
+    struct UnboundBalance {
+        #[inscribe(serialize)]
+        balance: u64,
+    }
+
+    #[derive(Inscribe)]
+    #[inscribe_mark("Account")]
+    struct UnboundNonce {
+        #[inscribe(serialize)]
+        #[inscribe_name(nonce)]
+        balance: u64,
+    }
+
+    #[test]
+    /// Test that `#[inscribe(bind_names)]` absorbs each member's sort name ahead of its value, so
+    /// renaming a member changes the inscription
+    fn test_derive_bind_names() {
+        let bound = BoundBalance { balance: 7u64 };
+
+        let mut hasher = TupleHash::v256("Account".as_bytes());
+        hasher.update(b"balance");
+        hasher.update(&bcs::to_bytes(&7u64).unwrap());
+        hasher.update(&[]);
+        let mut buffer: [u8; INSCRIBE_LENGTH] = [0u8; INSCRIBE_LENGTH];
+        hasher.finalize(&mut buffer);
+        assert_eq!(bound.get_inscription().unwrap(), buffer.to_vec());
+
+        // Without `bind_names`, only the value counts
+        assert_eq!(UnboundBalance { balance: 7u64 }.get_inscription().unwrap(),
+                   UnboundNonce { balance: 7u64 }.get_inscription().unwrap());
+        assert_ne!(bound.get_inscription().unwrap(), BoundNonce { balance: 7u64 }.get_inscription().unwrap());
+        assert_ne!(bound.get_inscription().unwrap(), UnboundBalance { balance: 7u64 }.get_inscription().unwrap());
+    }
+
+    #[derive(Inscribe)]
+    struct PointPair {
+        first: Point,
+        second: Point,
+    }
+
+    #[test]
+    /// Test that tuples are inscribed positionally, and differ from a struct with the same members
+    fn test_tuple_inscribe() {
+        let a = Point { x: 1i32, y: 2i32 };
+        let b = Point { x: 3i32, y: 4i32 };
+        let tuple = (Point { x: 1i32, y: 2i32 }, Point { x: 3i32, y: 4i32 });
+        let swapped = (Point { x: 3i32, y: 4i32 }, Point { x: 1i32, y: 2i32 });
+        let pair = PointPair { first: Point { x: 1i32, y: 2i32 }, second: Point { x: 3i32, y: 4i32 } };
+
+        let mut hasher = TupleHash::v256("tuple".as_bytes());
+        hasher.update(&a.get_inscription().unwrap());
+        hasher.update(&b.get_inscription().unwrap());
+        let mut buffer: [u8; INSCRIBE_LENGTH] = [0u8; INSCRIBE_LENGTH];
+        hasher.finalize(&mut buffer);
+        assert_eq!(tuple.get_inscription().unwrap(), buffer.to_vec());
+
+        assert_ne!(tuple.get_inscription().unwrap(), swapped.get_inscription().unwrap());
+        assert_ne!(tuple.get_inscription().unwrap(), pair.get_inscription().unwrap());
+    }
+
+    #[derive(Inscribe)]
+    struct PartyCommitments {
+        ordered: std::collections::BTreeMap<u32, Point>,
+        unordered: std::collections::HashMap<u32, Point>,
+    }
+
+    #[test]
+    /// Test that maps inscribe the same regardless of the order entries were inserted in
+    fn test_map_insertion_order() {
+        use std::collections::{BTreeMap, HashMap};
+        let build = |parties: &[u32]| PartyCommitments {
+            ordered: parties.iter().map(|i| (*i, Point { x: *i as i32, y: 0i32 })).collect::<BTreeMap<_, _>>(),
+            unordered: parties.iter().map(|i| (*i, Point { x: *i as i32, y: 0i32 })).collect::<HashMap<_, _>>(),
+        };
+        let forward = build(&[1u32, 2u32, 3u32, 4u32]);
+        let backward = build(&[4u32, 3u32, 2u32, 1u32]);
+        let missing = build(&[1u32, 2u32, 3u32]);
+
+        assert_eq!(forward.ordered.get_inscription().unwrap(), backward.ordered.get_inscription().unwrap());
+        assert_eq!(forward.unordered.get_inscription().unwrap(), backward.unordered.get_inscription().unwrap());
+        assert_eq!(forward.get_inscription().unwrap(), backward.get_inscription().unwrap());
+        assert_ne!(forward.get_inscription().unwrap(), missing.get_inscription().unwrap());
+    }
+
+    #[derive(Inscribe)]
+    struct Unserializable {
+        #[inscribe(serialize)]
+        value: f64,
+    }
+
+    #[test]
+    /// Test that a member that can't be serialized gives a serialization error
+    fn test_derive_serialization_error() {
+        use decree::error::DecreeErrType;
+        let unserializable = Unserializable { value: 1.5f64 };
+        let err = unserializable.get_inscription().unwrap_err();
+        assert_eq!(err.get_type(), DecreeErrType::Serialization);
+        assert!(err.get_str().contains("value"));
+    }
+
+    #[test]
+    #[cfg(feature = "either")]
+    /// Test that the two sides of an `Either` are domain-separated
+    fn test_either_inscribe() {
+        use either::Either;
+        let left: Either<Point, Point> = Either::Left(Point { x: 1i32, y: 2i32 });
+        let right: Either<Point, Point> = Either::Right(Point { x: 1i32, y: 2i32 });
+        let left_again: Either<Point, Point> = Either::Left(Point { x: 1i32, y: 2i32 });
+
+        let left_inscription = left.get_inscription().unwrap();
+        assert_ne!(left_inscription, right.get_inscription().unwrap());
+        assert_eq!(left_inscription, left_again.get_inscription().unwrap());
+
+        // The tag and inner inscription are hashed under the `either` mark
+        let mut tuplehasher = TupleHash::v256("either".as_bytes());
+        tuplehasher.update(&[0u8]);
+        tuplehasher.update(&Point { x: 1i32, y: 2i32 }.get_inscription().unwrap());
+        let mut buffer: [u8; INSCRIBE_LENGTH] = [0u8; INSCRIBE_LENGTH];
+        tuplehasher.finalize(&mut buffer);
+        assert_eq!(left_inscription, buffer.to_vec());
+    }
+
+    /// Big-endian encoders, in place of `bcs`
+    mod encoders {
+        use decree::error::Error;
+
+        pub fn big_endian(value: &u64) -> Result<Vec<u8>, Error> {
+            Ok(value.to_be_bytes().to_vec())
+        }
+
+        pub fn big_endian_32(value: &u32) -> Result<Vec<u8>, Error> {
+            Ok(value.to_be_bytes().to_vec())
+        }
+    }
+
+    /// A struct with custom-encoded members
+    #[derive(Inscribe)]
+    struct CustomEncoded {
+        #[inscribe(serialize_with = encoders::big_endian)]
+        counter: u64,
+        #[inscribe(serialize_with = "encoders::big_endian_32")]
+        index: u32,
+    }
+
+    #[test]
+    /// Test that `serialize_with` members are absorbed using the given function
+    fn test_derive_serialize_with() {
+        let custom = CustomEncoded { counter: 0x0102030405060708u64, index: 9u32 };
+
+        // `counter` sorts ahead of `index`
+        let mut tuplehasher = TupleHash::v256("CustomEncoded".as_bytes());
+        tuplehasher.update(&[1u8, 2u8, 3u8, 4u8, 5u8, 6u8, 7u8, 8u8]);
+        tuplehasher.update(&[0u8, 0u8, 0u8, 9u8]);
+        tuplehasher.update(&[]);
+        let mut buffer: [u8; INSCRIBE_LENGTH] = [0u8; INSCRIBE_LENGTH];
+        tuplehasher.finalize(&mut buffer);
+        assert_eq!(custom.get_inscription().unwrap(), buffer.to_vec());
+    }
+
+    /// A struct whose `sum` member is computed rather than stored
+    #[derive(Inscribe)]
+    struct ComputedTest {
+        a: Point,
+        b: Point,
+        #[inscribe(compute_with = compute_sum)]
+        #[allow(dead_code)]
+        sum: (),
+    }
+
+    impl ComputedTest {
+        fn compute_sum(&self) -> Point {
+            Point { x: self.a.x + self.b.x, y: self.a.y + self.b.y }
+        }
+    }
+
+    #[test]
+    /// Test that `compute_with` members are inscribed at their sort position, and that the
+    /// inscription tracks the computed value.
+    fn test_derive_compute_with() {
+        let computed = ComputedTest {
+            a: Point { x: 1i32, y: 2i32 },
+            b: Point { x: 3i32, y: 4i32 },
+            sum: (),
+        };
+        let inscript_auto = computed.get_inscription().unwrap();
+
+        // `a`, `b`, and `sum` are already in sorted order
+        let mut tuplehasher = TupleHash::v256("ComputedTest".as_bytes());
+        tuplehasher.update(&computed.a.get_inscription().unwrap());
+        tuplehasher.update(&computed.b.get_inscription().unwrap());
+        tuplehasher.update(&computed.compute_sum().get_inscription().unwrap());
+        tuplehasher.update(&[]);
+        let mut buffer: [u8; INSCRIBE_LENGTH] = [0u8; INSCRIBE_LENGTH];
+        tuplehasher.finalize(&mut buffer);
+        assert_eq!(inscript_auto, buffer.to_vec());
+
+        // Changing an input to the computation changes the inscription
+        let altered = ComputedTest {
+            a: Point { x: 1i32, y: 2i32 },
+            b: Point { x: 3i32, y: 5i32 },
+            sum: (),
+        };
+        assert_ne!(inscript_auto, altered.get_inscription().unwrap());
+    }
+
+    /// An enum with unit, struct-like, and identically-shaped variants
+    #[derive(Inscribe)]
+    enum ProofMode {
+        Interactive,
+        NonInteractive,
+        Opening {
+            #[inscribe(serialize)]
+            value: u32,
+            point: Point,
+        },
+        Reopening {
+            #[inscribe(serialize)]
+            value: u32,
+            point: Point,
+        },
+        Tagged(#[inscribe(serialize)] u32, Point),
+    }
+
+    #[test]
+    /// Test that different variants of the same enum yield different inscriptions
+    fn test_derive_enum() {
+        let interactive = ProofMode::Interactive.get_inscription().unwrap();
+        let non_interactive = ProofMode::NonInteractive.get_inscription().unwrap();
+        assert_ne!(interactive, non_interactive);
+
+        let opening = ProofMode::Opening { value: 7u32, point: Point { x: 1i32, y: 2i32 } };
+        let reopening = ProofMode::Reopening { value: 7u32, point: Point { x: 1i32, y: 2i32 } };
+        let opening_inscription = opening.get_inscription().unwrap();
+        assert_ne!(opening_inscription, reopening.get_inscription().unwrap());
+
+        // Unit variants hash their index and name
+        let mut tuplehasher = TupleHash::v256("ProofMode".as_bytes());
+        tuplehasher.update(&1u32.to_le_bytes());
+        tuplehasher.update("NonInteractive".as_bytes());
+        tuplehasher.update(&[]);
+        let mut buffer: [u8; INSCRIBE_LENGTH] = [0u8; INSCRIBE_LENGTH];
+        tuplehasher.finalize(&mut buffer);
+        assert_eq!(non_interactive, buffer.to_vec());
+
+        // Fields follow the index and name, in sorted order
+        let mut tuplehasher = TupleHash::v256("ProofMode".as_bytes());
+        tuplehasher.update(&2u32.to_le_bytes());
+        tuplehasher.update("Opening".as_bytes());
+        tuplehasher.update(&Point { x: 1i32, y: 2i32 }.get_inscription().unwrap());
+        tuplehasher.update(&bcs::to_bytes(&7u32).unwrap());
+        tuplehasher.update(&[]);
+        let mut buffer: [u8; INSCRIBE_LENGTH] = [0u8; INSCRIBE_LENGTH];
+        tuplehasher.finalize(&mut buffer);
+        assert_eq!(opening_inscription, buffer.to_vec());
+
+        // Tuple variants keep their positional order
+        let tagged = ProofMode::Tagged(7u32, Point { x: 1i32, y: 2i32 });
+        let mut tuplehasher = TupleHash::v256("ProofMode".as_bytes());
+        tuplehasher.update(&4u32.to_le_bytes());
+        tuplehasher.update("Tagged".as_bytes());
+        tuplehasher.update(&bcs::to_bytes(&7u32).unwrap());
+        tuplehasher.update(&Point { x: 1i32, y: 2i32 }.get_inscription().unwrap());
+        tuplehasher.update(&[]);
+        let mut buffer: [u8; INSCRIBE_LENGTH] = [0u8; INSCRIBE_LENGTH];
+        tuplehasher.finalize(&mut buffer);
+        assert_eq!(tagged.get_inscription().unwrap(), buffer.to_vec());
+    }
+
+    #[test]
+    #[cfg(feature = "describe")]
+    /// Test the description of an enum
+    fn test_derive_enum_describe() {
+        assert_eq!(
+            ProofMode::Interactive.describe(),
+            "ProofMode\n  variant 0 Interactive (Interactive)\n  variant 1 NonInteractive (NonInteractive)\n  \
+             variant 2 Opening (Opening)\n    point (point): recurse\n    value (value): serialize\n  \
+             variant 3 Reopening (Reopening)\n    point (point): recurse\n    value (value): serialize\n  \
+             variant 4 Tagged (Tagged)\n    0 (0): serialize\n    1 (1): recurse\n  \
+             additional: none\n");
+    }
+
+    /// An enum whose variant tags are fixed by an external specification
+    #[derive(Inscribe)]
+    enum WireMessage {
+        #[inscribe_discriminant(7)]
+        Hello,
+        Data(#[inscribe(serialize)] u32),
+        #[inscribe_discriminant(0x20)]
+        #[inscribe_name("goodbye")]
+        Goodbye,
+        #[inscribe_discriminant(0x21)]
+        #[inscribe_name("")]
+        Ping,
+    }
+
+    #[test]
+    /// Test that explicit discriminants are hashed in place of the declaration index
+    fn test_derive_enum_discriminant() {
+        let expected = |discriminant: u32, name: &str, fields: &[Vec<u8>]| {
+            let mut tuplehasher = TupleHash::v256("WireMessage".as_bytes());
+            tuplehasher.update(&discriminant.to_le_bytes());
+            tuplehasher.update(name.as_bytes());
+            for field in fields {
+                tuplehasher.update(field);
+            }
+            tuplehasher.update(&[]);
+            let mut buffer: [u8; INSCRIBE_LENGTH] = [0u8; INSCRIBE_LENGTH];
+            tuplehasher.finalize(&mut buffer);
+            buffer.to_vec()
+        };
+
+        assert_eq!(WireMessage::Hello.get_inscription().unwrap(), expected(7, "Hello", &[]));
+        // Variants without the attribute keep their index
+        assert_eq!(WireMessage::Data(3u32).get_inscription().unwrap(),
+                   expected(1, "Data", &[bcs::to_bytes(&3u32).unwrap()]));
+        // A variant's name can be replaced, or emptied so that only its tag is significant
+        assert_eq!(WireMessage::Goodbye.get_inscription().unwrap(), expected(0x20, "goodbye", &[]));
+        assert_eq!(WireMessage::Ping.get_inscription().unwrap(), expected(0x21, "", &[]));
+    }
+
+    #[test]
+    #[cfg(feature = "describe")]
+    /// Test that the description of an enum lists each variant's discriminant and hashed name
+    fn test_derive_enum_discriminant_describe() {
+        assert_eq!(
+            WireMessage::Hello.describe(),
+            "WireMessage\n  variant 7 Hello (Hello)\n  variant 1 Data (Data)\n    0 (0): serialize\n  \
+             variant 32 Goodbye (goodbye)\n  variant 33 Ping ()\n  additional: none\n");
+    }
+
+    /// A newtype wrapper around a foreign type
+    #[derive(Inscribe)]
+    struct Wrapped(#[inscribe(serialize)] u64);
+
+    /// A tuple struct whose members are inscribed in positional order
+    #[derive(Inscribe)]
+    struct Triple(Point, #[inscribe(serialize)] u32, Point);
+
+    /// A tuple struct that renames one of its members
+    #[derive(Inscribe)]
+    struct RenamedTriple(Point, #[inscribe(serialize)] #[inscribe_name(a)] u32, Point);
+
+    #[test]
+    /// Test that newtype wrappers forward to their single member
+    fn test_derive_newtype() {
+        let wrapped = Wrapped(42u64);
+        let mut tuplehasher = TupleHash::v256("Wrapped".as_bytes());
+        tuplehasher.update(&bcs::to_bytes(&42u64).unwrap());
+        tuplehasher.update(&[]);
+        let mut buffer: [u8; INSCRIBE_LENGTH] = [0u8; INSCRIBE_LENGTH];
+        tuplehasher.finalize(&mut buffer);
+        assert_eq!(wrapped.get_inscription().unwrap(), buffer.to_vec());
+    }
+
+    #[test]
+    /// Test that tuple struct members keep their positional order, and that named members come
+    /// after positional ones
+    fn test_derive_tuple_struct() {
+        let p0 = Point { x: 1i32, y: 2i32 };
+        let p2 = Point { x: 3i32, y: 4i32 };
+
+        let triple = Triple(Point { x: 1i32, y: 2i32 }, 5u32, Point { x: 3i32, y: 4i32 });
+        let mut tuplehasher = TupleHash::v256("Triple".as_bytes());
+        tuplehasher.update(&p0.get_inscription().unwrap());
+        tuplehasher.update(&bcs::to_bytes(&5u32).unwrap());
+        tuplehasher.update(&p2.get_inscription().unwrap());
+        tuplehasher.update(&[]);
+        let mut buffer: [u8; INSCRIBE_LENGTH] = [0u8; INSCRIBE_LENGTH];
+        tuplehasher.finalize(&mut buffer);
+        assert_eq!(triple.get_inscription().unwrap(), buffer.to_vec());
+
+        // Swapping the positional members changes the inscription
+        let swapped = Triple(Point { x: 3i32, y: 4i32 }, 5u32, Point { x: 1i32, y: 2i32 });
+        assert_ne!(triple.get_inscription().unwrap(), swapped.get_inscription().unwrap());
+
+        let renamed = RenamedTriple(Point { x: 1i32, y: 2i32 }, 5u32, Point { x: 3i32, y: 4i32 });
+        let mut tuplehasher = TupleHash::v256("RenamedTriple".as_bytes());
+        tuplehasher.update(&p0.get_inscription().unwrap());
+        tuplehasher.update(&p2.get_inscription().unwrap());
+        tuplehasher.update(&bcs::to_bytes(&5u32).unwrap());
+        tuplehasher.update(&[]);
+        let mut buffer: [u8; INSCRIBE_LENGTH] = [0u8; INSCRIBE_LENGTH];
+        tuplehasher.finalize(&mut buffer);
+        assert_eq!(renamed.get_inscription().unwrap(), buffer.to_vec());
+    }
+
+    /// A struct with raw byte members
+    #[derive(Inscribe)]
+    struct BytesTest {
+        #[inscribe(bytes)]
+        digest: [u8; 32],
+        #[inscribe(bytes)]
+        encoded: Vec<u8>,
+    }
+
+    #[test]
+    /// Test that `bytes` members are added to the TupleHash verbatim
+    fn test_derive_bytes() {
+        let bytes_test = BytesTest { digest: [7u8; 32], encoded: vec![1u8, 2u8, 3u8] };
+
+        let mut tuplehasher = TupleHash::v256("BytesTest".as_bytes());
+        tuplehasher.update(&[7u8; 32]);
+        tuplehasher.update(&[1u8, 2u8, 3u8]);
+        tuplehasher.update(&[]);
+        let mut buffer: [u8; INSCRIBE_LENGTH] = [0u8; INSCRIBE_LENGTH];
+        tuplehasher.finalize(&mut buffer);
+        assert_eq!(bytes_test.get_inscription().unwrap(), buffer.to_vec());
+    }
+
+    /// A struct with a shorter inscription
+    #[derive(Inscribe)]
+    #[inscribe_length(32)]
+    struct ShortPoint {
+        #[inscribe(serialize)]
+        x: i32,
+        #[inscribe(serialize)]
+        y: i32,
+    }
+
+    /// A struct with the default inscription length that contains a shorter one
+    #[derive(Inscribe)]
+    struct ShortParent {
+        short: ShortPoint,
+    }
+
+    #[test]
+    /// Test that `inscribe_length` sets the TupleHash output length, and that members of
+    /// different lengths compose
+    fn test_derive_inscribe_length() {
+        let short = ShortPoint { x: 1i32, y: 2i32 };
+        let inscription = short.get_inscription().unwrap();
+        assert_eq!(short.inscription_length(), 32);
+
+        let mut tuplehasher = TupleHash::v256("ShortPoint".as_bytes());
+        tuplehasher.update(&bcs::to_bytes(&1i32).unwrap());
+        tuplehasher.update(&bcs::to_bytes(&2i32).unwrap());
+        tuplehasher.update(&[]);
+        let mut buffer: [u8; 32] = [0u8; 32];
+        tuplehasher.finalize(&mut buffer);
+        assert_eq!(inscription, buffer.to_vec());
+
+        let parent = ShortParent { short: ShortPoint { x: 1i32, y: 2i32 } };
+        let mut tuplehasher = TupleHash::v256("ShortParent".as_bytes());
+        tuplehasher.update(&inscription);
+        tuplehasher.update(&[]);
+        let mut buffer: [u8; INSCRIBE_LENGTH] = [0u8; INSCRIBE_LENGTH];
+        tuplehasher.finalize(&mut buffer);
+        assert_eq!(parent.get_inscription().unwrap(), buffer.to_vec());
+    }
+
+    /// A struct with a longer inscription that contains a shorter one and a default-length one
+    #[derive(Inscribe)]
+    #[inscribe_length(128)]
+    struct LongParent {
+        parent: ShortParent,
+        short: ShortPoint,
+    }
+
+    #[test]
+    /// Test that a child's inscription is absorbed at its own length, regardless of the length of
+    /// the parent
+    fn test_derive_inscribe_length_nested() {
+        let short = ShortPoint { x: 3i32, y: 4i32 };
+        let short_inscription = short.get_inscription().unwrap();
+        let parent = ShortParent { short: ShortPoint { x: 1i32, y: 2i32 } };
+        let parent_inscription = parent.get_inscription().unwrap();
+        assert_eq!(short_inscription.len(), 32);
+        assert_eq!(parent_inscription.len(), INSCRIBE_LENGTH);
+
+        let long = LongParent { parent, short };
+        assert_eq!(long.inscription_length(), 128);
+
+        let mut tuplehasher = TupleHash::v256("LongParent".as_bytes());
+        tuplehasher.update(&parent_inscription);
+        tuplehasher.update(&short_inscription);
+        tuplehasher.update(&[]);
+        let mut buffer: [u8; 128] = [0u8; 128];
+        tuplehasher.finalize(&mut buffer);
+        assert_eq!(long.get_inscription().unwrap(), buffer.to_vec());
+
+        // Streaming into a parent's hasher absorbs the same bytes as `get_inscription`
+        use decree::backend::{HashBackend, TupleHash as TupleHashBackend};
+        let mut streamed = TupleHashBackend::new_with_domain(b"outer");
+        long.get_inscription_into(&mut streamed).unwrap();
+        let mut streamed_buf = [0u8; 32];
+        streamed.finalize_into(&mut streamed_buf);
+
+        let mut expected = TupleHash::v256(b"outer");
+        expected.update(&buffer);
+        let mut expected_buf = [0u8; 32];
+        expected.finalize(&mut expected_buf);
+        assert_eq!(streamed_buf, expected_buf);
+    }
+
+    #[test]
+    /// Test that values with different inscription lengths can be used together as trait objects
+    fn test_inscribe_length_dyn() {
+        let short = ShortPoint { x: 1i32, y: 2i32 };
+        let parent = ShortParent { short: ShortPoint { x: 1i32, y: 2i32 } };
+        let long = LongParent {
+            parent: ShortParent { short: ShortPoint { x: 1i32, y: 2i32 } },
+            short: ShortPoint { x: 3i32, y: 4i32 },
+        };
+        let values: [&dyn Inscribe; 3] = [&short, &parent, &long];
+        let lengths: Vec<usize> = values.iter()
+            .map(|value| value.get_inscription().unwrap().len())
+            .collect();
+        assert_eq!(lengths, vec![32, INSCRIBE_LENGTH, 128]);
+        for value in values {
+            assert_eq!(value.get_inscription().unwrap().len(), value.inscription_length());
+        }
+    }
+
+    #[cfg(feature = "blake3")]
+    #[derive(Inscribe)]
+    #[inscribe_backend(Blake3)]
+    struct Blake3Point {
+        #[inscribe(serialize)]
+        x: i32,
+        #[inscribe(serialize)]
+        y: i32,
+    }
+
+    #[test]
+    #[cfg(feature = "blake3")]
+    /// Test that the BLAKE3 backend frames each item with its length
+    fn test_derive_blake3_backend() {
+        let point = Blake3Point { x: 1i32, y: 2i32 };
+
+        let mut hasher = blake3::Hasher::new();
+        for item in [b"Blake3Point".to_vec(), bcs::to_bytes(&1i32).unwrap(),
+                     bcs::to_bytes(&2i32).unwrap(), Vec::new()] {
+            hasher.update(&(item.len() as u64).to_le_bytes());
+            hasher.update(&item);
+        }
+        hasher.update(&(INSCRIBE_LENGTH as u64).to_le_bytes());
+        let mut buffer: [u8; INSCRIBE_LENGTH] = [0u8; INSCRIBE_LENGTH];
+        hasher.finalize_xof().fill(&mut buffer);
+        assert_eq!(point.get_inscription().unwrap(), buffer.to_vec());
+    }
+
+    #[derive(Inscribe)]
+    struct Commitments {
+        #[inscribe(serialize)]
+        modulus: BigInt,
+        #[inscribe(serialize)]
+        elements: Vec<BigInt>,
+    }
+
+    #[test]
+    /// Test that `add` and `add_serial` take borrowed inputs, leaving them usable afterwards
+    fn test_add_by_reference() {
+        use decree::decree::Decree;
+        let commitments = Commitments {
+            modulus: BigInt::from(2u32).pow(127) - BigInt::from(1u32),
+            elements: (0u32..64).map(|i| BigInt::from(43u32).pow(i)).collect(),
+        };
+
+        let challenge = |borrowed: bool| -> [u8; 32] {
+            let mut transcript = Decree::new(
+                "by reference",
+                vec!["commitments", "modulus"].as_slice(),
+                vec!["challenge"].as_slice()).unwrap();
+            transcript.add("commitments", &commitments).unwrap();
+            if borrowed {
+                transcript.add_serial("modulus", &commitments.modulus).unwrap();
+            } else {
+                transcript.add_serial("modulus", commitments.modulus.clone()).unwrap();
+            }
+            let mut challenge_bytes: [u8; 32] = [0u8; 32];
+            transcript.get_challenge("challenge", &mut challenge_bytes).unwrap();
+            challenge_bytes
+        };
+
+        assert_eq!(challenge(true), challenge(false));
+        assert_eq!(commitments.elements.len(), 64);
+    }
+
     #[test]
     /// This is an example of how to use Decree to do a Girault proof. Note that this code is for
     /// illustrative purposes, not for production use.
@@ -184,6 +1459,61 @@ mod tests {
         assert_eq!(u_verify, check);
     }
 
+    /// A Girault proof value, inscribed as its serialization
+    #[derive(Inscribe)]
+    struct GiraultValue(#[inscribe(serialize)] num_bigint::BigUint);
+
+    #[test]
+    /// The Girault proof again, with the verifier rebuilding the transcript with `replay` rather
+    /// than repeating the prover's sequence of `add` calls.
+    fn test_girault_replay() {
+        use decree::decree::{Decree, InputLabel, ChallengeLabel};
+        use num_bigint::{BigUint, RandBigInt};
+
+        let inputs: [InputLabel; 4] = ["g", "N", "h", "u"];
+        let challenges: [ChallengeLabel; 1] = ["e"];
+        let one = BigUint::from(1u32);
+        let x = BigUint::from(8675309u32);
+        let p: BigUint = BigUint::parse_bytes(
+            b"e955c307804136f22408b416ebc081ae\
+              c8d940e1ebd790cbe128485b15a8064d\
+              5015e2b4c0058d403670a8cfa00fe1ad\
+              866312656e740e58b566fa4eddde2883", 16).unwrap();
+        let q: BigUint = BigUint::parse_bytes(
+            b"d608e1552a96613570afb9e7291b2916\
+              2ad18868e2f7aedeba2b321d13ab2b79\
+              99a1e449e433c5947af5194471e84ce0\
+              d34b30b761004c8efdad598771b37e13", 16).unwrap();
+
+        // Prover
+        let n = GiraultValue(&p * &q);
+        let g = GiraultValue(BigUint::from(2u32));
+        let g_inv = (&n.0 + &one) / &g.0;
+        let h = GiraultValue(g_inv.modpow(&x, &n.0));
+        let r = rand::thread_rng().gen_biguint(1024);
+        let u = GiraultValue(g.0.modpow(&r, &n.0));
+
+        let mut transcript = Decree::new("girault", &inputs, &challenges).unwrap();
+        transcript.add("N", &n).unwrap();
+        transcript.add("g", &g).unwrap();
+        transcript.add("h", &h).unwrap();
+        transcript.add("u", &u).unwrap();
+        let mut prover_challenge_bytes: [u8; 128] = [0u8; 128];
+        transcript.get_challenge("e", &mut prover_challenge_bytes).unwrap();
+        let z = r + (x * BigUint::from_bytes_le(&prover_challenge_bytes));
+
+        // Verifier
+        let mut transcript_verify = Decree::replay("girault", &inputs, &challenges,
+            &[("N", &n), ("g", &g), ("h", &h), ("u", &u)]).unwrap();
+        let mut verifier_challenge_bytes: [u8; 128] = [0u8; 128];
+        transcript_verify.get_challenge("e", &mut verifier_challenge_bytes).unwrap();
+        assert_eq!(verifier_challenge_bytes, prover_challenge_bytes);
+
+        let verifier_challenge_int = BigUint::from_bytes_le(&verifier_challenge_bytes);
+        let check = (g.0.modpow(&z, &n.0) * h.0.modpow(&verifier_challenge_int, &n.0)) % &n.0;
+        assert_eq!(u.0, check);
+    }
+
     /// Schnorr proof as a struct
     #[derive(Inscribe)]
     struct SchnorrProof {
@@ -212,10 +1542,10 @@ mod tests {
         let randomizer = base.modpow(&randomizer_exp, &modulus);
 
         let mut proof = SchnorrProof {
-            base: base,
-            target: target,
-            modulus: modulus,
-            randomizer: randomizer,
+            base,
+            target,
+            modulus,
+            randomizer,
             z: BigInt::from(0u32),
         };
 
@@ -236,7 +1566,6 @@ mod tests {
 
     fn schnorr_verify(proof: &SchnorrProof) -> bool {
         use decree::decree::Decree;
-        use num_traits::sign::Signed;
         let mut transcript = Decree::new(
             "schnorr proof",
             vec!["proof_data"].as_slice(),
@@ -256,4 +1585,110 @@ mod tests {
         let proof = schnorr_prove();
         assert!(schnorr_verify(&proof));
     }
+
+    /// A struct with an unannotated marker member
+    #[derive(Inscribe)]
+    #[inscribe_mark("Tagged")]
+    struct WithMarker {
+        #[inscribe(serialize)]
+        value: u64,
+        marker: std::marker::PhantomData<u32>,
+    }
+
+    /// The same struct, without the marker
+    #[derive(Inscribe)]
+    #[inscribe_mark("Tagged")]
+    struct WithoutMarker {
+        #[inscribe(serialize)]
+        value: u64,
+    }
+
+    /// A struct with bound names and an unannotated marker member
+    #[derive(Inscribe)]
+    #[inscribe(bind_names)]
+    #[inscribe_mark("Tagged")]
+    struct BoundWithMarker {
+        #[inscribe(serialize)]
+        value: u64,
+        marker: std::marker::PhantomData<u32>,
+    }
+
+    /// The same struct, without the marker
+    #[derive(Inscribe)]
+    #[inscribe(bind_names)]
+    #[inscribe_mark("Tagged")]
+    struct BoundWithoutMarker {
+        #[inscribe(serialize)]
+        value: u64,
+    }
+
+    #[test]
+    /// Test that `PhantomData` members contribute nothing to the inscription
+    fn test_phantom_data_member() {
+        let with_marker = WithMarker { value: 5u64, marker: std::marker::PhantomData };
+        let without_marker = WithoutMarker { value: 5u64 };
+        assert_eq!(with_marker.get_inscription().unwrap(), without_marker.get_inscription().unwrap());
+        assert!(with_marker.marker.get_inscription().unwrap().is_empty());
+        assert_eq!(with_marker.marker.inscription_length(), 0);
+
+        // Not even their names, with `bind_names`
+        let bound_with_marker = BoundWithMarker { value: 5u64, marker: std::marker::PhantomData };
+        let bound_without_marker = BoundWithoutMarker { value: 5u64 };
+        assert_eq!(bound_with_marker.get_inscription().unwrap(), bound_without_marker.get_inscription().unwrap());
+        assert_ne!(bound_with_marker.get_inscription().unwrap(), with_marker.get_inscription().unwrap());
+    }
+
+    /// A curve parameter for generic protocol structs
+    trait Curve {
+        const NAME: &'static str;
+    }
+
+    struct Ristretto;
+
+    impl Curve for Ristretto {
+        const NAME: &'static str = "ristretto";
+    }
+
+    /// A generic protocol struct that carries its curve as a marker
+    #[derive(Inscribe)]
+    #[inscribe(bind_names)]
+    #[inscribe_mark("Tagged")]
+    struct GenericWithMarker<C: Curve> {
+        #[inscribe(serialize)]
+        value: u64,
+        marker: std::marker::PhantomData<C>,
+    }
+
+    /// A generic struct with a `where` clause and a generic member
+    #[derive(Inscribe)]
+    #[inscribe_mark("Generic")]
+    struct GenericWithWhere<C, P>
+    where
+        C: Curve,
+        P: Inscribe,
+    {
+        point: P,
+        marker: std::marker::PhantomData<C>,
+    }
+
+    #[test]
+    /// Test that structs with bounded type parameters and `where` clauses can be derived, and that
+    /// a `PhantomData` of the type parameter contributes nothing
+    fn test_generic_phantom_data_member() {
+        let generic = GenericWithMarker::<Ristretto> { value: 5u64, marker: std::marker::PhantomData };
+        let bound_without_marker = BoundWithoutMarker { value: 5u64 };
+        assert_eq!(Ristretto::NAME, "ristretto");
+        assert_eq!(generic.get_inscription().unwrap(), bound_without_marker.get_inscription().unwrap());
+
+        let with_where = GenericWithWhere::<Ristretto, _> {
+            point: WithoutMarker { value: 5u64 },
+            marker: std::marker::PhantomData,
+        };
+        let mut tuplehasher = TupleHash::v256("Generic".as_bytes());
+        tuplehasher.update(&WithoutMarker { value: 5u64 }.get_inscription().unwrap());
+        tuplehasher.update(&[]);
+        let mut buffer: [u8; INSCRIBE_LENGTH] = [0u8; INSCRIBE_LENGTH];
+        tuplehasher.finalize(&mut buffer);
+        assert_eq!(with_where.get_inscription().unwrap(), buffer.to_vec());
+    }
 }
\ No newline at end of file
@@ -0,0 +1,27 @@
+//! Tests of label interning on deserialization. These live in their own test binary, since they
+//! exhaust the process-wide table of interned labels.
+#![cfg(feature = "serde")]
+use decree::decree::{Decree, InputLabel, ChallengeLabel};
+use decree::error::DecreeResult;
+
+#[test]
+/// Deserializing state with ever more distinct labels fails once the interning limit is reached,
+/// while labels that were already interned keep working
+fn test_intern_limit() -> DecreeResult<()> {
+    let name: &'static str = Box::leak("n".repeat(1000).into_boxed_str());
+    let inputs: [InputLabel; 1] = ["input1"];
+    let challenges: [ChallengeLabel; 1] = ["challenge1"];
+    let saved = bcs::to_bytes(&Decree::new(name, &inputs, &challenges)?).unwrap();
+    let name_start = saved.windows(name.len()).position(|window| window == name.as_bytes()).unwrap();
+
+    let renamed = |index: usize| {
+        let mut renamed = saved.clone();
+        renamed[name_start..name_start + 4].copy_from_slice(format!("{index:04}").as_bytes());
+        bcs::from_bytes::<Decree>(&renamed)
+    };
+    let first_failure = (0..1000).find(|index| renamed(*index).is_err()).unwrap();
+    assert!(first_failure > 0);
+    assert!(renamed(first_failure + 1).is_err());
+    assert_eq!(renamed(0).unwrap().name(), format!("0000{}", &name[4..]));
+    Ok(())
+}
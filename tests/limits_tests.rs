@@ -0,0 +1,45 @@
+//! Tests of the process-wide limits. These live in their own test binary, since they change
+//! global state that every `Decree::new` in the process reads.
+use std::sync::Mutex;
+use std::thread;
+use decree::decree::{Decree, DecreeLimits, InputLabel, ChallengeLabel};
+use decree::error::DecreeResult;
+
+// Serializes the tests in this binary, since they all change the global limits
+static GLOBAL_STATE: Mutex<()> = Mutex::new(());
+
+#[test]
+/// Global limits apply to `new`
+fn test_global_limits() -> DecreeResult<()> {
+    let _guard = GLOBAL_STATE.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    let inputs: [InputLabel; 2] = ["input1", "input2"];
+    let challenges: [ChallengeLabel; 1] = ["challenge1"];
+
+    DecreeLimits::set_global(DecreeLimits { max_inputs: 1, ..DecreeLimits::default() });
+    assert_eq!(DecreeLimits::global().max_inputs, 1);
+    assert!(Decree::new("testname", &inputs, &challenges).is_err());
+
+    DecreeLimits::set_global(DecreeLimits::default());
+    Decree::new("testname", &inputs, &challenges)?;
+    Ok(())
+}
+
+#[test]
+/// Readers never see a mix of two sets of limits while they are being replaced
+fn test_global_limits_atomic() {
+    let _guard = GLOBAL_STATE.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    let strict = DecreeLimits { max_inputs: 1, max_challenges: 1, max_input_bytes: 1 };
+    let loose = DecreeLimits::default();
+
+    let writer = thread::spawn(move || {
+        for round in 0..10_000 {
+            DecreeLimits::set_global(if round % 2 == 0 { strict } else { loose });
+        }
+    });
+    while !writer.is_finished() {
+        let limits = DecreeLimits::global();
+        assert!(limits == strict || limits == loose, "Torn limits: {:?}", limits);
+    }
+    writer.join().unwrap();
+    DecreeLimits::set_global(DecreeLimits::default());
+}
@@ -0,0 +1,70 @@
+#![cfg(feature = "fuzzing")]
+
+#[cfg(test)]
+mod tests {
+    use decree::decree::Decree;
+    use proptest::prelude::*;
+
+    const INPUTS: [&str; 3] = ["input0", "input1", "input2"];
+    const CHALLENGES: [&str; 2] = ["challenge0", "challenge1"];
+
+    // A single call against the `Decree`. Label indices past the end of the specification pick
+    // an unknown label.
+    #[derive(Clone, Debug)]
+    enum Op {
+        AddBytes(usize, Vec<u8>),
+        GetChallenge(usize),
+    }
+
+    fn op() -> impl Strategy<Value = Op> {
+        prop_oneof![
+            (0..=INPUTS.len(), prop::collection::vec(any::<u8>(), 0..8))
+                .prop_map(|(index, bytes)| Op::AddBytes(index, bytes)),
+            (0..=CHALLENGES.len()).prop_map(Op::GetChallenge),
+        ]
+    }
+
+    fn input_label(index: usize) -> &'static str {
+        INPUTS.get(index).copied().unwrap_or("unknown_input")
+    }
+
+    fn challenge_label(index: usize) -> &'static str {
+        CHALLENGES.get(index).copied().unwrap_or("unknown_challenge")
+    }
+
+    proptest! {
+        #[test]
+        /// No sequence of `add_bytes` and `get_challenge` calls produces a challenge before every
+        /// input has been committed, and no call leaves the `Decree` in an inconsistent state
+        fn prop_no_challenge_before_commitment(
+                prefilled in prop::collection::vec(0..INPUTS.len(), 0..3),
+                ops in prop::collection::vec(op(), 0..16)) {
+            let inputs: Vec<String> = INPUTS.iter().map(|label| label.to_string()).collect();
+            let challenges: Vec<String> = CHALLENGES.iter().map(|label| label.to_string()).collect();
+            let values: Vec<(String, Vec<u8>)> = prefilled.iter()
+                .map(|index| (input_label(*index).to_string(), vec![*index as u8]))
+                .collect();
+
+            // Repeated prefilled labels are rejected up front
+            let mut decree = match Decree::from_parts("proptest", &inputs, &challenges, &values) {
+                Ok(decree) => decree,
+                Err(_) => return Ok(()),
+            };
+            prop_assert!(decree.check_invariants().is_ok());
+
+            for op in ops.iter() {
+                match op {
+                    Op::AddBytes(index, bytes) => {
+                        let _ = decree.add_bytes(input_label(*index), bytes);
+                    },
+                    Op::GetChallenge(index) => {
+                        let complete = decree.missing_inputs().is_empty();
+                        let result = decree.get_challenge_vec(challenge_label(*index), 32);
+                        prop_assert!(result.is_err() || (complete && decree.is_committed()));
+                    },
+                }
+                prop_assert!(decree.check_invariants().is_ok(), "{:?}", decree.check_invariants());
+            }
+        }
+    }
+}
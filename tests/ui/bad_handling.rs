@@ -0,0 +1,9 @@
+use decree::Inscribe;
+
+#[derive(Inscribe)]
+struct BadHandling {
+    #[inscribe(bogus)]
+    x: u32,
+}
+
+fn main() {}
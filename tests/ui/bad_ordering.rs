@@ -0,0 +1,10 @@
+use decree::Inscribe;
+
+#[derive(Inscribe)]
+#[inscribe(sorted)]
+struct BadOrdering {
+    #[inscribe(serialize)]
+    x: u32,
+}
+
+fn main() {}
@@ -0,0 +1,10 @@
+use decree::Inscribe;
+
+#[derive(Inscribe)]
+enum DuplicateDiscriminant {
+    First,
+    #[inscribe_discriminant(0)]
+    Second,
+}
+
+fn main() {}
@@ -0,0 +1,11 @@
+use decree::Inscribe;
+
+#[derive(Inscribe)]
+#[inscribe_mark(first_mark)]
+#[inscribe_mark(second_mark)]
+struct DuplicateMark {
+    #[inscribe(serialize)]
+    x: u32,
+}
+
+fn main() {}
@@ -0,0 +1,13 @@
+use decree::Inscribe;
+
+#[derive(Inscribe)]
+struct DuplicateName {
+    #[inscribe(serialize)]
+    #[inscribe_name(shared)]
+    x: u32,
+    #[inscribe(serialize)]
+    #[inscribe_name(shared)]
+    y: u32,
+}
+
+fn main() {}
@@ -0,0 +1,16 @@
+use decree::Inscribe;
+
+#[derive(Inscribe)]
+#[inscribe(ordered)]
+struct DuplicateOrderedName {
+    #[inscribe(serialize)]
+    #[inscribe_name(shared)]
+    x: u32,
+    #[inscribe(serialize)]
+    y: u32,
+    #[inscribe(serialize)]
+    #[inscribe_name(shared)]
+    z: u32,
+}
+
+fn main() {}
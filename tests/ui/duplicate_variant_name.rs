@@ -0,0 +1,10 @@
+use decree::Inscribe;
+
+#[derive(Inscribe)]
+enum DuplicateVariantName {
+    #[inscribe_name("first")]
+    #[inscribe_name("second")]
+    First,
+}
+
+fn main() {}
@@ -0,0 +1,9 @@
+use decree::Inscribe;
+
+#[derive(Inscribe)]
+union Overlap {
+    x: u32,
+    y: f32,
+}
+
+fn main() {}
@@ -0,0 +1,6 @@
+use decree::Inscribe;
+
+#[derive(Inscribe)]
+struct Empty;
+
+fn main() {}
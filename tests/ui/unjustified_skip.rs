@@ -0,0 +1,12 @@
+use decree::Inscribe;
+
+#[derive(Inscribe)]
+#[inscribe(deny_unjustified_skip)]
+struct UnjustifiedSkip {
+    #[inscribe(serialize)]
+    x: u32,
+    #[inscribe(skip)]
+    cache: Vec<u8>,
+}
+
+fn main() {}
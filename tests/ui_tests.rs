@@ -0,0 +1,7 @@
+#[test]
+/// Malformed `derive(Inscribe)` input should produce compile errors that point at the offending
+/// tokens.
+fn test_derive_diagnostics() {
+    let t = trybuild::TestCases::new();
+    t.compile_fail("tests/ui/*.rs");
+}
@@ -0,0 +1,112 @@
+//! Tests that inputs are wiped from memory. These live in their own test binary, since they
+//! install a global allocator that inspects every buffer as it is freed.
+#![cfg(feature = "zeroize")]
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use decree::decree::{Decree, DecreeCheckpoint, InputLabel, ChallengeLabel};
+use decree::error::DecreeResult;
+
+// A pattern that only appears in memory where it was put by the test
+const SECRET: [u8; 32] = *b"zeroize-test-secret-0123456789ab";
+
+// Counts freed buffers that still hold `SECRET`, while enabled.
+struct CheckingAllocator;
+
+static CHECKING: AtomicBool = AtomicBool::new(false);
+static LEAKED: AtomicUsize = AtomicUsize::new(0);
+// Serializes the tests in this binary, since they share the counter
+static COUNTING: Mutex<()> = Mutex::new(());
+
+unsafe impl GlobalAlloc for CheckingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        if CHECKING.load(Ordering::SeqCst) {
+            // The buffer is still allocated until it is handed back below
+            let freed = std::slice::from_raw_parts(ptr, layout.size());
+            if freed.windows(SECRET.len()).any(|window| window == SECRET) {
+                LEAKED.fetch_add(1, Ordering::SeqCst);
+            }
+        }
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CheckingAllocator = CheckingAllocator;
+
+// Runs `body` and returns how many freed buffers still held `SECRET`.
+fn count_leaks(body: impl FnOnce() -> DecreeResult<()>) -> DecreeResult<usize> {
+    let _guard = COUNTING.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    LEAKED.store(0, Ordering::SeqCst);
+    CHECKING.store(true, Ordering::SeqCst);
+    let result = body();
+    CHECKING.store(false, Ordering::SeqCst);
+    result.map(|()| LEAKED.load(Ordering::SeqCst))
+}
+
+#[test]
+/// Inputs are wiped when a `Decree`, its clones, and its checkpoints are dropped, and the check
+/// does catch buffers that aren't
+fn test_zeroize_on_drop() -> DecreeResult<()> {
+    fn assert_zeroize_on_drop<T: zeroize::ZeroizeOnDrop>(_: &T) {}
+
+    let inputs: [InputLabel; 2] = ["witness", "public"];
+    let challenges: [ChallengeLabel; 1] = ["challenge1"];
+    let round_inputs: [InputLabel; 1] = ["round"];
+
+    let control = count_leaks(|| {
+        drop(SECRET.to_vec());
+        Ok(())
+    })?;
+    assert_eq!(control, 1);
+
+    let leaks = count_leaks(|| {
+        let mut my_decree = Decree::new("testname", &inputs, &challenges)?;
+        my_decree.add_bytes("witness", &SECRET)?;
+        let checkpoint: DecreeCheckpoint = my_decree.checkpoint();
+        assert_zeroize_on_drop(&my_decree);
+        assert_zeroize_on_drop(&checkpoint);
+        let fork = my_decree.clone();
+        my_decree.add_bytes("public", &[1u8; 4])?;
+        let _: [u8; 32] = my_decree.challenge("challenge1")?;
+        my_decree.extend_keeping(&round_inputs, &challenges, &["witness"])?;
+        drop((my_decree, checkpoint, fork));
+        Ok(())
+    })?;
+    assert_eq!(leaks, 0);
+    Ok(())
+}
+
+#[test]
+#[cfg(feature = "serde")]
+/// The copies of inputs made while serializing and deserializing are wiped too; the serialized
+/// bytes themselves are the caller's responsibility
+fn test_zeroize_serde() -> DecreeResult<()> {
+    use zeroize::Zeroize;
+
+    let inputs: [InputLabel; 2] = ["witness", "public"];
+    let challenges: [ChallengeLabel; 1] = ["challenge1"];
+    let round_inputs: [InputLabel; 1] = ["round"];
+
+    let leaks = count_leaks(|| {
+        let mut my_decree = Decree::new("testname", &inputs, &challenges)?;
+        my_decree.add_bytes("witness", &SECRET)?;
+        my_decree.add_bytes("public", &[1u8; 4])?;
+        let _: [u8; 32] = my_decree.challenge("challenge1")?;
+        my_decree.extend(&round_inputs, &challenges)?;
+
+        // Sized up front, so that no partly written copy is freed along the way
+        let mut saved = Vec::with_capacity(bcs::serialized_size(&my_decree).unwrap());
+        bcs::serialize_into(&mut saved, &my_decree).unwrap();
+        let restored: Decree = bcs::from_bytes(&saved).unwrap();
+        saved.zeroize();
+        drop((my_decree, restored, saved));
+        Ok(())
+    })?;
+    assert_eq!(leaks, 0);
+    Ok(())
+}